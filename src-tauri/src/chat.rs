@@ -8,6 +8,8 @@ use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use tokio::time::sleep;
 
+use crate::plan::TaskStatus;
+
 /// Stream event types matching the frontend types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -26,7 +28,7 @@ pub enum StreamEventType {
 pub struct PlanUpdate {
     pub node_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub status: Option<String>,
+    pub status: Option<TaskStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
 }
@@ -52,25 +54,44 @@ const CANNED_RESPONSES: &[&str] = &[
     "Let me help you break down this task into smaller, manageable pieces. This will make tracking progress easier and help identify bottlenecks early.",
 ];
 
+/// Task ID mentioned in a message (e.g., "mark t1 complete")
+fn mentioned_task(message: &str) -> Option<&str> {
+    message
+        .split_whitespace()
+        .find(|word| word.starts_with('t') && word[1..].chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Words that turn the command word after them around ("don't cancel")
+const NEGATIONS: &[&str] = &["don't", "dont", "not", "never", "no"];
+
 /// Check if the message is a plan update command
-fn is_plan_update_command(message: &str) -> Option<(&str, &str)> {
-    let lower = message.to_lowercase();
-    if lower.contains("mark") && lower.contains("complete") {
-        // Extract task ID if mentioned (e.g., "mark t1 complete")
-        for word in message.split_whitespace() {
-            if word.starts_with('t') && word[1..].chars().all(|c| c.is_ascii_digit()) {
-                return Some((word, "completed"));
-            }
-        }
-    }
-    if lower.contains("start") || lower.contains("begin") {
-        for word in message.split_whitespace() {
-            if word.starts_with('t') && word[1..].chars().all(|c| c.is_ascii_digit()) {
-                return Some((word, "in_progress"));
-            }
-        }
-    }
-    None
+/// Command words match whole words only, and not right after a negation
+fn is_plan_update_command(message: &str) -> Option<(&str, TaskStatus)> {
+    let lower = message.to_lowercase().replace('\u{2019}', "'");
+    let words: Vec<&str> = lower
+        .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let said = |options: &[&str]| {
+        words.iter().enumerate().any(|(i, word)| {
+            options.contains(word) && (i == 0 || !NEGATIONS.contains(&words[i - 1]))
+        })
+    };
+
+    let status = if said(&["mark"]) && said(&["complete", "completed"]) {
+        TaskStatus::Completed
+    } else if said(&["cancel", "cancelled", "canceled"]) {
+        TaskStatus::Cancelled
+    } else if said(&["unblock", "unblocked"]) {
+        TaskStatus::Pending
+    } else if said(&["block", "blocked"]) {
+        TaskStatus::Blocked
+    } else if said(&["start", "started", "begin"]) {
+        TaskStatus::InProgress
+    } else {
+        return None;
+    };
+    mentioned_task(message).map(|task_id| (task_id, status))
 }
 
 /// Send a chat message and receive a streaming response
@@ -90,7 +111,13 @@ pub async fn send_chat_message(app: AppHandle, message: String) -> Result<(), St
     let response = if let Some((task_id, status)) = plan_update {
         format!(
             "I'll {} task {} for you.\n\n{}",
-            if status == "completed" { "mark as complete" } else { "start" },
+            match status {
+                TaskStatus::Completed => "mark as complete",
+                TaskStatus::Cancelled => "cancel",
+                TaskStatus::Blocked => "mark as blocked",
+                TaskStatus::Pending => "unblock",
+                _ => "start",
+            },
             task_id,
             base_response
         )
@@ -149,7 +176,7 @@ pub async fn send_chat_message(app: AppHandle, message: String) -> Result<(), St
             content: None,
             plan_update: Some(PlanUpdate {
                 node_id: task_id.to_string(),
-                status: Some(status.to_string()),
+                status: Some(status),
                 content: None,
             }),
         })?;
@@ -198,7 +225,7 @@ mod tests {
             content: None,
             plan_update: Some(PlanUpdate {
                 node_id: "t1".to_string(),
-                status: Some("completed".to_string()),
+                status: Some(TaskStatus::Completed),
                 content: None,
             }),
         };
@@ -207,5 +234,32 @@ mod tests {
         assert!(json.contains("plan_update"));
         assert!(json.contains("nodeId")); // camelCase
         assert!(json.contains("t1"));
+        assert!(json.contains("\"status\":\"completed\""));
+    }
+
+    #[test]
+    fn test_plan_update_commands() {
+        assert_eq!(
+            is_plan_update_command("mark t1 complete"),
+            Some(("t1", TaskStatus::Completed))
+        );
+        assert_eq!(
+            is_plan_update_command("t2 is blocked on the vendor"),
+            Some(("t2", TaskStatus::Blocked))
+        );
+        assert_eq!(
+            is_plan_update_command("cancel t3"),
+            Some(("t3", TaskStatus::Cancelled))
+        );
+        assert_eq!(is_plan_update_command("cancel everything"), None);
+        assert_eq!(
+            is_plan_update_command("unblock t2"),
+            Some(("t2", TaskStatus::Pending))
+        );
+        assert_eq!(
+            is_plan_update_command("don't cancel, start t3"),
+            Some(("t3", TaskStatus::InProgress))
+        );
+        assert_eq!(is_plan_update_command("restart the blocker t4"), None);
     }
 }
@@ -4,8 +4,16 @@
 //! - `--plan path/to/plan.md` - Open a specific plan file
 //! - `--agent claude-code|codex|opencode` - Pre-select an agent
 //! - `--cwd /path/to/dir` - Set working directory
+//!
+//! Only what was given on the command line ends up in the launch config; the
+//! frontend asks `get_plan_defaults` for the plan's own agent and working directory.
+//!
+//! Subcommands run without opening a window:
+//! - `lint path/to/plan.md [--fix] [--json]` - Report plan diagnostics; `--fix` first
+//!   assigns missing task IDs and renumbers phases
+//! - `stats path/to/plan.md [--days N] [--json]` - Report plan progress
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Mutex;
@@ -27,6 +35,41 @@ pub struct CliArgs {
     /// Working directory for the agent
     #[arg(long, short = 'c')]
     pub cwd: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Headless subcommands
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Check a plan for problems and print diagnostics
+    Lint {
+        /// Path to the plan.md file
+        plan: PathBuf,
+
+        /// Assign IDs to tasks without one and renumber phases before linting
+        #[arg(long)]
+        fix: bool,
+
+        /// Print diagnostics as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print progress statistics for a plan
+    Stats {
+        /// Path to the plan.md file
+        plan: PathBuf,
+
+        /// Also count tasks completed in this many days, using plan history
+        #[arg(long)]
+        days: Option<u32>,
+
+        /// Print statistics as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 /// Launch configuration passed to the frontend
@@ -41,7 +84,7 @@ pub struct LaunchConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agent: Option<String>,
 
-    /// Working directory
+    /// Working directory (if specified via CLI)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cwd: Option<String>,
 
@@ -76,6 +119,15 @@ impl Default for LaunchConfigState {
 pub fn parse_args() -> LaunchConfig {
     let args = CliArgs::parse();
 
+    if let Some(command) = args.command {
+        std::process::exit(run_command(command));
+    }
+
+    launch_config(args)
+}
+
+/// Build the launch config from CLI arguments
+fn launch_config(args: CliArgs) -> LaunchConfig {
     let from_cli = args.plan.is_some() || args.agent.is_some() || args.cwd.is_some();
 
     LaunchConfig {
@@ -86,6 +138,84 @@ pub fn parse_args() -> LaunchConfig {
     }
 }
 
+/// Run a headless subcommand and return the process exit code
+fn run_command(command: Command) -> i32 {
+    match command {
+        Command::Lint { plan, fix, json } => {
+            let path = plan.to_string_lossy().to_string();
+            if fix {
+                match crate::plan::normalize::normalize_file(&path, None) {
+                    Ok(result) => {
+                        for change in &result.changes {
+                            let text = format!("{}: fixed {}", path, change.describe());
+                            // Keep stdout valid JSON
+                            if json {
+                                eprintln!("{}", text);
+                            } else {
+                                println!("{}", text);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return 2;
+                    }
+                }
+            }
+
+            let diagnostics = match crate::lint::lint_file(&path) {
+                Ok(d) => d,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return 2;
+                }
+            };
+
+            if json {
+                match serde_json::to_string_pretty(&diagnostics) {
+                    Ok(text) => println!("{}", text),
+                    Err(e) => {
+                        eprintln!("Failed to serialize diagnostics: {}", e);
+                        return 2;
+                    }
+                }
+            } else {
+                for diagnostic in &diagnostics {
+                    println!("{}", crate::lint::format_diagnostic(&path, diagnostic));
+                }
+            }
+
+            let has_errors = diagnostics
+                .iter()
+                .any(|d| d.severity == crate::lint::Severity::Error);
+            i32::from(has_errors)
+        }
+        Command::Stats { plan, days, json } => {
+            let path = plan.to_string_lossy().to_string();
+            let stats = match crate::plan::stats::plan_stats(&path, days) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return 2;
+                }
+            };
+
+            if json {
+                match serde_json::to_string_pretty(&stats) {
+                    Ok(text) => println!("{}", text),
+                    Err(e) => {
+                        eprintln!("Failed to serialize statistics: {}", e);
+                        return 2;
+                    }
+                }
+            } else {
+                print!("{}", crate::plan::stats::format_stats(&stats));
+            }
+            0
+        }
+    }
+}
+
 /// Get the launch configuration
 #[tauri::command]
 pub fn get_launch_config(state: tauri::State<'_, LaunchConfigState>) -> LaunchConfig {
@@ -145,4 +275,47 @@ mod tests {
         assert_eq!(normalize_agent_name("claude-code"), "claude_code");
         assert_eq!(normalize_agent_name("codex"), "codex");
     }
+
+    #[test]
+    fn test_lint_subcommand_parsing() {
+        let args = CliArgs::parse_from(["plan-visualizer", "lint", "plan.md", "--json"]);
+        match args.command {
+            Some(Command::Lint { plan, fix, json }) => {
+                assert_eq!(plan, PathBuf::from("plan.md"));
+                assert!(!fix);
+                assert!(json);
+            }
+            _ => panic!("expected lint subcommand"),
+        }
+
+        let args = CliArgs::parse_from(["plan-visualizer", "--plan", "plan.md"]);
+        assert!(args.command.is_none());
+    }
+
+    #[test]
+    fn test_launch_config_ignores_front_matter() {
+        let temp = tempfile::tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        std::fs::write(&plan_path, "---\nagent: codex\ncwd: app\n---\n# Plan: X\n").unwrap();
+        let plan = plan_path.to_string_lossy().to_string();
+
+        // Front matter is left to get_plan_defaults
+        let config = launch_config(CliArgs::parse_from(["plan-visualizer", "--plan", &plan]));
+        assert_eq!(config.plan_path.as_deref(), Some(plan.as_str()));
+        assert!(config.agent.is_none());
+        assert!(config.cwd.is_none());
+        assert!(config.from_cli);
+
+        let config = launch_config(CliArgs::parse_from([
+            "plan-visualizer",
+            "--plan",
+            &plan,
+            "--agent",
+            "opencode",
+            "--cwd",
+            "/work",
+        ]));
+        assert_eq!(config.agent.as_deref(), Some("opencode"));
+        assert_eq!(config.cwd.as_deref(), Some("/work"));
+    }
 }
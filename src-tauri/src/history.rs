@@ -1,21 +1,161 @@
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::layout::LayoutFile;
+use sha2::{Digest, Sha256};
+
+use crate::layout::{self, LayoutFile};
+use crate::preferences;
+
+pub mod gc;
+pub mod git;
+pub mod replay;
+
+use git::GitHistory;
 
 /// Maximum number of snapshots to retain per plan
 const MAX_SNAPSHOTS: usize = 5;
 
+/// Maximum number of plan content snapshots to retain per plan
+/// Plan snapshots are small and feed progress replay, so more are kept
+const MAX_PLAN_SNAPSHOTS: usize = 200;
+
 /// Minimum interval between time-based snapshots (in seconds)
 const MIN_SNAPSHOT_INTERVAL_SECS: u64 = 300; // 5 minutes
 
-/// Get the history directory path for a given plan path
-/// Creates .plan-history/ in the same directory as the plan file
-pub fn get_history_dir(plan_path: &str) -> PathBuf {
+/// Name of the in-repository history directory
+const REPO_HISTORY_DIR: &str = ".plan-history";
+
+/// Metadata file recording which plan a centralized history directory belongs to
+const HISTORY_META_FILE: &str = "plan.json";
+
+/// Where snapshots and checkpoints are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryStorage {
+    /// Under the app data directory, keyed by canonical plan path
+    #[default]
+    AppData,
+    /// In a .plan-history/ directory next to the plan file (opt-in)
+    Repository,
+    /// As commits on refs/planviz/history in the plan's git repository
+    /// Checkpoints, and plans outside a repository, use app data storage
+    Git,
+}
+
+/// Metadata stored alongside centralized history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryMeta {
+    /// Canonical path of the plan this history belongs to
+    pub plan_path: String,
+}
+
+/// Root directory for centralized history storage
+#[cfg(not(test))]
+fn app_data_history_root() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("plan-visualizer").join("history"))
+}
+
+/// Tests keep centralized history out of the real app data directory
+#[cfg(test)]
+fn app_data_history_root() -> Option<PathBuf> {
+    Some(std::env::temp_dir().join("plan-visualizer-test-history"))
+}
+
+/// Resolve a plan path to its canonical absolute form
+/// Falls back to canonicalizing the parent when the plan doesn't exist yet
+pub fn canonical_plan_path(plan_path: &str) -> PathBuf {
+    let plan = Path::new(plan_path);
+    if let Ok(canonical) = fs::canonicalize(plan) {
+        return canonical;
+    }
+
+    let parent = match plan.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    match (fs::canonicalize(parent), plan.file_name()) {
+        (Ok(parent), Some(name)) => parent.join(name),
+        _ => plan.to_path_buf(),
+    }
+}
+
+/// Get the stable storage key for a plan: `<filename>-<path hash>`
+fn get_history_key(plan_path: &str) -> String {
+    let canonical = canonical_plan_path(plan_path);
+    let filename = canonical.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let digest = Sha256::digest(canonical.to_string_lossy().as_bytes());
+    let hash: String = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}", filename, hash)
+}
+
+/// Get the in-repository history directory for a plan
+/// This is .plan-history/ in the same directory as the plan file
+pub fn get_repo_history_dir(plan_path: &str) -> PathBuf {
     let plan = Path::new(plan_path);
     let parent = plan.parent().unwrap_or(Path::new("."));
-    parent.join(".plan-history")
+    parent.join(REPO_HISTORY_DIR)
+}
+
+/// Get the history directory for a plan under a specific storage location
+pub fn get_history_dir_for(plan_path: &str, storage: HistoryStorage) -> PathBuf {
+    match storage {
+        HistoryStorage::Repository => get_repo_history_dir(plan_path),
+        HistoryStorage::AppData | HistoryStorage::Git => match app_data_history_root() {
+            Some(root) => root.join(get_history_key(plan_path)),
+            // No data directory on this platform, fall back to the repository
+            None => get_repo_history_dir(plan_path),
+        },
+    }
+}
+
+/// Get the history directory path for a given plan path
+/// Uses the storage location configured in preferences
+pub fn get_history_dir(plan_path: &str) -> PathBuf {
+    get_history_dir_for(plan_path, preferences::get_history_storage())
+}
+
+/// Get git-backed history for a plan, if git storage is enabled and the plan is in a repository
+fn get_git_history(plan_path: &str) -> Option<GitHistory> {
+    if preferences::get_history_storage() != HistoryStorage::Git {
+        return None;
+    }
+    GitHistory::open(plan_path)
+}
+
+/// Create a history directory if needed
+/// Centralized directories also record which plan they belong to
+fn init_history_dir(history_dir: &Path, plan_path: &str) -> Result<(), String> {
+    if !history_dir.exists() {
+        fs::create_dir_all(history_dir)
+            .map_err(|e| format!("Failed to create history directory: {}", e))?;
+    }
+
+    if history_dir == get_repo_history_dir(plan_path) {
+        return Ok(());
+    }
+
+    let meta_path = history_dir.join(HISTORY_META_FILE);
+    if !meta_path.exists() {
+        let meta = HistoryMeta {
+            plan_path: canonical_plan_path(plan_path).to_string_lossy().to_string(),
+        };
+        let content = serde_json::to_string_pretty(&meta)
+            .map_err(|e| format!("Failed to serialize history metadata: {}", e))?;
+        fs::write(&meta_path, content)
+            .map_err(|e| format!("Failed to write history metadata: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Create the history directory for a plan if needed
+fn ensure_history_dir(plan_path: &str) -> Result<PathBuf, String> {
+    let history_dir = get_history_dir(plan_path);
+    init_history_dir(&history_dir, plan_path)?;
+    Ok(history_dir)
 }
 
 /// Get the snapshot filename prefix for a plan
@@ -26,48 +166,68 @@ fn get_snapshot_prefix(plan_path: &str) -> String {
     format!("{}.layout", filename)
 }
 
+/// Get the checkpoint filename prefix for a plan
+/// Distinct from the snapshot prefix so rotation never matches checkpoints
+fn get_checkpoint_prefix(plan_path: &str) -> String {
+    let plan = Path::new(plan_path);
+    let filename = plan.file_name().unwrap_or_default().to_string_lossy();
+    format!("{}.checkpoint", filename)
+}
+
+/// Get the plan content snapshot filename prefix for a plan
+fn get_plan_snapshot_prefix(plan_path: &str) -> String {
+    let plan = Path::new(plan_path);
+    let filename = plan.file_name().unwrap_or_default().to_string_lossy();
+    format!("{}.plan", filename)
+}
+
+/// Current time in milliseconds since the Unix epoch
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
 /// Generate a timestamped snapshot filename
 fn generate_snapshot_filename(plan_path: &str) -> String {
     let prefix = get_snapshot_prefix(plan_path);
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis();
-    format!("{}.{}.json", prefix, timestamp)
+    format!("{}.{}.json", prefix, now_millis())
 }
 
-/// List all snapshots for a given plan, sorted by timestamp (oldest first)
-pub fn list_snapshots(plan_path: &str) -> Result<Vec<PathBuf>, String> {
+/// List history files named `<prefix>.<timestamp>.<extension>`, sorted oldest first
+fn list_history_files(
+    plan_path: &str,
+    prefix: &str,
+    extension: &str,
+) -> Result<Vec<PathBuf>, String> {
     let history_dir = get_history_dir(plan_path);
-    let prefix = get_snapshot_prefix(plan_path);
+    let prefix = format!("{}.", prefix);
+    let suffix = format!(".{}", extension);
 
     if !history_dir.exists() {
         return Ok(Vec::new());
     }
 
-    let mut snapshots: Vec<PathBuf> = fs::read_dir(&history_dir)
+    let mut files: Vec<PathBuf> = fs::read_dir(&history_dir)
         .map_err(|e| format!("Failed to read history directory: {}", e))?
         .filter_map(|entry| entry.ok())
         .map(|entry| entry.path())
         .filter(|path| {
             path.file_name()
                 .and_then(|n| n.to_str())
-                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".json"))
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(&suffix))
         })
         .collect();
 
     // Sort by filename (which includes timestamp) - oldest first
-    snapshots.sort();
-    Ok(snapshots)
+    files.sort();
+    Ok(files)
 }
 
-/// Get the timestamp from the most recent snapshot (if any)
-fn get_latest_snapshot_time(plan_path: &str) -> Option<u64> {
-    let snapshots = list_snapshots(plan_path).ok()?;
-    let latest = snapshots.last()?;
-    let filename = latest.file_name()?.to_str()?;
-
-    // Extract timestamp from filename: prefix.TIMESTAMP.json
+/// Extract the timestamp from a `prefix.TIMESTAMP.ext` history filename
+fn parse_snapshot_timestamp(path: &Path) -> Option<u64> {
+    let filename = path.file_name()?.to_str()?;
     let parts: Vec<&str> = filename.rsplitn(3, '.').collect();
     if parts.len() >= 3 {
         parts[1].parse().ok()
@@ -76,18 +236,33 @@ fn get_latest_snapshot_time(plan_path: &str) -> Option<u64> {
     }
 }
 
+/// List all snapshots for a given plan, sorted by timestamp (oldest first)
+pub fn list_snapshots(plan_path: &str) -> Result<Vec<PathBuf>, String> {
+    list_history_files(plan_path, &get_snapshot_prefix(plan_path), "json")
+}
+
+/// List all plan content snapshots for a given plan, sorted by timestamp (oldest first)
+pub fn list_plan_snapshots(plan_path: &str) -> Result<Vec<PathBuf>, String> {
+    list_history_files(plan_path, &get_plan_snapshot_prefix(plan_path), "md")
+}
+
+/// Get the timestamp from the most recent snapshot (if any)
+fn get_latest_snapshot_time(plan_path: &str) -> Option<u64> {
+    if let Some(git) = get_git_history(plan_path) {
+        return git.list_snapshots().ok()?.last().map(|s| s.timestamp);
+    }
+
+    let snapshots = list_snapshots(plan_path).ok()?;
+    parse_snapshot_timestamp(snapshots.last()?)
+}
+
 /// Check if enough time has passed since the last snapshot
 pub fn should_create_snapshot(plan_path: &str) -> bool {
     let Some(last_timestamp_ms) = get_latest_snapshot_time(plan_path) else {
         return true; // No previous snapshot, create one
     };
 
-    let current_time_ms = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis() as u64;
-
-    let elapsed_secs = (current_time_ms.saturating_sub(last_timestamp_ms)) / 1000;
+    let elapsed_secs = (now_millis().saturating_sub(last_timestamp_ms)) / 1000;
     elapsed_secs >= MIN_SNAPSHOT_INTERVAL_SECS
 }
 
@@ -99,13 +274,15 @@ pub fn create_snapshot(plan_path: &str, layout: &LayoutFile, force: bool) -> Res
         return Ok(());
     }
 
-    let history_dir = get_history_dir(plan_path);
+    // Git history is unlimited and deduplicated, so it needs no rotation
+    if let Some(git) = get_git_history(plan_path) {
+        let plan_content = fs::read_to_string(plan_path).ok();
+        git.record_snapshot(plan_content.as_deref(), layout, now_millis())?;
+        return Ok(());
+    }
 
     // Create history directory if needed
-    if !history_dir.exists() {
-        fs::create_dir_all(&history_dir)
-            .map_err(|e| format!("Failed to create history directory: {}", e))?;
-    }
+    let history_dir = ensure_history_dir(plan_path)?;
 
     // Generate snapshot filename and write
     let snapshot_filename = generate_snapshot_filename(plan_path);
@@ -141,40 +318,125 @@ pub fn rotate_snapshots(plan_path: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Plan content captured at a point in time
+#[derive(Debug, Clone)]
+pub struct PlanSnapshot {
+    pub timestamp: u64,
+    pub content: String,
+}
+
+/// Record the current plan file content, skipping it if unchanged since the last snapshot
+/// Returns whether a new snapshot was written
+pub fn record_plan_snapshot(plan_path: &str) -> Result<bool, String> {
+    let Ok(content) = fs::read_to_string(plan_path) else {
+        return Ok(false);
+    };
+
+    if let Some(git) = get_git_history(plan_path) {
+        let layout = layout::read_layout(plan_path.to_string())?;
+        return git.record_snapshot(Some(&content), &layout, now_millis());
+    }
+
+    let snapshots = list_plan_snapshots(plan_path)?;
+    if let Some(latest) = snapshots.last() {
+        if fs::read_to_string(latest).is_ok_and(|previous| previous == content) {
+            return Ok(false);
+        }
+    }
+
+    let history_dir = ensure_history_dir(plan_path)?;
+    let filename = format!("{}.{}.md", get_plan_snapshot_prefix(plan_path), now_millis());
+    fs::write(history_dir.join(filename), &content)
+        .map_err(|e| format!("Failed to write plan snapshot: {}", e))?;
+
+    // Rotate old plan snapshots (list is sorted oldest first)
+    let snapshots = list_plan_snapshots(plan_path)?;
+    let to_remove = snapshots.len().saturating_sub(MAX_PLAN_SNAPSHOTS);
+    for snapshot in snapshots.iter().take(to_remove) {
+        fs::remove_file(snapshot)
+            .map_err(|e| format!("Failed to remove old snapshot {:?}: {}", snapshot, e))?;
+    }
+
+    Ok(true)
+}
+
+/// Load every stored plan content snapshot, including checkpoints (oldest first)
+/// Unreadable entries are skipped and consecutive duplicates collapsed
+pub fn load_plan_snapshots(plan_path: &str) -> Result<Vec<PlanSnapshot>, String> {
+    let mut snapshots: Vec<PlanSnapshot> = Vec::new();
+
+    if let Some(git) = get_git_history(plan_path) {
+        for snapshot in git.list_plan_snapshots()? {
+            if let Some(content) = git.read_plan(&snapshot.commit) {
+                snapshots.push(PlanSnapshot {
+                    timestamp: snapshot.timestamp,
+                    content,
+                });
+            }
+        }
+    } else {
+        for path in list_plan_snapshots(plan_path)? {
+            let Some(timestamp) = parse_snapshot_timestamp(&path) else {
+                continue;
+            };
+            if let Ok(content) = fs::read_to_string(&path) {
+                snapshots.push(PlanSnapshot { timestamp, content });
+            }
+        }
+    }
+
+    for path in list_checkpoint_files(plan_path)? {
+        if let Ok(Checkpoint {
+            id,
+            plan_content: Some(content),
+            ..
+        }) = read_checkpoint(&path)
+        {
+            snapshots.push(PlanSnapshot {
+                timestamp: id,
+                content,
+            });
+        }
+    }
+
+    snapshots.sort_by_key(|s| s.timestamp);
+    snapshots.dedup_by(|later, earlier| later.content == earlier.content);
+    Ok(snapshots)
+}
+
 /// Get the most recent snapshot for a plan (useful for undo)
 #[tauri::command]
 pub fn get_latest_snapshot(plan_path: String) -> Result<Option<LayoutFile>, String> {
-    let snapshots = list_snapshots(&plan_path)?;
-
-    let Some(latest) = snapshots.last() else {
-        return Ok(None);
-    };
+    if let Some(git) = get_git_history(&plan_path) {
+        return match git.list_snapshots()?.last() {
+            Some(latest) => git.read_layout(&latest.commit).map(Some),
+            None => Ok(None),
+        };
+    }
 
-    let content = fs::read_to_string(latest)
-        .map_err(|e| format!("Failed to read snapshot: {}", e))?;
+    let snapshots = list_snapshots(&plan_path)?;
 
-    let layout: LayoutFile = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse snapshot: {}", e))?;
+    // Newest first, skipping unreadable or corrupt snapshots
+    let latest = snapshots.iter().rev().find_map(|path| {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str::<LayoutFile>(&content).ok()
+    });
 
-    Ok(Some(layout))
+    Ok(latest)
 }
 
 /// List all snapshot timestamps for a plan
 #[tauri::command]
 pub fn list_snapshot_timestamps(plan_path: String) -> Result<Vec<u64>, String> {
+    if let Some(git) = get_git_history(&plan_path) {
+        return Ok(git.list_snapshots()?.iter().map(|s| s.timestamp).collect());
+    }
+
     let snapshots = list_snapshots(&plan_path)?;
 
     let timestamps: Vec<u64> = snapshots
         .iter()
-        .filter_map(|path| {
-            let filename = path.file_name()?.to_str()?;
-            let parts: Vec<&str> = filename.rsplitn(3, '.').collect();
-            if parts.len() >= 3 {
-                parts[1].parse().ok()
-            } else {
-                None
-            }
-        })
+        .filter_map(|path| parse_snapshot_timestamp(path))
         .collect();
 
     Ok(timestamps)
@@ -183,7 +445,12 @@ pub fn list_snapshot_timestamps(plan_path: String) -> Result<Vec<u64>, String> {
 /// Clear all snapshots for a plan
 #[tauri::command]
 pub fn clear_snapshots(plan_path: String) -> Result<(), String> {
-    let snapshots = list_snapshots(&plan_path)?;
+    if let Some(git) = get_git_history(&plan_path) {
+        return git.clear();
+    }
+
+    let mut snapshots = list_snapshots(&plan_path)?;
+    snapshots.extend(list_plan_snapshots(&plan_path)?);
 
     for snapshot in snapshots {
         fs::remove_file(&snapshot)
@@ -193,6 +460,318 @@ pub fn clear_snapshots(plan_path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Restore the snapshot taken at `timestamp`
+/// Git snapshots also restore the plan file when it was recorded
+#[tauri::command]
+pub fn restore_snapshot(plan_path: String, timestamp: u64) -> Result<LayoutFile, String> {
+    let (layout, plan_content) = if let Some(git) = get_git_history(&plan_path) {
+        let snapshot = git
+            .list_snapshots()?
+            .into_iter()
+            .find(|s| s.timestamp == timestamp)
+            .ok_or_else(|| format!("Snapshot {} not found", timestamp))?;
+        (git.read_layout(&snapshot.commit)?, git.read_plan(&snapshot.commit))
+    } else {
+        let filename = format!("{}.{}.json", get_snapshot_prefix(&plan_path), timestamp);
+        let path = get_history_dir(&plan_path).join(filename);
+        if !path.exists() {
+            return Err(format!("Snapshot {} not found", timestamp));
+        }
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read snapshot: {}", e))?;
+        let layout = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse snapshot: {}", e))?;
+        (layout, None)
+    };
+
+    // Snapshot the current state so the restore itself can be undone
+    let current = layout::read_layout(plan_path.clone())?;
+    if !current.layouts.is_empty() {
+        create_snapshot(&plan_path, &current, true)?;
+    }
+
+    if let Some(plan_content) = plan_content {
+        fs::write(&plan_path, plan_content)
+            .map_err(|e| format!("Failed to restore plan file: {}", e))?;
+    }
+
+    layout::write_layout(plan_path, layout.clone())?;
+
+    Ok(layout)
+}
+
+/// A named checkpoint pinned against rotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    /// Checkpoint ID (creation timestamp in milliseconds)
+    pub id: u64,
+    /// User-supplied checkpoint name
+    pub name: String,
+    /// Contents of the plan file at checkpoint time (if it existed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_content: Option<String>,
+    /// Layout at checkpoint time
+    pub layout: LayoutFile,
+}
+
+/// Checkpoint summary returned by list_checkpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointInfo {
+    pub id: u64,
+    pub name: String,
+    pub has_plan_content: bool,
+}
+
+/// Get the path of a checkpoint file by ID
+fn get_checkpoint_path(plan_path: &str, id: u64) -> PathBuf {
+    let prefix = get_checkpoint_prefix(plan_path);
+    get_history_dir(plan_path).join(format!("{}.{}.json", prefix, id))
+}
+
+/// List all checkpoint files for a plan, sorted by timestamp (oldest first)
+fn list_checkpoint_files(plan_path: &str) -> Result<Vec<PathBuf>, String> {
+    list_history_files(plan_path, &get_checkpoint_prefix(plan_path), "json")
+}
+
+/// Read a checkpoint file from disk
+fn read_checkpoint(path: &Path) -> Result<Checkpoint, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read checkpoint: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse checkpoint: {}", e))
+}
+
+/// Create a named checkpoint of the current plan and layout
+/// Checkpoints live alongside snapshots but are never rotated
+#[tauri::command]
+pub fn create_checkpoint(plan_path: String, name: String) -> Result<CheckpointInfo, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Checkpoint name cannot be empty".to_string());
+    }
+
+    ensure_history_dir(&plan_path)?;
+
+    // Bump the timestamp if a checkpoint was already created this millisecond
+    let mut id = now_millis();
+    while get_checkpoint_path(&plan_path, id).exists() {
+        id += 1;
+    }
+
+    let checkpoint = Checkpoint {
+        id,
+        name,
+        plan_content: fs::read_to_string(&plan_path).ok(),
+        layout: layout::read_layout(plan_path.clone())?,
+    };
+
+    let content = serde_json::to_string_pretty(&checkpoint)
+        .map_err(|e| format!("Failed to serialize checkpoint: {}", e))?;
+
+    fs::write(get_checkpoint_path(&plan_path, id), content)
+        .map_err(|e| format!("Failed to write checkpoint: {}", e))?;
+
+    Ok(CheckpointInfo {
+        id,
+        name: checkpoint.name,
+        has_plan_content: checkpoint.plan_content.is_some(),
+    })
+}
+
+/// List all checkpoints for a plan (oldest first)
+/// Unreadable checkpoint files are skipped
+#[tauri::command]
+pub fn list_checkpoints(plan_path: String) -> Result<Vec<CheckpointInfo>, String> {
+    let checkpoints = list_checkpoint_files(&plan_path)?
+        .iter()
+        .filter_map(|path| read_checkpoint(path).ok())
+        .map(|checkpoint| CheckpointInfo {
+            id: checkpoint.id,
+            name: checkpoint.name,
+            has_plan_content: checkpoint.plan_content.is_some(),
+        })
+        .collect();
+
+    Ok(checkpoints)
+}
+
+/// Restore a checkpoint, writing its plan content and layout back to disk
+/// The current layout is snapshotted first so the restore can be undone
+#[tauri::command]
+pub fn restore_checkpoint(plan_path: String, id: u64) -> Result<Checkpoint, String> {
+    let path = get_checkpoint_path(&plan_path, id);
+    if !path.exists() {
+        return Err(format!("Checkpoint {} not found", id));
+    }
+
+    let checkpoint = read_checkpoint(&path)?;
+
+    let current = layout::read_layout(plan_path.clone())?;
+    if !current.layouts.is_empty() {
+        create_snapshot(&plan_path, &current, true)?;
+    }
+
+    if let Some(plan_content) = &checkpoint.plan_content {
+        fs::write(&plan_path, plan_content)
+            .map_err(|e| format!("Failed to restore plan file: {}", e))?;
+    }
+
+    layout::write_layout(plan_path, checkpoint.layout.clone())?;
+
+    Ok(checkpoint)
+}
+
+/// Delete a checkpoint by ID
+#[tauri::command]
+pub fn delete_checkpoint(plan_path: String, id: u64) -> Result<(), String> {
+    let path = get_checkpoint_path(&plan_path, id);
+    if !path.exists() {
+        return Err(format!("Checkpoint {} not found", id));
+    }
+
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove checkpoint {:?}: {}", path, e))
+}
+
+/// Files moved for one plan during a history migration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigratedPlan {
+    /// Plan path the files belong to
+    pub plan_path: String,
+    /// New history directory
+    pub history_dir: String,
+    /// Filenames that were moved
+    pub moved: Vec<String>,
+    /// Filenames left in place because the destination already had them
+    pub skipped: Vec<String>,
+}
+
+/// Result of migrating a .plan-history/ folder into centralized storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryMigration {
+    /// The .plan-history/ directory that was migrated
+    pub source_dir: String,
+    /// Per-plan results
+    pub plans: Vec<MigratedPlan>,
+    /// Whether the source directory was removed (it was left empty)
+    pub removed_source: bool,
+}
+
+/// Kind of file stored in a history directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HistoryFileKind {
+    Layout,
+    Checkpoint,
+    Plan,
+}
+
+/// Split a history filename into the plan filename and file kind
+/// Matches `<plan>.layout.<ts>.json`, `<plan>.checkpoint.<ts>.json` and `<plan>.plan.<ts>.md`
+fn parse_history_filename(filename: &str) -> Option<(&str, HistoryFileKind)> {
+    let parts: Vec<&str> = filename.rsplitn(4, '.').collect();
+    if parts.len() != 4 || parts[1].parse::<u64>().is_err() {
+        return None;
+    }
+    let kind = match (parts[2], parts[0]) {
+        ("layout", "json") => HistoryFileKind::Layout,
+        ("checkpoint", "json") => HistoryFileKind::Checkpoint,
+        ("plan", "md") => HistoryFileKind::Plan,
+        _ => return None,
+    };
+    Some((parts[3], kind))
+}
+
+/// Move a file, falling back to copy + remove across filesystems
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(|e| format!("Failed to copy {:?}: {}", from, e))?;
+    fs::remove_file(from).map_err(|e| format!("Failed to remove {:?}: {}", from, e))
+}
+
+/// Move every plan's history out of a .plan-history/ folder into centralized storage
+fn migrate_repo_history(plan_path: &str) -> Result<HistoryMigration, String> {
+    let source_dir = get_repo_history_dir(plan_path);
+    let mut migration = HistoryMigration {
+        source_dir: source_dir.to_string_lossy().to_string(),
+        plans: Vec::new(),
+        removed_source: false,
+    };
+
+    if !source_dir.exists() {
+        return Ok(migration);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(&source_dir)
+        .map_err(|e| format!("Failed to read history directory: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+
+    let plan_dir = source_dir.parent().unwrap_or(Path::new("."));
+
+    for file in files {
+        let Some(filename) = file.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some((plan_filename, _)) = parse_history_filename(filename) else {
+            continue;
+        };
+
+        let owner = plan_dir.join(plan_filename).to_string_lossy().to_string();
+        let target_dir = get_history_dir_for(&owner, HistoryStorage::AppData);
+        if target_dir == source_dir {
+            continue;
+        }
+
+        let index = match migration.plans.iter().position(|p| p.plan_path == owner) {
+            Some(index) => index,
+            None => {
+                init_history_dir(&target_dir, &owner)?;
+                migration.plans.push(MigratedPlan {
+                    plan_path: owner.clone(),
+                    history_dir: target_dir.to_string_lossy().to_string(),
+                    moved: Vec::new(),
+                    skipped: Vec::new(),
+                });
+                migration.plans.len() - 1
+            }
+        };
+
+        let target = target_dir.join(filename);
+        let entry = &mut migration.plans[index];
+        if target.exists() {
+            entry.skipped.push(filename.to_string());
+        } else {
+            move_file(&file, &target)?;
+            entry.moved.push(filename.to_string());
+        }
+    }
+
+    let is_empty = fs::read_dir(&source_dir)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(false);
+    if is_empty {
+        fs::remove_dir(&source_dir)
+            .map_err(|e| format!("Failed to remove history directory: {}", e))?;
+        migration.removed_source = true;
+    }
+
+    Ok(migration)
+}
+
+/// Migrate the .plan-history/ folder next to a plan into centralized storage
+/// Moves the history of every plan sharing that folder, not just this one
+#[tauri::command]
+pub fn migrate_history(plan_path: String) -> Result<HistoryMigration, String> {
+    migrate_repo_history(&plan_path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -205,17 +784,88 @@ mod tests {
             plan_hash: hash.to_string(),
             layouts: HashMap::new(),
             last_modified: "2024-01-01T00:00:00Z".to_string(),
+            stale: false,
         }
     }
 
     #[test]
-    fn test_get_history_dir() {
-        let dir = get_history_dir("/path/to/plan.md");
+    fn test_get_repo_history_dir() {
+        let dir = get_repo_history_dir("/path/to/plan.md");
         assert_eq!(dir, PathBuf::from("/path/to/.plan-history"));
 
         // For relative paths, parent of "plan.md" is "." which joins with ".plan-history"
-        let dir = get_history_dir("plan.md");
+        let dir = get_repo_history_dir("plan.md");
         assert_eq!(dir, PathBuf::from(".plan-history"));
+
+        let dir = get_history_dir_for("/path/to/plan.md", HistoryStorage::Repository);
+        assert_eq!(dir, PathBuf::from("/path/to/.plan-history"));
+    }
+
+    #[test]
+    fn test_app_data_history_dir_outside_repo() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+
+        let dir = get_history_dir_for(&plan_path_str, HistoryStorage::AppData);
+        assert!(!dir.starts_with(temp.path()));
+        assert!(dir.file_name().unwrap().to_string_lossy().starts_with("plan.md-"));
+
+        // Keyed by canonical path, so equivalent spellings share a directory
+        let dotted = temp.path().join(".").join("plan.md");
+        assert_eq!(
+            get_history_dir_for(&dotted.to_string_lossy(), HistoryStorage::AppData),
+            dir
+        );
+
+        // Different plans with the same filename get different directories
+        let other = temp.path().join("sub").join("plan.md");
+        assert_ne!(get_history_dir_for(&other.to_string_lossy(), HistoryStorage::AppData), dir);
+    }
+
+    #[test]
+    fn test_parse_history_filename() {
+        assert_eq!(
+            parse_history_filename("plan.md.layout.123.json"),
+            Some(("plan.md", HistoryFileKind::Layout))
+        );
+        assert_eq!(
+            parse_history_filename("backend.plan.md.checkpoint.123.json"),
+            Some(("backend.plan.md", HistoryFileKind::Checkpoint))
+        );
+        assert_eq!(
+            parse_history_filename("plan.md.plan.123.md"),
+            Some(("plan.md", HistoryFileKind::Plan))
+        );
+        assert_eq!(parse_history_filename("plan.md.plan.123.json"), None);
+        assert_eq!(parse_history_filename("notes.txt"), None);
+        assert_eq!(parse_history_filename("plan.md.layout.abc.json"), None);
+    }
+
+    #[test]
+    fn test_migrate_history() {
+        let temp = tempdir().unwrap();
+        let repo_dir = temp.path().join(".plan-history");
+        fs::create_dir_all(&repo_dir).unwrap();
+
+        let content = serde_json::to_string(&create_test_layout("old")).unwrap();
+        fs::write(repo_dir.join("plan.md.layout.1000.json"), &content).unwrap();
+        fs::write(repo_dir.join("other.md.layout.1000.json"), &content).unwrap();
+
+        let plan_path = temp.path().join("plan.md").to_string_lossy().to_string();
+        let migration = migrate_history(plan_path.clone()).unwrap();
+
+        assert_eq!(migration.plans.len(), 2);
+        assert!(migration.removed_source);
+        assert!(!repo_dir.exists());
+
+        let target = get_history_dir_for(&plan_path, HistoryStorage::AppData);
+        assert!(target.join("plan.md.layout.1000.json").exists());
+        assert!(target.join(HISTORY_META_FILE).exists());
+
+        let other = temp.path().join("other.md").to_string_lossy().to_string();
+        let other_target = get_history_dir_for(&other, HistoryStorage::AppData);
+        assert!(other_target.join("other.md.layout.1000.json").exists());
     }
 
     #[test]
@@ -323,4 +973,135 @@ mod tests {
         let snapshots = list_snapshots(&plan_path_str).unwrap();
         assert!(snapshots.is_empty());
     }
+
+    #[test]
+    fn test_checkpoint_prefix_distinct_from_snapshots() {
+        let checkpoint = get_checkpoint_prefix("/path/to/plan.md");
+        assert_eq!(checkpoint, "plan.md.checkpoint");
+        assert!(!checkpoint.starts_with(&get_snapshot_prefix("/path/to/plan.md")));
+    }
+
+    #[test]
+    fn test_checkpoints_survive_rotation() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+
+        let info = create_checkpoint(plan_path_str.clone(), "before refactor".to_string()).unwrap();
+
+        for i in 0..(MAX_SNAPSHOTS + 2) {
+            create_snapshot(&plan_path_str, &create_test_layout(&format!("hash{}", i)), true)
+                .unwrap();
+        }
+        rotate_snapshots(&plan_path_str).unwrap();
+
+        let checkpoints = list_checkpoints(plan_path_str.clone()).unwrap();
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints[0].id, info.id);
+        assert_eq!(checkpoints[0].name, "before refactor");
+    }
+
+    #[test]
+    fn test_create_checkpoint_rejects_empty_name() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+
+        let result = create_checkpoint(plan_path.to_string_lossy().to_string(), "  ".to_string());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_restore_checkpoint() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+
+        fs::write(&plan_path, "# Plan: Original\n").unwrap();
+        let mut original = create_test_layout("original");
+        original.layouts.insert(
+            "t1".to_string(),
+            layout::NodeLayout { x: 1.0, y: 2.0, width: 100.0, height: 50.0 },
+        );
+        layout::write_layout(plan_path_str.clone(), original).unwrap();
+        let info = create_checkpoint(plan_path_str.clone(), "original".to_string()).unwrap();
+        assert!(info.has_plan_content);
+
+        fs::write(&plan_path, "# Plan: Rewritten\n").unwrap();
+        layout::write_layout(plan_path_str.clone(), create_test_layout("rewritten")).unwrap();
+
+        let restored = restore_checkpoint(plan_path_str.clone(), info.id).unwrap();
+        assert_eq!(restored.name, "original");
+        assert_eq!(fs::read_to_string(&plan_path).unwrap(), "# Plan: Original\n");
+        // The plan hash is recomputed on write, so tell the layouts apart by their nodes
+        let restored_layout = layout::read_layout(plan_path_str).unwrap();
+        assert!(restored_layout.layouts.contains_key("t1"));
+    }
+
+    #[test]
+    fn test_delete_checkpoint() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+
+        let info = create_checkpoint(plan_path_str.clone(), "temp".to_string()).unwrap();
+        delete_checkpoint(plan_path_str.clone(), info.id).unwrap();
+
+        assert!(list_checkpoints(plan_path_str.clone()).unwrap().is_empty());
+        assert!(delete_checkpoint(plan_path_str, info.id).is_err());
+    }
+
+    #[test]
+    fn test_restore_snapshot() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+        let history_dir = get_history_dir(&plan_path_str);
+        fs::create_dir_all(&history_dir).unwrap();
+
+        let content = serde_json::to_string(&create_test_layout("restored")).unwrap();
+        fs::write(history_dir.join("plan.md.layout.1000000000000.json"), content).unwrap();
+
+        let layout = restore_snapshot(plan_path_str.clone(), 1000000000000).unwrap();
+        assert_eq!(layout.plan_hash, "restored");
+        assert_eq!(layout::read_layout(plan_path_str.clone()).unwrap().plan_hash, "restored");
+
+        assert!(restore_snapshot(plan_path_str, 42).is_err());
+    }
+
+    #[test]
+    fn test_record_plan_snapshot_dedupes() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+
+        // Missing plan file records nothing
+        assert!(!record_plan_snapshot(&plan_path_str).unwrap());
+
+        fs::write(&plan_path, "# Plan: One\n").unwrap();
+        assert!(record_plan_snapshot(&plan_path_str).unwrap());
+        assert!(!record_plan_snapshot(&plan_path_str).unwrap());
+
+        let snapshots = load_plan_snapshots(&plan_path_str).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].content, "# Plan: One\n");
+
+        // Plan snapshots don't count against layout rotation
+        assert!(list_snapshots(&plan_path_str).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_latest_snapshot_skips_corrupt() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+        let history_dir = get_history_dir(&plan_path_str);
+        fs::create_dir_all(&history_dir).unwrap();
+
+        let content = serde_json::to_string(&create_test_layout("valid")).unwrap();
+        fs::write(history_dir.join("plan.md.layout.1000000000000.json"), content).unwrap();
+        fs::write(history_dir.join("plan.md.layout.1000000000001.json"), "{ truncated").unwrap();
+
+        let result = get_latest_snapshot(plan_path_str).unwrap();
+        assert_eq!(result.unwrap().plan_hash, "valid");
+    }
 }
@@ -0,0 +1,353 @@
+//! History integrity check and garbage collection
+//!
+//! Parses every stored snapshot, moves corrupt files into a `quarantine/`
+//! folder, removes history of plans that no longer exist and reports disk
+//! usage per plan. Checkpoints are pinned, so they are kept even when their
+//! plan is missing (it may only be on another branch or an unmounted share).
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{
+    app_data_history_root, get_history_dir_for, get_repo_history_dir, move_file,
+    parse_history_filename, Checkpoint, HistoryFileKind, HistoryMeta, HistoryStorage,
+    HISTORY_META_FILE,
+};
+use crate::layout::LayoutFile;
+
+/// Folder (inside a history directory) that holds corrupt files
+const QUARANTINE_DIR: &str = "quarantine";
+
+/// Disk usage and health of one plan's history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanHistoryUsage {
+    /// Plan path, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_path: Option<String>,
+    pub history_dir: String,
+    /// Whether the plan file still exists
+    pub plan_exists: bool,
+    pub snapshots: usize,
+    pub checkpoints: usize,
+    pub plan_snapshots: usize,
+    /// Total bytes used by the remaining history files
+    pub bytes: u64,
+}
+
+/// Result of a history verify/gc pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryGcReport {
+    /// Whether this was a dry run (nothing moved or deleted)
+    pub dry_run: bool,
+    pub plans: Vec<PlanHistoryUsage>,
+    /// Corrupt files moved (or that would be moved) to quarantine
+    pub quarantined: Vec<String>,
+    /// Files removed (or that would be removed) because their plan no longer exists
+    pub removed: Vec<String>,
+    /// Checkpoints kept although their plan no longer exists
+    pub kept_checkpoints: Vec<String>,
+    /// Bytes freed by removals
+    pub bytes_freed: u64,
+}
+
+/// Check that a history file can be read and parsed
+fn is_valid(path: &Path, kind: HistoryFileKind) -> bool {
+    let Ok(content) = fs::read_to_string(path) else {
+        return false;
+    };
+    match kind {
+        HistoryFileKind::Layout => serde_json::from_str::<LayoutFile>(&content).is_ok(),
+        HistoryFileKind::Checkpoint => serde_json::from_str::<Checkpoint>(&content).is_ok(),
+        HistoryFileKind::Plan => true,
+    }
+}
+
+/// History files in a directory, optionally restricted to one plan filename
+fn history_files(dir: &Path, plan_filename: Option<&str>) -> Vec<(PathBuf, HistoryFileKind)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(PathBuf, HistoryFileKind)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            let (owner, kind) = parse_history_filename(name)?;
+            if plan_filename.is_some_and(|plan| plan != owner) {
+                return None;
+            }
+            Some((path.clone(), kind))
+        })
+        .collect();
+
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+/// Verify or collect one plan's history files
+fn process_plan(
+    plan_path: Option<&str>,
+    history_dir: &Path,
+    files: Vec<(PathBuf, HistoryFileKind)>,
+    report: &mut HistoryGcReport,
+) -> Result<PlanHistoryUsage, String> {
+    let plan_exists = plan_path.is_some_and(|p| Path::new(p).exists());
+    let mut usage = PlanHistoryUsage {
+        plan_path: plan_path.map(String::from),
+        history_dir: history_dir.to_string_lossy().to_string(),
+        plan_exists,
+        ..Default::default()
+    };
+
+    for (path, kind) in files {
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+
+        // Only remove when we know which plan the history belonged to
+        let orphaned = plan_path.is_some() && !plan_exists;
+        if orphaned && kind == HistoryFileKind::Checkpoint {
+            report
+                .kept_checkpoints
+                .push(path.to_string_lossy().to_string());
+        } else if orphaned {
+            if !report.dry_run {
+                fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+            }
+            report.removed.push(path.to_string_lossy().to_string());
+            report.bytes_freed += size;
+            continue;
+        }
+
+        if !is_valid(&path, kind) {
+            if !report.dry_run {
+                let quarantine = history_dir.join(QUARANTINE_DIR);
+                fs::create_dir_all(&quarantine)
+                    .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+                move_file(
+                    &path,
+                    &quarantine.join(path.file_name().unwrap_or_default()),
+                )?;
+            }
+            report.quarantined.push(path.to_string_lossy().to_string());
+            continue;
+        }
+
+        usage.bytes += size;
+        match kind {
+            HistoryFileKind::Layout => usage.snapshots += 1,
+            HistoryFileKind::Checkpoint => usage.checkpoints += 1,
+            HistoryFileKind::Plan => usage.plan_snapshots += 1,
+        }
+    }
+
+    Ok(usage)
+}
+
+/// Read the plan path recorded in a centralized history directory
+fn read_meta(history_dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(history_dir.join(HISTORY_META_FILE)).ok()?;
+    let meta: HistoryMeta = serde_json::from_str(&content).ok()?;
+    Some(meta.plan_path)
+}
+
+/// Remove a directory if nothing is left in it
+fn remove_if_empty(dir: &Path) {
+    let is_empty = fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none());
+    if is_empty {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+/// Verify and collect a centralized history directory
+fn process_app_data_dir(history_dir: &Path, report: &mut HistoryGcReport) -> Result<(), String> {
+    let plan_path = read_meta(history_dir);
+    let files = history_files(history_dir, None);
+    let usage = process_plan(plan_path.as_deref(), history_dir, files, report)?;
+
+    // A centralized directory belongs to a single plan, so drop it entirely once orphaned
+    // Kept checkpoints still need the metadata naming their plan
+    if usage.plan_path.is_some() && !usage.plan_exists && usage.checkpoints == 0 && !report.dry_run
+    {
+        let _ = fs::remove_file(history_dir.join(HISTORY_META_FILE));
+        remove_if_empty(history_dir);
+    }
+
+    report.plans.push(usage);
+    Ok(())
+}
+
+/// Verify and collect a .plan-history/ directory, optionally for one plan only
+fn process_repo_dir(
+    history_dir: &Path,
+    plan_filename: Option<&str>,
+    report: &mut HistoryGcReport,
+) -> Result<(), String> {
+    let plan_dir = history_dir.parent().unwrap_or(Path::new("."));
+
+    let mut owners: Vec<String> = history_files(history_dir, plan_filename)
+        .iter()
+        .filter_map(|(path, _)| {
+            let name = path.file_name()?.to_str()?;
+            parse_history_filename(name).map(|(owner, _)| owner.to_string())
+        })
+        .collect();
+    owners.sort();
+    owners.dedup();
+
+    for owner in owners {
+        let plan_path = plan_dir.join(&owner).to_string_lossy().to_string();
+        let files = history_files(history_dir, Some(&owner));
+        let usage = process_plan(Some(&plan_path), history_dir, files, report)?;
+        report.plans.push(usage);
+    }
+
+    if !report.dry_run {
+        remove_if_empty(history_dir);
+    }
+
+    Ok(())
+}
+
+/// Verify stored history and collect garbage
+/// With a plan path only that plan's history is checked; otherwise all
+/// centralized history is. Dry runs report without moving or deleting.
+#[tauri::command]
+pub fn gc_history(plan_path: Option<String>, dry_run: bool) -> Result<HistoryGcReport, String> {
+    let mut report = HistoryGcReport {
+        dry_run,
+        ..Default::default()
+    };
+
+    match plan_path {
+        Some(plan_path) => {
+            let app_data_dir = get_history_dir_for(&plan_path, HistoryStorage::AppData);
+            let repo_dir = get_repo_history_dir(&plan_path);
+
+            if app_data_dir != repo_dir && app_data_dir.exists() {
+                process_app_data_dir(&app_data_dir, &mut report)?;
+            }
+            if repo_dir.exists() {
+                let filename = Path::new(&plan_path)
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                process_repo_dir(&repo_dir, Some(&filename), &mut report)?;
+            }
+        }
+        None => {
+            let Some(root) = app_data_history_root() else {
+                return Ok(report);
+            };
+            let Ok(entries) = fs::read_dir(&root) else {
+                return Ok(report);
+            };
+
+            let mut dirs: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect();
+            dirs.sort();
+
+            for dir in dirs {
+                process_app_data_dir(&dir, &mut report)?;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{create_checkpoint, get_history_dir, list_snapshots};
+    use tempfile::tempdir;
+
+    fn write_snapshot(dir: &Path, name: &str, valid: bool) {
+        let content = if valid {
+            serde_json::to_string(&LayoutFile::default()).unwrap()
+        } else {
+            "{ not json".to_string()
+        };
+        fs::write(dir.join(name), content).unwrap();
+    }
+
+    #[test]
+    fn test_gc_quarantines_corrupt_snapshots() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+        fs::write(&plan_path, "# Plan: Test\n").unwrap();
+
+        create_checkpoint(plan_path_str.clone(), "keep".to_string()).unwrap();
+        let history_dir = get_history_dir(&plan_path_str);
+        write_snapshot(&history_dir, "plan.md.layout.1000000000000.json", true);
+        write_snapshot(&history_dir, "plan.md.layout.1000000000001.json", false);
+
+        // Dry run reports without touching anything
+        let report = gc_history(Some(plan_path_str.clone()), true).unwrap();
+        assert_eq!(report.quarantined.len(), 1);
+        assert_eq!(list_snapshots(&plan_path_str).unwrap().len(), 2);
+
+        let report = gc_history(Some(plan_path_str.clone()), false).unwrap();
+        assert_eq!(report.quarantined.len(), 1);
+        assert!(report.removed.is_empty());
+
+        let usage = &report.plans[0];
+        assert!(usage.plan_exists);
+        assert_eq!(usage.snapshots, 1);
+        assert_eq!(usage.checkpoints, 1);
+        assert!(usage.bytes > 0);
+
+        assert_eq!(list_snapshots(&plan_path_str).unwrap().len(), 1);
+        assert!(history_dir
+            .join(QUARANTINE_DIR)
+            .join("plan.md.layout.1000000000001.json")
+            .exists());
+    }
+
+    #[test]
+    fn test_gc_removes_history_of_missing_plans() {
+        let temp = tempdir().unwrap();
+        let repo_dir = temp.path().join(".plan-history");
+        fs::create_dir_all(&repo_dir).unwrap();
+        write_snapshot(&repo_dir, "gone.md.layout.1000000000000.json", true);
+
+        let plan_path = temp.path().join("gone.md").to_string_lossy().to_string();
+        let report = gc_history(Some(plan_path), false).unwrap();
+
+        assert_eq!(report.removed.len(), 1);
+        assert!(report.bytes_freed > 0);
+        assert!(!report.plans[0].plan_exists);
+        assert!(!repo_dir.exists());
+    }
+
+    #[test]
+    fn test_gc_keeps_checkpoints_of_missing_plans() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+        fs::write(&plan_path, "# Plan: Test\n").unwrap();
+
+        create_checkpoint(plan_path_str.clone(), "keep".to_string()).unwrap();
+        let history_dir = get_history_dir(&plan_path_str);
+        write_snapshot(&history_dir, "plan.md.layout.1000000000000.json", true);
+
+        // The plan goes missing, e.g. after switching branches
+        fs::remove_file(&plan_path).unwrap();
+        let report = gc_history(Some(plan_path_str.clone()), false).unwrap();
+
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(report.kept_checkpoints.len(), 1);
+        assert_eq!(report.plans[0].checkpoints, 1);
+        assert!(Path::new(&report.kept_checkpoints[0]).exists());
+        assert!(history_dir.exists());
+    }
+}
@@ -0,0 +1,414 @@
+//! Git-backed plan history
+//!
+//! Records plan and layout snapshots as commits on a dedicated ref using git
+//! plumbing commands, so the working tree, index and branches are never touched.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::layout::LayoutFile;
+
+/// Ref that holds the snapshot commits
+pub const HISTORY_REF: &str = "refs/planviz/history";
+
+/// Commit trailer carrying the snapshot timestamp in milliseconds
+const TIMESTAMP_TRAILER: &str = "Planviz-Timestamp";
+
+/// Identity used when the repository has no committer configured
+const FALLBACK_NAME: &str = "Plan Visualizer";
+const FALLBACK_EMAIL: &str = "planviz@localhost";
+
+/// A snapshot commit on the history ref
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitSnapshot {
+    pub commit: String,
+    pub timestamp: u64,
+}
+
+/// History for one plan stored in its enclosing git repository
+#[derive(Debug, Clone)]
+pub struct GitHistory {
+    /// Repository top-level directory
+    repo_root: PathBuf,
+    /// Plan path relative to the repository root, with forward slashes
+    plan_rel: String,
+}
+
+impl GitHistory {
+    /// Open git history for a plan, if the plan lives inside a git repository
+    pub fn open(plan_path: &str) -> Option<Self> {
+        let canonical = super::canonical_plan_path(plan_path);
+        let plan_dir = canonical.parent()?;
+
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(plan_dir)
+            .args(["rev-parse", "--show-toplevel"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let repo_root = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        let repo_root = std::fs::canonicalize(&repo_root).unwrap_or(repo_root);
+        let rel = canonical.strip_prefix(&repo_root).ok()?;
+        let plan_rel = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        Some(Self {
+            repo_root,
+            plan_rel,
+        })
+    }
+
+    /// Path of the layout file inside the snapshot tree
+    fn layout_rel(&self) -> String {
+        format!("{}.layout.json", self.plan_rel)
+    }
+
+    /// Build a git command rooted at the repository
+    fn git(&self) -> Command {
+        let mut cmd = Command::new("git");
+        cmd.arg("-C").arg(&self.repo_root);
+        cmd
+    }
+
+    /// Run a git command and return its stdout verbatim
+    fn run_raw(&self, mut cmd: Command) -> Result<String, String> {
+        let output = cmd
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "git failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Run a git command and return its trimmed stdout
+    fn run(&self, cmd: Command) -> Result<String, String> {
+        self.run_raw(cmd).map(|out| out.trim().to_string())
+    }
+
+    /// Run a git command feeding `input` on stdin
+    fn run_with_input(&self, mut cmd: Command, input: &[u8]) -> Result<String, String> {
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input)
+                .map_err(|e| format!("Failed to write to git: {}", e))?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "git failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Current tip of the history ref, if it exists
+    fn head(&self) -> Option<String> {
+        let mut cmd = self.git();
+        cmd.args(["rev-parse", "--verify", "-q"])
+            .arg(format!("{}^{{commit}}", HISTORY_REF));
+        self.run(cmd).ok().filter(|s| !s.is_empty())
+    }
+
+    /// Write a blob to the object database
+    fn hash_object(&self, content: &[u8]) -> Result<String, String> {
+        let mut cmd = self.git();
+        cmd.args(["hash-object", "-w", "--stdin"]);
+        self.run_with_input(cmd, content)
+    }
+
+    /// Whether git already knows who the committer is
+    fn has_identity(&self) -> bool {
+        let mut cmd = self.git();
+        cmd.args(["var", "GIT_COMMITTER_IDENT"]);
+        self.run(cmd).is_ok()
+    }
+
+    /// Record the plan file and layout as a new commit on the history ref
+    /// Returns false when nothing changed since the previous snapshot
+    pub fn record_snapshot(
+        &self,
+        plan_content: Option<&str>,
+        layout: &LayoutFile,
+        timestamp: u64,
+    ) -> Result<bool, String> {
+        let parent = self.head();
+
+        // Build the new tree in a private index so the user's index is untouched
+        let index_path = std::env::temp_dir().join(format!(
+            "planviz-index-{}-{}",
+            std::process::id(),
+            timestamp
+        ));
+        let result = self.write_snapshot_tree(&index_path, parent.as_deref(), plan_content, layout);
+        let _ = std::fs::remove_file(&index_path);
+        let tree = result?;
+
+        if let Some(parent) = &parent {
+            let mut cmd = self.git();
+            cmd.args(["rev-parse", &format!("{}^{{tree}}", parent)]);
+            if self.run(cmd)? == tree {
+                return Ok(false);
+            }
+        }
+
+        let message = format!(
+            "Snapshot {}\n\n{}: {}\n",
+            self.plan_rel, TIMESTAMP_TRAILER, timestamp
+        );
+        let mut cmd = self.git();
+        cmd.args(["commit-tree", &tree]);
+        if let Some(parent) = &parent {
+            cmd.args(["-p", parent]);
+        }
+        if !self.has_identity() {
+            cmd.env("GIT_AUTHOR_NAME", FALLBACK_NAME)
+                .env("GIT_AUTHOR_EMAIL", FALLBACK_EMAIL)
+                .env("GIT_COMMITTER_NAME", FALLBACK_NAME)
+                .env("GIT_COMMITTER_EMAIL", FALLBACK_EMAIL);
+        }
+        let commit = self.run_with_input(cmd, message.as_bytes())?;
+
+        // Compare-and-swap so concurrent writers can't silently drop commits
+        let mut cmd = self.git();
+        cmd.args(["update-ref", HISTORY_REF, &commit]);
+        cmd.arg(parent.as_deref().unwrap_or(""));
+        self.run(cmd)?;
+
+        Ok(true)
+    }
+
+    /// Stage the snapshot files into a temporary index and write its tree
+    fn write_snapshot_tree(
+        &self,
+        index_path: &Path,
+        parent: Option<&str>,
+        plan_content: Option<&str>,
+        layout: &LayoutFile,
+    ) -> Result<String, String> {
+        let mut cmd = self.git();
+        cmd.env("GIT_INDEX_FILE", index_path).arg("read-tree");
+        match parent {
+            Some(parent) => cmd.arg(parent),
+            None => cmd.arg("--empty"),
+        };
+        self.run(cmd)?;
+
+        let layout_json = serde_json::to_string_pretty(layout)
+            .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+        let mut entries = vec![(self.layout_rel(), layout_json)];
+        if let Some(plan_content) = plan_content {
+            entries.push((self.plan_rel.clone(), plan_content.to_string()));
+        }
+
+        for (path, content) in entries {
+            let blob = self.hash_object(content.as_bytes())?;
+            let mut cmd = self.git();
+            cmd.env("GIT_INDEX_FILE", index_path)
+                .args(["update-index", "--add", "--cacheinfo"])
+                .arg(format!("100644,{},{}", blob, path));
+            self.run(cmd)?;
+        }
+
+        let mut cmd = self.git();
+        cmd.env("GIT_INDEX_FILE", index_path).arg("write-tree");
+        self.run(cmd)
+    }
+
+    /// List snapshot commits that touched this plan's layout (oldest first)
+    pub fn list_snapshots(&self) -> Result<Vec<GitSnapshot>, String> {
+        self.list_commits_touching(&self.layout_rel())
+    }
+
+    /// List snapshot commits that touched this plan's content (oldest first)
+    pub fn list_plan_snapshots(&self) -> Result<Vec<GitSnapshot>, String> {
+        self.list_commits_touching(&self.plan_rel)
+    }
+
+    /// List snapshot commits that touched a path in the snapshot tree (oldest first)
+    fn list_commits_touching(&self, path: &str) -> Result<Vec<GitSnapshot>, String> {
+        if self.head().is_none() {
+            return Ok(Vec::new());
+        }
+
+        let mut cmd = self.git();
+        cmd.args([
+            "log",
+            "--reverse",
+            &format!(
+                "--format=%H %(trailers:key={},valueonly,separator=)",
+                TIMESTAMP_TRAILER
+            ),
+            HISTORY_REF,
+            "--",
+            path,
+        ]);
+        let output = self.run(cmd)?;
+
+        let snapshots = output
+            .lines()
+            .filter_map(|line| {
+                let (commit, timestamp) = line.split_once(' ')?;
+                Some(GitSnapshot {
+                    commit: commit.to_string(),
+                    timestamp: timestamp.trim().parse().ok()?,
+                })
+            })
+            .collect();
+
+        Ok(snapshots)
+    }
+
+    /// Read a file from a snapshot commit
+    fn show(&self, commit: &str, path: &str) -> Result<String, String> {
+        let mut cmd = self.git();
+        cmd.args(["show", &format!("{}:{}", commit, path)]);
+        self.run_raw(cmd)
+    }
+
+    /// Read the layout stored in a snapshot commit
+    pub fn read_layout(&self, commit: &str) -> Result<LayoutFile, String> {
+        let content = self.show(commit, &self.layout_rel())?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse snapshot: {}", e))
+    }
+
+    /// Read the plan content stored in a snapshot commit, if it was recorded
+    pub fn read_plan(&self, commit: &str) -> Option<String> {
+        self.show(commit, &self.plan_rel).ok()
+    }
+
+    /// Delete the history ref, provided it only tracks this plan
+    pub fn clear(&self) -> Result<(), String> {
+        let Some(head) = self.head() else {
+            return Ok(());
+        };
+
+        let mut cmd = self.git();
+        cmd.args(["ls-tree", "-r", "--name-only", &head]);
+        let tracked = self.run(cmd)?;
+        let layout_rel = self.layout_rel();
+        if tracked
+            .lines()
+            .any(|path| path != self.plan_rel && path != layout_rel)
+        {
+            return Err(format!(
+                "{} holds history for other plans; it can't be cleared for a single plan",
+                HISTORY_REF
+            ));
+        }
+
+        let mut cmd = self.git();
+        cmd.args(["update-ref", "-d", HISTORY_REF, &head]);
+        self.run(cmd).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn create_test_layout(hash: &str) -> LayoutFile {
+        LayoutFile {
+            version: 1,
+            plan_hash: hash.to_string(),
+            layouts: HashMap::new(),
+            last_modified: "2024-01-01T00:00:00Z".to_string(),
+            stale: false,
+        }
+    }
+
+    /// Initialize a repository, or return None when git isn't installed
+    fn init_repo(dir: &Path) -> Option<()> {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["init", "-q"])
+            .status()
+            .ok()?;
+        status.success().then_some(())
+    }
+
+    #[test]
+    fn test_open_outside_repo() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        assert!(GitHistory::open(&plan_path.to_string_lossy()).is_none());
+    }
+
+    #[test]
+    fn test_record_and_list_snapshots() {
+        let temp = tempdir().unwrap();
+        if init_repo(temp.path()).is_none() {
+            return;
+        }
+        fs::create_dir_all(temp.path().join("docs")).unwrap();
+        let plan_path = temp.path().join("docs").join("plan.md");
+        fs::write(&plan_path, "# Plan: Test\n").unwrap();
+
+        let history = GitHistory::open(&plan_path.to_string_lossy()).unwrap();
+        assert_eq!(history.plan_rel, "docs/plan.md");
+        assert!(history.list_snapshots().unwrap().is_empty());
+
+        assert!(history
+            .record_snapshot(Some("# Plan: Test\n"), &create_test_layout("one"), 1000)
+            .unwrap());
+        // Identical content is deduplicated
+        assert!(!history
+            .record_snapshot(Some("# Plan: Test\n"), &create_test_layout("one"), 2000)
+            .unwrap());
+        assert!(history
+            .record_snapshot(Some("# Plan: Test\n"), &create_test_layout("two"), 3000)
+            .unwrap());
+
+        let snapshots = history.list_snapshots().unwrap();
+        let timestamps: Vec<u64> = snapshots.iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, vec![1000, 3000]);
+
+        // Only the first commit changed the plan content
+        assert_eq!(history.list_plan_snapshots().unwrap().len(), 1);
+
+        let layout = history.read_layout(&snapshots[1].commit).unwrap();
+        assert_eq!(layout.plan_hash, "two");
+        assert_eq!(
+            history.read_plan(&snapshots[0].commit).as_deref(),
+            Some("# Plan: Test\n")
+        );
+
+        // The working tree and index stay untouched
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(temp.path())
+            .args(["status", "--porcelain"])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&status.stdout).trim(), "?? docs/");
+
+        history.clear().unwrap();
+        assert!(history.list_snapshots().unwrap().is_empty());
+    }
+}
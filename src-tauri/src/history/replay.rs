@@ -0,0 +1,232 @@
+//! Progress replay
+//!
+//! Reconstructs how a plan evolved from its stored plan snapshots: an ordered
+//! timeline of plan states, plus the tasks added, removed and status changes
+//! between consecutive states.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+use super::{load_plan_snapshots, PlanSnapshot};
+use crate::outline::{self, OutlineTask, PlanOutline};
+
+/// Plan state at one point in the timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanState {
+    /// Snapshot timestamp in milliseconds
+    pub timestamp: u64,
+    pub tasks: Vec<OutlineTask>,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// What happened to a task between two states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionKind {
+    Added,
+    Removed,
+    StatusChanged,
+}
+
+/// A single task change in the timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTransition {
+    /// Timestamp of the state where the change was first seen
+    pub timestamp: u64,
+    pub task_id: String,
+    pub content: String,
+    pub kind: TransitionKind,
+    /// Status before the change (absent for added tasks)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Status after the change (absent for removed tasks)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+/// Ordered plan states and the transitions between them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanTimeline {
+    pub states: Vec<PlanState>,
+    pub transitions: Vec<TaskTransition>,
+}
+
+/// Compute task transitions from one outline to the next
+pub fn diff_outlines(
+    before: &PlanOutline,
+    after: &PlanOutline,
+    timestamp: u64,
+) -> Vec<TaskTransition> {
+    let mut transitions = Vec::new();
+
+    for task in &after.tasks {
+        match before.task(&task.id) {
+            None => transitions.push(TaskTransition {
+                timestamp,
+                task_id: task.id.clone(),
+                content: task.content.clone(),
+                kind: TransitionKind::Added,
+                from: None,
+                to: Some(task.status.clone()),
+            }),
+            Some(previous) if previous.status != task.status => transitions.push(TaskTransition {
+                timestamp,
+                task_id: task.id.clone(),
+                content: task.content.clone(),
+                kind: TransitionKind::StatusChanged,
+                from: Some(previous.status.clone()),
+                to: Some(task.status.clone()),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for task in &before.tasks {
+        if after.task(&task.id).is_none() {
+            transitions.push(TaskTransition {
+                timestamp,
+                task_id: task.id.clone(),
+                content: task.content.clone(),
+                kind: TransitionKind::Removed,
+                from: Some(task.status.clone()),
+                to: None,
+            });
+        }
+    }
+
+    transitions
+}
+
+/// Build a timeline from plan snapshots (oldest first)
+/// With `since`, transitions before it are dropped and the last earlier state is kept as a baseline
+pub fn build_timeline(snapshots: &[PlanSnapshot], since: Option<u64>) -> PlanTimeline {
+    let since = since.unwrap_or(0);
+    let mut timeline = PlanTimeline::default();
+    let mut previous = PlanOutline::default();
+    let mut baseline: Option<PlanState> = None;
+
+    for snapshot in snapshots {
+        let current = outline::scan(&snapshot.content);
+        let state = PlanState {
+            timestamp: snapshot.timestamp,
+            completed: current
+                .tasks
+                .iter()
+                .filter(|t| t.status == "completed")
+                .count(),
+            total: current.tasks.len(),
+            tasks: current.tasks.clone(),
+        };
+
+        if snapshot.timestamp < since {
+            baseline = Some(state);
+        } else {
+            timeline
+                .transitions
+                .extend(diff_outlines(&previous, &current, snapshot.timestamp));
+            timeline.states.push(state);
+        }
+
+        previous = current;
+    }
+
+    if let Some(baseline) = baseline {
+        timeline.states.insert(0, baseline);
+    }
+
+    timeline
+}
+
+/// Stored plan snapshots plus the current plan file as the final entry
+/// when it differs from the last snapshot
+pub fn load_snapshots_with_current(plan_path: &str) -> Result<Vec<PlanSnapshot>, String> {
+    let mut snapshots = load_plan_snapshots(plan_path)?;
+
+    if let Ok(content) = fs::read_to_string(plan_path) {
+        if snapshots.last().map(|s| &s.content) != Some(&content) {
+            let timestamp = fs::metadata(plan_path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_else(super::now_millis);
+            snapshots.push(PlanSnapshot { timestamp, content });
+        }
+    }
+
+    Ok(snapshots)
+}
+
+/// Replay how a plan evolved from its stored snapshots
+/// The current plan file is included as the final state when it differs from the last snapshot
+#[tauri::command]
+pub fn replay_plan_history(plan_path: String, since: Option<u64>) -> Result<PlanTimeline, String> {
+    let snapshots = load_snapshots_with_current(&plan_path)?;
+    Ok(build_timeline(&snapshots, since))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(timestamp: u64, tasks: &str) -> PlanSnapshot {
+        PlanSnapshot {
+            timestamp,
+            content: format!("# Plan: Test\n\n## Phase 0 — Work\n{}", tasks),
+        }
+    }
+
+    #[test]
+    fn test_build_timeline_transitions() {
+        let snapshots = vec![
+            snapshot(100, "- [ ] Build (id: t1)\n"),
+            snapshot(200, "- [-] Build (id: t1)\n- [ ] Test (id: t2)\n"),
+            snapshot(300, "- [x] Build (id: t1)\n"),
+        ];
+
+        let timeline = build_timeline(&snapshots, None);
+        assert_eq!(timeline.states.len(), 3);
+        assert_eq!(timeline.states[2].completed, 1);
+
+        let summary: Vec<(u64, &str, TransitionKind)> = timeline
+            .transitions
+            .iter()
+            .map(|t| (t.timestamp, t.task_id.as_str(), t.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (100, "t1", TransitionKind::Added),
+                (200, "t1", TransitionKind::StatusChanged),
+                (200, "t2", TransitionKind::Added),
+                (300, "t1", TransitionKind::StatusChanged),
+                (300, "t2", TransitionKind::Removed),
+            ]
+        );
+
+        let started = &timeline.transitions[1];
+        assert_eq!(started.from.as_deref(), Some("pending"));
+        assert_eq!(started.to.as_deref(), Some("in_progress"));
+    }
+
+    #[test]
+    fn test_build_timeline_since() {
+        let snapshots = vec![
+            snapshot(100, "- [ ] Build (id: t1)\n"),
+            snapshot(200, "- [x] Build (id: t1)\n"),
+        ];
+
+        let timeline = build_timeline(&snapshots, Some(150));
+
+        // Baseline state is kept, but only changes after `since` are reported
+        assert_eq!(timeline.states.len(), 2);
+        assert_eq!(timeline.states[0].timestamp, 100);
+        assert_eq!(timeline.transitions.len(), 1);
+        assert_eq!(timeline.transitions[0].to.as_deref(), Some("completed"));
+    }
+}
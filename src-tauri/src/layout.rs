@@ -4,6 +4,8 @@ use std::fs;
 use std::path::Path;
 
 use crate::history;
+use crate::plan::{self, PlanDoc};
+use crate::watcher;
 
 /// Default dimensions for auto-placed nodes
 const DEFAULT_NODE_WIDTH: f64 = 280.0;
@@ -14,6 +16,38 @@ const GRID_SPACING_Y: f64 = 100.0;
 const GRID_COLUMNS: usize = 3;
 const GRID_START_X: f64 = 50.0;
 const GRID_START_Y: f64 = 50.0;
+const SUBTASK_INDENT_X: f64 = 24.0;
+const SUBTASK_HEIGHT: f64 = 60.0;
+const SUBTASK_SPACING_Y: f64 = 10.0;
+
+/// How new nodes are placed on the canvas, set per plan in front matter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutStrategy {
+    /// Tasks in rows of three under their phase
+    #[default]
+    Grid,
+    /// Tasks in a single column under their phase
+    Column,
+}
+
+impl LayoutStrategy {
+    /// Strategy for a name as written in front matter
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "grid" => Some(Self::Grid),
+            "column" => Some(Self::Column),
+            _ => None,
+        }
+    }
+
+    fn columns(self) -> usize {
+        match self {
+            Self::Grid => GRID_COLUMNS,
+            Self::Column => 1,
+        }
+    }
+}
 
 /// Position and size for a node on the canvas
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,9 +66,16 @@ pub type LayoutMap = HashMap<String, NodeLayout>;
 #[serde(rename_all = "camelCase")]
 pub struct LayoutFile {
     pub version: u32,
+    /// Structural hash of the plan the layout was saved for (`PlanDoc::structure_hash`);
+    /// always set by the backend, so callers may leave it out
+    #[serde(default)]
     pub plan_hash: String,
     pub layouts: LayoutMap,
     pub last_modified: String,
+    /// Set by `read_layout` when the plan's structure changed since the layout was saved,
+    /// and kept by `merge_layout` until the layout is written again
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
 }
 
 /// Node info from parsed plan (for merge operations)
@@ -44,6 +85,8 @@ pub struct NodeInfo {
     pub id: String,
     pub node_type: String, // "phase" or "task"
     pub phase_id: Option<String>, // Parent phase ID for tasks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>, // Parent task ID for subtasks
 }
 
 /// Result of merging layout with plan
@@ -62,6 +105,7 @@ impl Default for LayoutFile {
             plan_hash: String::new(),
             layouts: HashMap::new(),
             last_modified: String::new(),
+            stale: false,
         }
     }
 }
@@ -71,8 +115,16 @@ fn get_layout_path(plan_path: &str) -> String {
     format!("{}.layout.json", plan_path)
 }
 
+/// Structural hash of the plan at `plan_path`, if it can be read
+fn plan_structure_hash(plan_path: &str) -> Option<String> {
+    plan::read_plan_file(plan_path)
+        .ok()
+        .map(|doc| doc.structure_hash())
+}
+
 /// Read layout from {plan_path}.layout.json
 /// Returns default empty LayoutFile if file doesn't exist
+/// The layout is flagged stale when its hash doesn't match the plan's current structure
 #[tauri::command]
 pub fn read_layout(plan_path: String) -> Result<LayoutFile, String> {
     let layout_path = get_layout_path(&plan_path);
@@ -85,15 +137,24 @@ pub fn read_layout(plan_path: String) -> Result<LayoutFile, String> {
     let content = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read layout file: {}", e))?;
 
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse layout file: {}", e))
+    let mut layout: LayoutFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse layout file: {}", e))?;
+    layout.stale = !layout.plan_hash.is_empty()
+        && plan_structure_hash(&plan_path).is_some_and(|hash| hash != layout.plan_hash);
+    Ok(layout)
 }
 
 /// Write layout to {plan_path}.layout.json
 /// Creates a snapshot of the existing layout before writing (for undo capability)
 /// Creates parent directories if needed
+/// The plan hash is computed from the plan file, not taken from the caller
 #[tauri::command]
-pub fn write_layout(plan_path: String, layout: LayoutFile) -> Result<(), String> {
+pub fn write_layout(plan_path: String, mut layout: LayoutFile) -> Result<(), String> {
+    if let Some(hash) = plan_structure_hash(&plan_path) {
+        layout.plan_hash = hash;
+    }
+    layout.stale = false;
+
     let layout_path = get_layout_path(&plan_path);
     let path = Path::new(&layout_path);
 
@@ -120,29 +181,238 @@ pub fn write_layout(plan_path: String, layout: LayoutFile) -> Result<(), String>
     let content = serde_json::to_string_pretty(&layout)
         .map_err(|e| format!("Failed to serialize layout: {}", e))?;
 
+    // Don't echo our own write back to the frontend as an external change
+    watcher::record_self_write(path, content.as_bytes());
+
     fs::write(path, content)
         .map_err(|e| format!("Failed to write layout file: {}", e))
 }
 
+/// Move layout entries to the new IDs of renamed nodes, e.g. renumbered phases
+/// A moved entry replaces any entry already under its new ID
+fn rename_nodes(layouts: &mut LayoutMap, renamed: &HashMap<String, String>) {
+    if !layouts.keys().any(|id| renamed.contains_key(id)) {
+        return;
+    }
+
+    let (moved, kept): (LayoutMap, LayoutMap) = layouts
+        .drain()
+        .partition(|(id, _)| renamed.contains_key(id));
+    *layouts = kept;
+    for (id, node) in moved {
+        layouts.insert(renamed[&id].clone(), node);
+    }
+}
+
+/// Task IDs above a node, nearest first, following `parent_id` links
+/// Stops at a repeated ID, so a malformed chain can't loop
+fn parent_chain<'a>(node: &'a NodeInfo, parents: &HashMap<&'a str, &'a str>) -> Vec<&'a str> {
+    let mut chain: Vec<&str> = Vec::new();
+    let mut parent = node.parent_id.as_deref();
+    while let Some(id) = parent {
+        if id == node.id || chain.contains(&id) {
+            break;
+        }
+        chain.push(id);
+        parent = parents.get(id).copied();
+    }
+    chain
+}
+
+/// How the plan's nodes nest, worked out once per layout pass so placing
+/// each node doesn't walk the whole plan again
+struct NodeTree<'a> {
+    nodes: &'a [NodeInfo],
+    /// Direct subtasks of each task, in plan order
+    children: HashMap<&'a str, Vec<&'a NodeInfo>>,
+    /// Subtasks at any depth under each task
+    descendants: HashMap<&'a str, Vec<&'a str>>,
+    /// Top-level tasks of each phase, in plan order
+    top_level: HashMap<&'a str, Vec<&'a NodeInfo>>,
+    /// Vertical space each phase takes
+    phase_spans: HashMap<&'a str, f64>,
+}
+
+impl<'a> NodeTree<'a> {
+    fn new(nodes: &'a [NodeInfo], columns: usize) -> Self {
+        let parents: HashMap<&str, &str> = nodes
+            .iter()
+            .filter_map(|n| Some((n.id.as_str(), n.parent_id.as_deref()?)))
+            .collect();
+
+        let mut tree = Self {
+            nodes,
+            children: HashMap::new(),
+            descendants: HashMap::new(),
+            top_level: HashMap::new(),
+            phase_spans: HashMap::new(),
+        };
+        for node in nodes.iter().filter(|n| n.node_type == "task") {
+            match node.parent_id.as_deref() {
+                Some(parent) => tree.children.entry(parent).or_default().push(node),
+                None => {
+                    if let Some(phase_id) = node.phase_id.as_deref() {
+                        tree.top_level.entry(phase_id).or_default().push(node);
+                    }
+                }
+            }
+            for ancestor in parent_chain(node, &parents) {
+                tree.descendants.entry(ancestor).or_default().push(&node.id);
+            }
+        }
+
+        let spans = nodes
+            .iter()
+            .filter(|n| n.node_type == "phase")
+            .map(|n| (n.id.as_str(), phase_span(&n.id, &tree, columns)))
+            .collect();
+        tree.phase_spans = spans;
+        tree
+    }
+
+    /// Top-level tasks of a phase, in plan order
+    fn top_level_tasks(&self, phase_id: &str) -> &[&'a NodeInfo] {
+        self.top_level.get(phase_id).map_or(&[], Vec::as_slice)
+    }
+
+    fn phase_span(&self, phase_id: &str) -> f64 {
+        self.phase_spans.get(phase_id).copied().unwrap_or_default()
+    }
+}
+
+/// Task nodes in depth-first order, so each parent comes before its subtasks
+/// and a subtask's own children come before its next sibling
+fn tasks_parents_first<'a>(tree: &NodeTree<'a>) -> Vec<&'a NodeInfo> {
+    fn push_children<'a>(
+        id: &str,
+        tree: &NodeTree<'a>,
+        seen: &mut HashSet<&'a str>,
+        out: &mut Vec<&'a NodeInfo>,
+    ) {
+        for child in tree.children.get(id).into_iter().flatten() {
+            if seen.insert(&child.id) {
+                out.push(child);
+                push_children(&child.id, tree, seen, out);
+            }
+        }
+    }
+
+    let tasks: Vec<&NodeInfo> = tree
+        .nodes
+        .iter()
+        .filter(|n| n.node_type == "task")
+        .collect();
+    let task_ids: HashSet<&str> = tasks.iter().map(|n| n.id.as_str()).collect();
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut ordered = Vec::with_capacity(tasks.len());
+    for task in &tasks {
+        let is_root = task
+            .parent_id
+            .as_deref()
+            .is_none_or(|parent| !task_ids.contains(parent));
+        if is_root && seen.insert(&task.id) {
+            ordered.push(*task);
+            push_children(&task.id, tree, &mut seen, &mut ordered);
+        }
+    }
+    // Anything left sits in a parent cycle; keep it rather than dropping it
+    for task in tasks {
+        if seen.insert(&task.id) {
+            ordered.push(task);
+        }
+    }
+    ordered
+}
+
+/// Height of a top-level task with all of its subtasks stacked below it
+fn task_stack_height(task_id: &str, tree: &NodeTree) -> f64 {
+    let subtasks = tree.descendants.get(task_id).map_or(0, Vec::len);
+    DEFAULT_NODE_HEIGHT + subtasks as f64 * (SUBTASK_SPACING_Y + SUBTASK_HEIGHT)
+}
+
+/// Heights of the rows `tasks` fill, each tall enough for its tallest subtask stack
+fn row_heights(tasks: &[&NodeInfo], tree: &NodeTree, columns: usize) -> Vec<f64> {
+    tasks
+        .chunks(columns)
+        .map(|row| {
+            row.iter()
+                .map(|task| {
+                    task_stack_height(&task.id, tree) + GRID_SPACING_Y - DEFAULT_NODE_HEIGHT
+                })
+                .fold(GRID_SPACING_Y, f64::max)
+        })
+        .collect()
+}
+
+/// Vertical space a phase takes: its heading and every row of its tasks
+fn phase_span(phase_id: &str, tree: &NodeTree, columns: usize) -> f64 {
+    let tasks = tree.top_level_tasks(phase_id);
+    let rows: f64 = row_heights(tasks, tree, columns).iter().sum();
+    (DEFAULT_PHASE_HEIGHT + GRID_SPACING_Y + rows).max(DEFAULT_PHASE_HEIGHT + GRID_SPACING_Y * 3.0)
+}
+
+/// Position for a subtask: indented under its parent, below the parent and
+/// any of the parent's subtasks already placed
+fn calculate_subtask_position(
+    node: &NodeInfo,
+    parent_id: &str,
+    parent_layout: &NodeLayout,
+    existing_layouts: &LayoutMap,
+    tree: &NodeTree,
+) -> NodeLayout {
+    let bottom = tree
+        .descendants
+        .get(parent_id)
+        .into_iter()
+        .flatten()
+        .filter(|id| **id != node.id)
+        .filter_map(|id| existing_layouts.get(*id))
+        .map(|layout| layout.y + layout.height)
+        .fold(parent_layout.y + parent_layout.height, f64::max);
+
+    NodeLayout {
+        x: parent_layout.x + SUBTASK_INDENT_X,
+        y: bottom + SUBTASK_SPACING_Y,
+        width: (parent_layout.width - SUBTASK_INDENT_X).max(DEFAULT_NODE_WIDTH / 2.0),
+        height: SUBTASK_HEIGHT,
+    }
+}
+
 /// Calculate grid position for a new node
-/// Uses hierarchical layout: phases in a column, tasks in grid under each phase
+/// Uses hierarchical layout: phases in a column, tasks in a grid (or a single
+/// column, per `strategy`) under each phase, subtasks stacked below their parent task
 fn calculate_auto_position(
     node: &NodeInfo,
     existing_layouts: &LayoutMap,
-    nodes: &[NodeInfo],
+    tree: &NodeTree,
+    strategy: LayoutStrategy,
 ) -> NodeLayout {
+    let columns = strategy.columns();
     let node_type = node.node_type.as_str();
 
+    if let Some(parent_id) = node.parent_id.as_deref() {
+        if let Some(parent_layout) = existing_layouts.get(parent_id) {
+            return calculate_subtask_position(
+                node,
+                parent_id,
+                parent_layout,
+                existing_layouts,
+                tree,
+            );
+        }
+    }
+
     match node_type {
         "phase" => {
-            // Phases are positioned in a vertical column on the left
-            // Find the bottom-most phase position
-            let phase_count = nodes
-                .iter()
-                .filter(|n| n.node_type == "phase" && existing_layouts.contains_key(&n.id))
-                .count();
-
-            let y = GRID_START_Y + (phase_count as f64 * (DEFAULT_PHASE_HEIGHT + GRID_SPACING_Y * 3.0));
+            // Phases are positioned in a vertical column on the left,
+            // below the space taken by the phases already placed
+            let y = GRID_START_Y
+                + tree
+                    .nodes
+                    .iter()
+                    .filter(|n| n.node_type == "phase" && existing_layouts.contains_key(&n.id))
+                    .map(|n| tree.phase_span(&n.id))
+                    .sum::<f64>();
 
             NodeLayout {
                 x: GRID_START_X,
@@ -159,32 +429,37 @@ fn calculate_auto_position(
             let phase_y = if let Some(phase_layout) = existing_layouts.get(phase_id) {
                 phase_layout.y
             } else {
-                // Calculate based on phase index
-                let phase_index = nodes
-                    .iter()
-                    .filter(|n| n.node_type == "phase")
-                    .position(|n| n.id == phase_id)
-                    .unwrap_or(0);
-                GRID_START_Y + (phase_index as f64 * (DEFAULT_PHASE_HEIGHT + GRID_SPACING_Y * 3.0))
+                // Calculate from the phases before it
+                GRID_START_Y
+                    + tree
+                        .nodes
+                        .iter()
+                        .filter(|n| n.node_type == "phase")
+                        .take_while(|n| n.id != phase_id)
+                        .map(|n| tree.phase_span(&n.id))
+                        .sum::<f64>()
             };
 
-            // Count tasks already positioned in this phase
-            let tasks_in_phase: Vec<&NodeInfo> = nodes
+            // Top-level tasks already positioned in this phase
+            let tasks_in_phase: Vec<&NodeInfo> = tree
+                .top_level_tasks(phase_id)
                 .iter()
-                .filter(|n| {
-                    n.node_type == "task"
-                        && n.phase_id.as_deref() == Some(phase_id)
-                        && existing_layouts.contains_key(&n.id)
-                })
+                .filter(|n| existing_layouts.contains_key(&n.id))
+                .copied()
                 .collect();
 
             let task_index = tasks_in_phase.len();
-            let row = task_index / GRID_COLUMNS;
-            let col = task_index % GRID_COLUMNS;
+            let row = task_index / columns;
+            let col = task_index % columns;
+            // Rows above make room for the subtasks stacked under their tasks
+            let rows_above: f64 = row_heights(&tasks_in_phase, tree, columns)
+                .iter()
+                .take(row)
+                .sum();
 
             NodeLayout {
                 x: GRID_START_X + (col as f64 * GRID_SPACING_X),
-                y: phase_y + DEFAULT_PHASE_HEIGHT + GRID_SPACING_Y + (row as f64 * GRID_SPACING_Y),
+                y: phase_y + DEFAULT_PHASE_HEIGHT + GRID_SPACING_Y + rows_above,
                 width: DEFAULT_NODE_WIDTH,
                 height: DEFAULT_NODE_HEIGHT,
             }
@@ -192,8 +467,8 @@ fn calculate_auto_position(
         _ => {
             // Fallback: simple grid position
             let count = existing_layouts.len();
-            let row = count / GRID_COLUMNS;
-            let col = count % GRID_COLUMNS;
+            let row = count / columns;
+            let col = count % columns;
 
             NodeLayout {
                 x: GRID_START_X + (col as f64 * GRID_SPACING_X),
@@ -209,13 +484,29 @@ fn calculate_auto_position(
 /// - Adds auto-positioned entries for new nodes
 /// - Removes entries for nodes that no longer exist
 /// - Preserves existing positions for unchanged nodes
+///
+/// New nodes are placed with the layout strategy from the plan's front matter
+/// and the layout takes the plan's current structural hash; a stale layout
+/// stays flagged, since its positions were arranged for another structure
 #[tauri::command]
-pub fn merge_layout(
+pub fn merge_layout(plan_path: String, nodes: Vec<NodeInfo>) -> Result<MergeResult, String> {
+    merge_layout_renaming(plan_path, nodes, &HashMap::new())
+}
+
+/// Merge as `merge_layout` does, first moving the entries of renamed nodes
+/// to their new IDs; nothing is written
+pub(crate) fn merge_layout_renaming(
     plan_path: String,
     nodes: Vec<NodeInfo>,
-    plan_hash: String,
+    renamed: &HashMap<String, String>,
 ) -> Result<MergeResult, String> {
+    let doc = plan::read_plan_file(&plan_path).ok();
+    let strategy = doc
+        .as_ref()
+        .and_then(|doc| doc.settings.layout)
+        .unwrap_or_default();
     let mut layout = read_layout(plan_path)?;
+    rename_nodes(&mut layout.layouts, renamed);
     let mut added_nodes: Vec<String> = Vec::new();
     let mut removed_nodes: Vec<String> = Vec::new();
 
@@ -235,14 +526,16 @@ pub fn merge_layout(
         removed_nodes.push(id);
     }
 
-    // Process nodes in order: phases first, then tasks (so phases have positions for task calculations)
+    // Process nodes in order: phases first, then tasks, parents before subtasks
+    // (so phases and parents have positions for task calculations)
+    let tree = NodeTree::new(&nodes, strategy.columns());
     let phases: Vec<&NodeInfo> = nodes.iter().filter(|n| n.node_type == "phase").collect();
-    let tasks: Vec<&NodeInfo> = nodes.iter().filter(|n| n.node_type == "task").collect();
+    let tasks = tasks_parents_first(&tree);
 
     // Add positions for new phases
     for node in &phases {
         if !layout.layouts.contains_key(&node.id) {
-            let position = calculate_auto_position(node, &layout.layouts, &nodes);
+            let position = calculate_auto_position(node, &layout.layouts, &tree, strategy);
             layout.layouts.insert(node.id.clone(), position);
             added_nodes.push(node.id.clone());
         }
@@ -251,14 +544,17 @@ pub fn merge_layout(
     // Add positions for new tasks
     for node in &tasks {
         if !layout.layouts.contains_key(&node.id) {
-            let position = calculate_auto_position(node, &layout.layouts, &nodes);
+            let position = calculate_auto_position(node, &layout.layouts, &tree, strategy);
             layout.layouts.insert(node.id.clone(), position);
             added_nodes.push(node.id.clone());
         }
     }
 
     // Update metadata
-    layout.plan_hash = plan_hash;
+    layout.plan_hash = doc
+        .as_ref()
+        .map(PlanDoc::structure_hash)
+        .unwrap_or_default();
     layout.last_modified = chrono::Utc::now().to_rfc3339();
 
     Ok(MergeResult {
@@ -269,28 +565,41 @@ pub fn merge_layout(
 }
 
 /// Generate a fresh layout for all nodes (used when no layout exists or cache miss)
+/// `strategy` overrides the plan's front matter setting; grid when neither is set
 #[tauri::command]
-pub fn generate_layout(nodes: Vec<NodeInfo>, plan_hash: String) -> Result<LayoutFile, String> {
+pub fn generate_layout(
+    plan_path: String,
+    nodes: Vec<NodeInfo>,
+    strategy: Option<LayoutStrategy>,
+) -> Result<LayoutFile, String> {
+    let doc = plan::read_plan_file(&plan_path).ok();
+    let strategy = strategy
+        .or_else(|| doc.as_ref().and_then(|doc| doc.settings.layout))
+        .unwrap_or_default();
     let mut layout = LayoutFile::default();
 
-    // Process nodes in order: phases first, then tasks
+    // Process nodes in order: phases first, then tasks, parents before subtasks
+    let tree = NodeTree::new(&nodes, strategy.columns());
     let phases: Vec<&NodeInfo> = nodes.iter().filter(|n| n.node_type == "phase").collect();
-    let tasks: Vec<&NodeInfo> = nodes.iter().filter(|n| n.node_type == "task").collect();
+    let tasks = tasks_parents_first(&tree);
 
     // Add positions for phases
     for node in &phases {
-        let position = calculate_auto_position(node, &layout.layouts, &nodes);
+        let position = calculate_auto_position(node, &layout.layouts, &tree, strategy);
         layout.layouts.insert(node.id.clone(), position);
     }
 
     // Add positions for tasks
     for node in &tasks {
-        let position = calculate_auto_position(node, &layout.layouts, &nodes);
+        let position = calculate_auto_position(node, &layout.layouts, &tree, strategy);
         layout.layouts.insert(node.id.clone(), position);
     }
 
     // Set metadata
-    layout.plan_hash = plan_hash;
+    layout.plan_hash = doc
+        .as_ref()
+        .map(PlanDoc::structure_hash)
+        .unwrap_or_default();
     layout.last_modified = chrono::Utc::now().to_rfc3339();
 
     Ok(layout)
@@ -341,6 +650,7 @@ mod tests {
             plan_hash: "abc123".to_string(),
             layouts,
             last_modified: "2024-01-01T00:00:00Z".to_string(),
+            stale: false,
         };
 
         let json = serde_json::to_string_pretty(&layout_file).unwrap();
@@ -356,22 +666,30 @@ mod tests {
             id: id.to_string(),
             node_type: node_type.to_string(),
             phase_id: phase_id.map(|s| s.to_string()),
+            parent_id: None,
+        }
+    }
+
+    fn create_subtask(id: &str, phase_id: &str, parent_id: &str) -> NodeInfo {
+        NodeInfo {
+            parent_id: Some(parent_id.to_string()),
+            ..create_node(id, "task", Some(phase_id))
         }
     }
 
     #[test]
     fn test_generate_layout_empty() {
         let nodes: Vec<NodeInfo> = vec![];
-        let result = generate_layout(nodes, "hash123".to_string()).unwrap();
+        let result = generate_layout(String::new(), nodes, None).unwrap();
 
         assert!(result.layouts.is_empty());
-        assert_eq!(result.plan_hash, "hash123");
+        assert!(result.plan_hash.is_empty());
     }
 
     #[test]
     fn test_generate_layout_single_phase() {
         let nodes = vec![create_node("phase_0", "phase", None)];
-        let result = generate_layout(nodes, "hash123".to_string()).unwrap();
+        let result = generate_layout(String::new(), nodes, None).unwrap();
 
         assert_eq!(result.layouts.len(), 1);
         assert!(result.layouts.contains_key("phase_0"));
@@ -389,7 +707,7 @@ mod tests {
             create_node("t1", "task", Some("phase_0")),
             create_node("t2", "task", Some("phase_0")),
         ];
-        let result = generate_layout(nodes, "hash".to_string()).unwrap();
+        let result = generate_layout(String::new(), nodes, None).unwrap();
 
         assert_eq!(result.layouts.len(), 3);
 
@@ -410,7 +728,7 @@ mod tests {
             create_node("t1", "task", Some("phase_0")),
             create_node("t2", "task", Some("phase_1")),
         ];
-        let result = generate_layout(nodes, "hash".to_string()).unwrap();
+        let result = generate_layout(String::new(), nodes, None).unwrap();
 
         assert_eq!(result.layouts.len(), 4);
 
@@ -429,7 +747,7 @@ mod tests {
             .chain(std::iter::once(create_node("phase_0", "phase", None)))
             .collect();
 
-        let result = generate_layout(nodes, "hash".to_string()).unwrap();
+        let result = generate_layout(String::new(), nodes, None).unwrap();
 
         // First row: t0, t1, t2
         let t0 = result.layouts.get("t0").unwrap();
@@ -446,6 +764,27 @@ mod tests {
         assert_eq!(t2.x, t0.x + 2.0 * GRID_SPACING_X);
     }
 
+    #[test]
+    fn test_column_strategy() {
+        let nodes = vec![
+            create_node("phase_0", "phase", None),
+            create_node("t1", "task", Some("phase_0")),
+            create_node("t2", "task", Some("phase_0")),
+        ];
+        let result =
+            generate_layout(String::new(), nodes, Some(LayoutStrategy::Column)).unwrap();
+
+        let t1 = result.layouts.get("t1").unwrap();
+        let t2 = result.layouts.get("t2").unwrap();
+        assert_eq!(t2.x, t1.x);
+        assert_eq!(t2.y, t1.y + GRID_SPACING_Y);
+        assert_eq!(
+            LayoutStrategy::parse("Column"),
+            Some(LayoutStrategy::Column)
+        );
+        assert_eq!(LayoutStrategy::parse("radial"), None);
+    }
+
     #[test]
     fn test_merge_layout_removes_orphans() {
         use tempfile::tempdir;
@@ -470,12 +809,7 @@ mod tests {
 
         // Merge with plan that only has t1
         let nodes = vec![create_node("t1", "task", Some("phase_0"))];
-        let result = merge_layout(
-            plan_path.to_string_lossy().to_string(),
-            nodes,
-            "new_hash".to_string(),
-        )
-        .unwrap();
+        let result = merge_layout(plan_path.to_string_lossy().to_string(), nodes).unwrap();
 
         // Orphan should be removed
         assert!(!result.layout.layouts.contains_key("orphan_node"));
@@ -511,12 +845,7 @@ mod tests {
             create_node("t1", "task", Some("phase_0")),
             create_node("t2", "task", Some("phase_0")),
         ];
-        let result = merge_layout(
-            plan_path.to_string_lossy().to_string(),
-            nodes,
-            "new_hash".to_string(),
-        )
-        .unwrap();
+        let result = merge_layout(plan_path.to_string_lossy().to_string(), nodes).unwrap();
 
         // New nodes should be added
         assert!(result.layout.layouts.contains_key("phase_0"));
@@ -556,12 +885,7 @@ mod tests {
             create_node("phase_0", "phase", None),
             create_node("t1", "task", Some("phase_0")),
         ];
-        let result = merge_layout(
-            plan_path.to_string_lossy().to_string(),
-            nodes,
-            "new_hash".to_string(),
-        )
-        .unwrap();
+        let result = merge_layout(plan_path.to_string_lossy().to_string(), nodes).unwrap();
 
         // Positions should be preserved exactly
         let phase = result.layout.layouts.get("phase_0").unwrap();
@@ -577,17 +901,65 @@ mod tests {
         assert!(result.removed_nodes.is_empty());
     }
 
+    #[test]
+    fn test_read_layout_flags_stale_layout() {
+        use tempfile::tempdir;
+
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let path = plan_path.to_string_lossy().to_string();
+        fs::write(&plan_path, "## Phase 1 — A\n- [ ] One (id: t1)\n").unwrap();
+
+        let nodes = crate::plan::read_plan_file(&path).unwrap().nodes();
+        let merged = merge_layout(path.clone(), nodes).unwrap();
+        write_layout(path.clone(), merged.layout).unwrap();
+        assert!(!read_layout(path.clone()).unwrap().stale);
+
+        // Prose edits keep the layout current
+        fs::write(
+            &plan_path,
+            "## Phase 1 — Renamed\n\n- [x] One, reworded (id: t1)\n",
+        )
+        .unwrap();
+        assert!(!read_layout(path.clone()).unwrap().stale);
+
+        // A new task doesn't
+        fs::write(
+            &plan_path,
+            "## Phase 1 — A\n- [ ] One (id: t1)\n- [ ] Two (id: t2)\n",
+        )
+        .unwrap();
+        let layout = read_layout(path.clone()).unwrap();
+        assert!(layout.stale);
+
+        // Merging places the new task but keeps the flag for the frontend to show
+        let nodes = crate::plan::read_plan_file(&path).unwrap().nodes();
+        assert!(merge_layout(path.clone(), nodes).unwrap().layout.stale);
+
+        // Writing ignores the caller's hash and stale flag
+        let layout = LayoutFile {
+            plan_hash: "frontend".to_string(),
+            ..layout
+        };
+        write_layout(path.clone(), layout).unwrap();
+        let layout = read_layout(path).unwrap();
+        assert!(!layout.stale);
+        assert_ne!(layout.plan_hash, "frontend");
+    }
+
     #[test]
     fn test_node_info_serialization() {
         let node = NodeInfo {
             id: "t1".to_string(),
             node_type: "task".to_string(),
             phase_id: Some("phase_0".to_string()),
+            parent_id: None,
         };
 
         let json = serde_json::to_string(&node).unwrap();
         assert!(json.contains("nodeType")); // camelCase
         assert!(json.contains("phaseId")); // camelCase
+        assert!(!json.contains("parentId"));
 
         let parsed: NodeInfo = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.id, "t1");
@@ -595,6 +967,60 @@ mod tests {
         assert_eq!(parsed.phase_id, Some("phase_0".to_string()));
     }
 
+    #[test]
+    fn test_generate_layout_subtasks() {
+        // Subtasks listed before their parent still get placed after it
+        let nodes = vec![
+            create_node("phase_0", "phase", None),
+            create_subtask("t3", "phase_0", "t2"),
+            create_subtask("t2", "phase_0", "t1"),
+            create_subtask("t4", "phase_0", "t1"),
+            create_node("t1", "task", Some("phase_0")),
+            create_node("t5", "task", Some("phase_0")),
+        ];
+        let result = generate_layout(String::new(), nodes, None).unwrap();
+        let get = |id: &str| result.layouts.get(id).unwrap();
+
+        // Subtasks don't take grid cells from top-level tasks
+        assert_eq!(get("t5").x, GRID_START_X + GRID_SPACING_X);
+
+        let (t1, t2, t3, t4) = (get("t1"), get("t2"), get("t3"), get("t4"));
+        assert_eq!(t2.x, t1.x + SUBTASK_INDENT_X);
+        assert_eq!(t2.y, t1.y + t1.height + SUBTASK_SPACING_Y);
+        assert_eq!(t3.x, t2.x + SUBTASK_INDENT_X);
+        assert_eq!(t3.y, t2.y + t2.height + SUBTASK_SPACING_Y);
+        // The second subtask goes below the first one's own subtasks
+        assert_eq!(t4.y, t3.y + t3.height + SUBTASK_SPACING_Y);
+    }
+
+    #[test]
+    fn test_subtasks_push_rows_and_phases_down() {
+        let nodes = vec![
+            create_node("phase_0", "phase", None),
+            create_node("phase_1", "phase", None),
+            create_node("t1", "task", Some("phase_0")),
+            create_subtask("t2", "phase_0", "t1"),
+            create_subtask("t3", "phase_0", "t2"),
+            create_subtask("t4", "phase_0", "t1"),
+            create_node("t5", "task", Some("phase_0")),
+            create_node("t6", "task", Some("phase_1")),
+        ];
+        let result =
+            generate_layout(String::new(), nodes, Some(LayoutStrategy::Column)).unwrap();
+        let get = |id: &str| result.layouts.get(id).unwrap();
+        let bottom = |id: &str| get(id).y + get(id).height;
+
+        // The next task starts below the whole subtask stack
+        assert!(get("t5").y > bottom("t4"));
+        assert_eq!(
+            get("t5").y - bottom("t4"),
+            GRID_SPACING_Y - DEFAULT_NODE_HEIGHT
+        );
+        // And so does the next phase
+        assert!(get("phase_1").y > bottom("t5"));
+        assert!(get("t6").y > get("phase_1").y);
+    }
+
     #[test]
     fn test_merge_result_serialization() {
         let result = MergeResult {
@@ -0,0 +1,810 @@
+//! Plan linting
+//!
+//! Checks a plan for problems the frontend parser either rejects or silently
+//! ignores: duplicate IDs, unknown and cyclic dependencies, tasks without an
+//! ID and phases numbered out of order. Each diagnostic carries a rule code,
+//! a line/column range and, where possible, suggested text edits.
+//!
+//! Missing IDs and phase order problems also get a fix that normalizes the
+//! whole plan (see `plan::normalize`).
+//!
+//! Cross-plan dependencies (`path#id`) and included files can only be checked
+//! when the plan's location is known, so `lint` skips them and `lint_file`
+//! reads the files they point at. Problems inside included files are left to
+//! linting those files; their IDs still count.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::plan::crossplan::{CrossPlanRef, ReferencedPlans};
+use crate::plan::include::{self, find_includes};
+use crate::plan::normalize::{self, IdAllocator};
+use crate::plan::{self, PlanDoc, PlanTask, Span};
+
+/// Rule codes
+pub const DUPLICATE_ID: &str = "duplicate-id";
+pub const UNKNOWN_DEPENDENCY: &str = "unknown-dependency";
+pub const DEPENDENCY_CYCLE: &str = "dependency-cycle";
+pub const MISSING_ID: &str = "missing-id";
+pub const PHASE_ORDER: &str = "phase-order";
+pub const BROKEN_INCLUDE: &str = "broken-include";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        }
+    }
+}
+
+/// 1-based line and character column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Range within the plan; `end` is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Replace the text in `range` with `new_text`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+/// A suggested fix made of one or more edits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fix {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Rule code, e.g. "unknown-dependency"
+    pub code: String,
+    pub message: String,
+    pub range: Range,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<Fix>,
+}
+
+/// A source line with helpers for turning byte offsets into ranges
+struct Line<'a> {
+    number: usize,
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    fn new(markdown: &'a str, span: Span) -> Self {
+        Self {
+            number: span.line,
+            text: &markdown[span.start..span.end],
+        }
+    }
+
+    fn position(&self, byte: usize) -> Position {
+        Position {
+            line: self.number,
+            column: self.text[..byte].chars().count() + 1,
+        }
+    }
+
+    fn range(&self, start: usize, end: usize) -> Range {
+        Range {
+            start: self.position(start),
+            end: self.position(end),
+        }
+    }
+
+    fn whole(&self) -> Range {
+        self.range(0, self.text.len())
+    }
+
+    /// Byte range of `needle` at or after `from`
+    fn find(&self, needle: &str, from: usize) -> Option<(usize, usize)> {
+        let start = from + self.text[from..].find(needle)?;
+        Some((start, start + needle.len()))
+    }
+
+    /// Byte range of the `(depends: ...)` annotation
+    fn depends_annotation(&self) -> Option<(usize, usize)> {
+        let (start, _) = self.find("(depends:", 0)?;
+        let (_, end) = self.find(")", start)?;
+        Some((start, end))
+    }
+
+    /// Byte range of a dependency ID inside the `(depends: ...)` annotation
+    fn dependency(&self, dep: &str) -> Option<(usize, usize)> {
+        let (start, end) = self.depends_annotation()?;
+        let mut from = start + "(depends:".len();
+        while let Some((s, e)) = self.find(dep, from) {
+            if e > end {
+                return None;
+            }
+            let bounded = |c: Option<char>| c.is_none_or(|c| matches!(c, ',' | ' ' | ':' | ')'));
+            if bounded(self.text[..s].chars().next_back()) && bounded(self.text[e..].chars().next())
+            {
+                return Some((s, e));
+            }
+            from = e;
+        }
+        None
+    }
+
+    /// Byte range of the ID inside the `(id: ...)` annotation
+    fn task_id(&self, id: &str) -> Option<(usize, usize)> {
+        let start = self.text.rfind("(id:")?;
+        self.find(id, start)
+    }
+
+    /// Edit rewriting the `(depends: ...)` annotation without `dep`
+    fn remove_dependency(&self, deps: &[String], dep: &str) -> Option<TextEdit> {
+        let (mut start, end) = self.depends_annotation()?;
+        let remaining: Vec<&str> = deps
+            .iter()
+            .map(String::as_str)
+            .filter(|d| *d != dep)
+            .collect();
+
+        let new_text = if remaining.is_empty() {
+            // Drop the annotation along with the space before it
+            if self.text[..start].ends_with(' ') {
+                start -= 1;
+            }
+            String::new()
+        } else {
+            format!("(depends: {})", remaining.join(", "))
+        };
+
+        Some(TextEdit {
+            range: self.range(start, end),
+            new_text,
+        })
+    }
+}
+
+/// Where an ID was first used: a line of this file, or None for an included file
+fn first_use(first: Option<usize>) -> String {
+    match first {
+        Some(line) => format!("first used on line {}", line),
+        None => "first used in an included file".to_string(),
+    }
+}
+
+fn check_duplicate_ids(markdown: &str, doc: &PlanDoc, ids: &mut IdAllocator) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut first_lines: HashMap<&str, Option<usize>> = HashMap::new();
+
+    for phase in &doc.phases {
+        let Some(&first) = first_lines.get(phase.id.as_str()) else {
+            first_lines.insert(&phase.id, phase.span.map(|span| span.line));
+            continue;
+        };
+        let Some(span) = phase.span else { continue };
+
+        let line = Line::new(markdown, span);
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            code: DUPLICATE_ID.to_string(),
+            message: format!("Duplicate phase ID: {} ({})", phase.id, first_use(first)),
+            range: line.whole(),
+            fixes: Vec::new(),
+        });
+    }
+
+    for task in doc.tasks() {
+        let Some(&first) = first_lines.get(task.id.as_str()) else {
+            first_lines.insert(&task.id, task.span.map(|span| span.line));
+            continue;
+        };
+        let Some(span) = task.span else { continue };
+
+        let line = Line::new(markdown, span);
+        let range = line
+            .task_id(&task.id)
+            .map_or_else(|| line.whole(), |(s, e)| line.range(s, e));
+        let new_id = ids.allocate(&task.content);
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            code: DUPLICATE_ID.to_string(),
+            message: format!("Duplicate task ID: {} ({})", task.id, first_use(first)),
+            range,
+            fixes: vec![Fix {
+                title: format!("Rename to {}", new_id),
+                edits: vec![TextEdit {
+                    range,
+                    new_text: new_id,
+                }],
+            }],
+        });
+    }
+
+    diagnostics
+}
+
+/// Check dependencies against this plan, and cross-plan ones against `referenced` when given
+fn check_unknown_dependencies(
+    markdown: &str,
+    doc: &PlanDoc,
+    referenced: Option<&ReferencedPlans>,
+) -> Vec<Diagnostic> {
+    let known: HashSet<&str> = doc
+        .tasks()
+        .map(|t| t.id.as_str())
+        .chain(doc.phases.iter().map(|p| p.id.as_str()))
+        .collect();
+    let mut diagnostics = Vec::new();
+
+    for task in doc.tasks() {
+        let Some(span) = task.span else { continue };
+        let line = Line::new(markdown, span);
+
+        for dep in &task.dependencies {
+            let message = match CrossPlanRef::parse(dep) {
+                Some(reference) => {
+                    let Some(Err(e)) = referenced.map(|r| r.task(&reference)) else {
+                        continue;
+                    };
+                    format!("Task \"{}\" depends on \"{}\": {}", task.id, dep, e)
+                }
+                None if known.contains(dep.as_str()) => continue,
+                None => format!("Task \"{}\" depends on unknown task \"{}\"", task.id, dep),
+            };
+            let range = line
+                .dependency(dep)
+                .map_or_else(|| line.whole(), |(s, e)| line.range(s, e));
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                code: UNKNOWN_DEPENDENCY.to_string(),
+                message,
+                range,
+                fixes: line
+                    .remove_dependency(&task.dependencies, dep)
+                    .map(|edit| Fix {
+                        title: format!("Remove dependency on {}", dep),
+                        edits: vec![edit],
+                    })
+                    .into_iter()
+                    .collect(),
+            });
+        }
+    }
+
+    diagnostics
+}
+
+fn check_cycles(markdown: &str, doc: &PlanDoc) -> Vec<Diagnostic> {
+    /// DFS state per task
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        Active,
+        Done,
+    }
+
+    let tasks: Vec<_> = doc.tasks().collect();
+    let index: HashMap<&str, usize> = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .rev()
+        .collect();
+    let mut state = vec![Visit::New; tasks.len()];
+    let mut diagnostics = Vec::new();
+
+    // Iterative DFS over dependency edges; a dependency on an active task closes a cycle
+    for root in 0..tasks.len() {
+        if state[root] != Visit::New {
+            continue;
+        }
+        let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+        state[root] = Visit::Active;
+
+        while let Some((node, next_dep)) = stack.last().copied() {
+            let deps = &tasks[node].dependencies;
+            if next_dep == deps.len() {
+                state[node] = Visit::Done;
+                stack.pop();
+                continue;
+            }
+            stack.last_mut().unwrap().1 += 1;
+
+            let dep = &deps[next_dep];
+            let Some(&target) = index.get(dep.as_str()) else {
+                continue;
+            };
+
+            match state[target] {
+                Visit::New => {
+                    state[target] = Visit::Active;
+                    stack.push((target, 0));
+                }
+                Visit::Active => {
+                    let start = stack.iter().position(|(n, _)| *n == target).unwrap_or(0);
+                    let mut cycle: Vec<&str> = stack[start..]
+                        .iter()
+                        .map(|(n, _)| tasks[*n].id.as_str())
+                        .collect();
+                    cycle.push(&tasks[target].id);
+
+                    let task = tasks[node];
+                    let Some(span) = task.span else { continue };
+                    let line = Line::new(markdown, span);
+                    let range = line
+                        .dependency(dep)
+                        .map_or_else(|| line.whole(), |(s, e)| line.range(s, e));
+                    diagnostics.push(Diagnostic {
+                        severity: Severity::Error,
+                        code: DEPENDENCY_CYCLE.to_string(),
+                        message: format!("Dependency cycle: {}", cycle.join(" → ")),
+                        range,
+                        fixes: line
+                            .remove_dependency(&task.dependencies, dep)
+                            .map(|edit| Fix {
+                                title: format!("Remove dependency of {} on {}", task.id, dep),
+                                edits: vec![edit],
+                            })
+                            .into_iter()
+                            .collect(),
+                    });
+                }
+                Visit::Done => {}
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn check_missing_ids(markdown: &str, doc: &PlanDoc, ids: &mut IdAllocator) -> Vec<Diagnostic> {
+    doc.tasks_without_id
+        .iter()
+        .map(|span| {
+            let line = Line::new(markdown, *span);
+            let end = line.text.trim_end().len();
+            let content = plan::parser::parse_checklist_line(line.text)
+                .map(|item| plan::parser::clean_task_content(item.text()))
+                .unwrap_or_default();
+            let new_id = ids.allocate(&content);
+            Diagnostic {
+                severity: Severity::Warning,
+                code: MISSING_ID.to_string(),
+                message: "Task has no valid (id: ...) and will not appear on the canvas"
+                    .to_string(),
+                range: line.whole(),
+                fixes: vec![Fix {
+                    title: format!("Add (id: {})", new_id),
+                    edits: vec![TextEdit {
+                        range: line.range(end, end),
+                        new_text: format!(" (id: {})", new_id),
+                    }],
+                }],
+            }
+        })
+        .collect()
+}
+
+fn check_phase_order(markdown: &str, doc: &PlanDoc) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let Some(first) = doc
+        .phases
+        .first()
+        .and_then(|p| p.number.parse::<u64>().ok())
+    else {
+        return diagnostics;
+    };
+
+    for (offset, phase) in doc.phases.iter().enumerate() {
+        let expected = first + offset as u64;
+        if phase.number.parse::<u64>().ok() == Some(expected) {
+            continue;
+        }
+        let Some(span) = phase.span else { continue };
+
+        let line = Line::new(markdown, span);
+        let range = line
+            .find("Phase", 0)
+            .and_then(|(_, end)| line.find(&phase.number, end))
+            .map_or_else(|| line.whole(), |(s, e)| line.range(s, e));
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            code: PHASE_ORDER.to_string(),
+            message: format!(
+                "Phase {} is out of order; expected Phase {}",
+                phase.number, expected
+            ),
+            range,
+            fixes: vec![Fix {
+                title: format!("Renumber to Phase {} (changes the phase ID)", expected),
+                edits: vec![TextEdit {
+                    range,
+                    new_text: expected.to_string(),
+                }],
+            }],
+        });
+    }
+
+    diagnostics
+}
+
+/// Report include directives whose file isn't part of the plan: it can't be read,
+/// or it is already included (including the plan itself, which would be a cycle)
+fn check_includes(markdown: &str, doc: &PlanDoc, plan_path: &Path) -> Vec<Diagnostic> {
+    let mut resolved = HashSet::new();
+    find_includes(markdown)
+        .into_iter()
+        .filter_map(|directive| {
+            let path = directive.resolve(plan_path);
+            if resolved.insert(path.clone()) && doc.included_files().any(|file| file == path) {
+                return None;
+            }
+            let message = match fs::metadata(&path) {
+                Ok(_) => format!("{} is already part of the plan", directive.path),
+                Err(e) => format!("Failed to read included file {}: {}", directive.path, e),
+            };
+            Some(Diagnostic {
+                severity: Severity::Error,
+                code: BROKEN_INCLUDE.to_string(),
+                message,
+                range: Line::new(markdown, directive.span).whole(),
+                fixes: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Drop the spans of phases from included files and their tasks
+/// They still count for IDs and dependencies, but are reported in their own files
+fn forget_included_spans(doc: &mut PlanDoc) {
+    fn forget(tasks: &mut [PlanTask]) {
+        for task in tasks {
+            task.span = None;
+            forget(&mut task.subtasks);
+        }
+    }
+    for phase in doc.phases.iter_mut().filter(|p| p.file.is_some()) {
+        phase.span = None;
+        forget(&mut phase.tasks);
+    }
+}
+
+/// Whole-line edits turning `markdown` into its normalized form, offered on
+/// missing-ID and phase-order diagnostics to fix them all at once
+fn normalize_fix(markdown: &str, doc: &PlanDoc) -> Option<Fix> {
+    let normalized = normalize::normalize(markdown, doc.settings.id_scheme.unwrap_or_default());
+    if normalized.changes.is_empty() {
+        return None;
+    }
+    let old_lines: Vec<&str> = markdown.split('\n').collect();
+    let new_lines: Vec<&str> = normalized.markdown.split('\n').collect();
+    if old_lines.len() != new_lines.len() {
+        return None;
+    }
+
+    let edits = old_lines
+        .iter()
+        .zip(&new_lines)
+        .enumerate()
+        .filter(|(_, (old, new))| old != new)
+        .map(|(index, (old, new))| {
+            let old = old.trim_end_matches('\r');
+            let position = |column| Position {
+                line: index + 1,
+                column,
+            };
+            TextEdit {
+                range: Range {
+                    start: position(1),
+                    end: position(old.chars().count() + 1),
+                },
+                new_text: new.trim_end_matches('\r').to_string(),
+            }
+        })
+        .collect();
+    Some(Fix {
+        title: format!("Normalize plan ({} changes)", normalized.changes.len()),
+        edits,
+    })
+}
+
+/// Lint plan markdown; diagnostics are sorted by position
+/// Cross-plan dependencies aren't checked, as there is no path to resolve them against
+pub fn lint(markdown: &str) -> Vec<Diagnostic> {
+    lint_at(markdown, None)
+}
+
+/// Lint plan markdown stored at `plan_path`, if known
+/// With a known location, the phases of included files are read as well
+fn lint_at(markdown: &str, plan_path: Option<&Path>) -> Vec<Diagnostic> {
+    let mut doc = match plan_path {
+        Some(path) => include::load_with(path, &|file| {
+            if file == path {
+                Ok(markdown.to_string())
+            } else {
+                fs::read_to_string(file)
+            }
+        })
+        .unwrap_or_else(|_| plan::parse(markdown)),
+        None => plan::parse(markdown),
+    };
+    forget_included_spans(&mut doc);
+    let referenced = plan_path.map(|path| ReferencedPlans::load(path, &doc));
+    let mut ids = IdAllocator::new(&doc, doc.settings.id_scheme.unwrap_or_default());
+
+    let mut diagnostics = check_duplicate_ids(markdown, &doc, &mut ids);
+    diagnostics.extend(check_unknown_dependencies(
+        markdown,
+        &doc,
+        referenced.as_ref(),
+    ));
+    diagnostics.extend(check_cycles(markdown, &doc));
+    diagnostics.extend(check_missing_ids(markdown, &doc, &mut ids));
+    diagnostics.extend(check_phase_order(markdown, &doc));
+    if let Some(path) = plan_path {
+        diagnostics.extend(check_includes(markdown, &doc, path));
+    }
+
+    // Normalizing this file alone would renumber phases without their included neighbours
+    let fixable = |d: &Diagnostic| d.code == MISSING_ID || d.code == PHASE_ORDER;
+    if diagnostics.iter().any(fixable) && doc.included_files().next().is_none() {
+        if let Some(fix) = normalize_fix(markdown, &doc) {
+            for diagnostic in diagnostics.iter_mut().filter(|d| fixable(d)) {
+                diagnostic.fixes.push(fix.clone());
+            }
+        }
+    }
+
+    diagnostics.sort_by(|a, b| {
+        a.range
+            .start
+            .cmp(&b.range.start)
+            .then(a.severity.cmp(&b.severity))
+    });
+    diagnostics
+}
+
+/// Lint a plan file, including the tasks its cross-plan dependencies point at
+pub fn lint_file(plan_path: &str) -> Result<Vec<Diagnostic>, String> {
+    let markdown =
+        fs::read_to_string(plan_path).map_err(|e| format!("Failed to read plan file: {}", e))?;
+    Ok(lint_at(&markdown, Some(Path::new(plan_path))))
+}
+
+/// Format a diagnostic as `path:line:column: severity[code]: message`
+pub fn format_diagnostic(plan_path: &str, diagnostic: &Diagnostic) -> String {
+    let mut text = format!(
+        "{}:{}:{}: {}[{}]: {}",
+        plan_path,
+        diagnostic.range.start.line,
+        diagnostic.range.start.column,
+        diagnostic.severity.as_str(),
+        diagnostic.code,
+        diagnostic.message
+    );
+    for fix in &diagnostic.fixes {
+        text.push_str(&format!("\n  fix: {}", fix.title));
+    }
+    text
+}
+
+/// Lint a plan file
+#[tauri::command]
+pub fn lint_plan(plan_path: String) -> Result<Vec<Diagnostic>, String> {
+    lint_file(&plan_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE_PLAN: &str = include_str!("../../src/parser/__tests__/fixtures/simple-plan.md");
+    const COMPLEX_PLAN: &str = include_str!("../../src/parser/__tests__/fixtures/complex-plan.md");
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_fixtures_are_clean() {
+        assert!(lint(SIMPLE_PLAN).is_empty());
+        assert!(lint(COMPLEX_PLAN).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_dependency() {
+        let markdown = "## Phase 1 — A\n- [ ] One (id: t1)\n- [ ] Two (depends: t1, t9) (id: t2)\n";
+        let diagnostics = lint(markdown);
+
+        assert_eq!(codes(&diagnostics), vec![UNKNOWN_DEPENDENCY]);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            diagnostic.range.start,
+            Position {
+                line: 3,
+                column: 25
+            }
+        );
+        assert_eq!(diagnostic.range.end.column, 27);
+
+        let edit = &diagnostic.fixes[0].edits[0];
+        assert_eq!(edit.new_text, "(depends: t1)");
+        assert_eq!(edit.range.start.column, 11);
+    }
+
+    #[test]
+    fn test_dependency_cycle() {
+        let markdown = "## Phase 1 — A\n- [ ] One (depends: t3) (id: t1)\n- [ ] Two (depends: t1) (id: t2)\n- [ ] Three (depends: t2) (id: t3)\n- [ ] Self (depends: t4) (id: t4)\n";
+        let diagnostics = lint(markdown);
+
+        assert_eq!(
+            codes(&diagnostics),
+            vec![DEPENDENCY_CYCLE, DEPENDENCY_CYCLE]
+        );
+        assert!(diagnostics[0].message.contains("t1 → t3 → t2 → t1"));
+        assert_eq!(diagnostics[1].range.start.line, 5);
+        // Removing the only dependency drops the whole annotation
+        assert_eq!(diagnostics[1].fixes[0].edits[0].new_text, "");
+    }
+
+    #[test]
+    fn test_duplicate_and_missing_ids() {
+        let markdown = "## Phase 1 — A\n- [ ] One (id: t1)\n- [ ] Again (id: t1)\n- [ ] No id\n";
+        let diagnostics = lint(markdown);
+
+        assert_eq!(codes(&diagnostics), vec![DUPLICATE_ID, MISSING_ID]);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].fixes[0].edits[0].new_text, "t2");
+
+        let missing = &diagnostics[1];
+        assert_eq!(missing.severity, Severity::Warning);
+        let edit = &missing.fixes[0].edits[0];
+        assert_eq!(edit.new_text, " (id: t3)");
+        assert_eq!(
+            edit.range.start,
+            Position {
+                line: 4,
+                column: 12
+            }
+        );
+    }
+
+    #[test]
+    fn test_normalize_fix() {
+        let markdown =
+            "## Phase 1 — A\n- [ ] One\n## Phase 3 — B\n- [ ] Two (depends: phase_3) (id: t1)\n";
+        let diagnostics = lint(markdown);
+        assert_eq!(codes(&diagnostics), vec![MISSING_ID, PHASE_ORDER]);
+
+        let fix = diagnostics[0].fixes.last().unwrap();
+        assert_eq!(fix, diagnostics[1].fixes.last().unwrap());
+        assert_eq!(fix.title, "Normalize plan (3 changes)");
+        let new_lines: Vec<&str> = fix.edits.iter().map(|e| e.new_text.as_str()).collect();
+        assert_eq!(
+            new_lines,
+            vec![
+                "- [ ] One (id: t2)",
+                "## Phase 2 — B",
+                "- [ ] Two (depends: phase_2) (id: t1)"
+            ]
+        );
+        assert_eq!(
+            fix.edits[0].range.end,
+            Position {
+                line: 2,
+                column: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_phase_order() {
+        let markdown = "## Phase 1 — A\n## Phase 3 — B\n## Phase 2 — C\n";
+        let diagnostics = lint(markdown);
+
+        assert_eq!(codes(&diagnostics), vec![PHASE_ORDER, PHASE_ORDER]);
+        assert_eq!(
+            diagnostics[0].range.start,
+            Position {
+                line: 2,
+                column: 10
+            }
+        );
+        assert_eq!(diagnostics[0].fixes[0].edits[0].new_text, "2");
+    }
+
+    #[test]
+    fn test_cross_plan_dependencies() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(
+            temp.path().join("backend.md"),
+            "## Phase 1 — API\n- [ ] Endpoints (id: t12)\n",
+        )
+        .unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let markdown = "## Phase 1 — UI\n- [ ] Screens (depends: backend.md#t12, backend.md#t13, missing.md#t1) (id: t1)\n";
+        fs::write(&plan_path, markdown).unwrap();
+
+        // Without a location the references can't be checked
+        assert!(lint(markdown).is_empty());
+
+        let diagnostics = lint_file(&plan_path.to_string_lossy()).unwrap();
+        assert_eq!(
+            codes(&diagnostics),
+            vec![UNKNOWN_DEPENDENCY, UNKNOWN_DEPENDENCY]
+        );
+        assert!(diagnostics[0].message.contains("No task \"t13\" in backend.md"));
+        assert_eq!(diagnostics[0].range.start.column, 41);
+        assert!(diagnostics[1].message.contains("missing.md"));
+        assert_eq!(
+            diagnostics[1].fixes[0].edits[0].new_text,
+            "(depends: backend.md#t12, backend.md#t13)"
+        );
+    }
+
+    #[test]
+    fn test_includes() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(
+            temp.path().join("auth.md"),
+            "## Phase 2 — Auth\n- [ ] Login (id: t2)\n- [ ] No id\n",
+        )
+        .unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let markdown = "## Phase 1 — Setup\n- [ ] Init (id: t1)\n<!-- include: auth.md -->\n<!-- include: auth.md -->\n<!-- include: missing.md -->\n## Phase 3 — Ship\n- [ ] Ship (depends: t1) (id: t2)\n";
+        fs::write(&plan_path, markdown).unwrap();
+
+        // Without a location the included phases are unknown
+        assert_eq!(codes(&lint(markdown)), vec![PHASE_ORDER]);
+
+        // Included IDs count, but problems inside auth.md are reported there
+        let diagnostics = lint_file(&plan_path.to_string_lossy()).unwrap();
+        assert_eq!(
+            codes(&diagnostics),
+            vec![BROKEN_INCLUDE, BROKEN_INCLUDE, DUPLICATE_ID]
+        );
+        assert_eq!(diagnostics[0].range.start.line, 4);
+        assert_eq!(
+            diagnostics[0].message,
+            "auth.md is already part of the plan"
+        );
+        assert!(diagnostics[1]
+            .message
+            .starts_with("Failed to read included file missing.md"));
+        assert!(diagnostics[2]
+            .message
+            .contains("first used in an included file"));
+    }
+
+    #[test]
+    fn test_format_diagnostic() {
+        let diagnostics = lint("## Phase 1 — A\n- [ ] Two (depends: t9) (id: t2)\n");
+        let text = format_diagnostic("plan.md", &diagnostics[0]);
+        assert!(text.starts_with("plan.md:2:21: error[unknown-dependency]:"));
+        assert!(text.contains("fix: Remove dependency on t9"));
+    }
+}
@@ -8,6 +8,10 @@ mod cli;
 mod credentials;
 mod history;
 mod layout;
+mod lint;
+mod merge;
+mod outline;
+mod plan;
 mod preferences;
 mod pty;
 mod watcher;
@@ -29,12 +33,40 @@ fn main() {
             layout::write_layout,
             layout::merge_layout,
             layout::generate_layout,
+            merge::merge_plan,
+            lint::lint_plan,
+            plan::read_plan,
+            plan::graph::analyze_plan,
+            plan::stats::get_plan_stats,
+            plan::edit::set_task_status,
+            plan::edit::set_task_text,
+            plan::edit::set_task_meta,
+            plan::edit::add_task,
+            plan::edit::remove_task,
+            plan::edit::move_task,
+            plan::edit::add_phase,
+            plan::edit::remove_phase,
+            plan::edit::rename_phase,
+            plan::edit::add_dependency,
+            plan::edit::remove_dependency,
+            plan::normalize::normalize_plan,
             history::get_latest_snapshot,
             history::list_snapshot_timestamps,
             history::clear_snapshots,
+            history::restore_snapshot,
+            history::gc::gc_history,
+            history::replay::replay_plan_history,
+            history::create_checkpoint,
+            history::list_checkpoints,
+            history::restore_checkpoint,
+            history::delete_checkpoint,
+            history::migrate_history,
             watcher::start_watching,
             watcher::stop_watching,
             watcher::get_watched_plan,
+            watcher::add_watched_plan,
+            watcher::remove_watched_plan,
+            watcher::list_watched_plans,
             chat::send_chat_message,
             pty::pty_create_session,
             pty::pty_spawn,
@@ -57,7 +89,10 @@ fn main() {
             preferences::set_last_plan,
             preferences::set_plan_agent,
             preferences::get_plan_agent,
+            preferences::get_plan_defaults,
             preferences::set_default_agent,
+            preferences::set_history_storage,
+            preferences::set_watcher_backend,
             preferences::get_recent_plans,
             preferences::remove_recent_plan,
             preferences::clear_preferences,
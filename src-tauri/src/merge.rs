@@ -0,0 +1,382 @@
+//! Three-way merge of plan markdown
+//!
+//! Used when plan.md changes on disk while the canvas has unsaved edits.
+//! Lines are merged diff3-style, with every task line standing in for its task
+//! ID; tasks are then merged field by field (indentation, checkbox, text), so
+//! a status change on one side and a rewording on the other merge cleanly.
+//! Tasks both sides added at the same spot are all kept, ours first; only a
+//! task added on both sides with the same ID and different lines conflicts.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::outline;
+
+/// Why a merge couldn't be resolved automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Both sides changed the same task differently
+    Task,
+    /// One side removed a task the other side modified
+    RemovedModified,
+    /// Both sides changed the same non-task lines differently
+    Text,
+}
+
+/// A conflict left for the user to resolve
+/// The merged markdown keeps our side of every conflict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeConflict {
+    pub kind: ConflictKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    /// 1-based line in the merged markdown where our side was kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ours: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theirs: Option<String>,
+}
+
+/// Result of a three-way plan merge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanMergeResult {
+    pub merged: String,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// A line for structural merging: task lines compare by task ID only
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Token<'a> {
+    Task(&'a str),
+    Text(&'a str),
+}
+
+fn tokenize(markdown: &str) -> Vec<Token<'_>> {
+    markdown
+        .lines()
+        .map(|line| match outline::task_line_id(line) {
+            Some(id) => Token::Task(id),
+            None => Token::Text(line),
+        })
+        .collect()
+}
+
+/// Task lines of a document keyed by ID (first occurrence wins)
+fn task_lines(markdown: &str) -> HashMap<&str, &str> {
+    let mut lines = HashMap::new();
+    for line in markdown.lines() {
+        if let Some(id) = outline::task_line_id(line) {
+            lines.entry(id).or_insert(line);
+        }
+    }
+    lines
+}
+
+/// For each base index, the matching index in `other` along a longest common subsequence
+fn lcs_matches<T: PartialEq>(base: &[T], other: &[T]) -> Vec<Option<usize>> {
+    let (n, m) = (base.len(), other.len());
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if base[i] == other[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut matches = vec![None; n];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if base[i] == other[j] {
+            matches[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
+
+/// Pick the side that changed, or None when both changed differently
+fn three_way<'a, T: PartialEq + ?Sized>(base: &'a T, ours: &'a T, theirs: &'a T) -> Option<&'a T> {
+    if ours == theirs || theirs == base {
+        Some(ours)
+    } else if ours == base {
+        Some(theirs)
+    } else {
+        None
+    }
+}
+
+/// A task line split into indentation/bullet, checkbox mark and text
+fn split_task_line(line: &str) -> Option<(&str, &str, &str)> {
+    let open = line.find('[')?;
+    let close = open + line[open..].find(']')?;
+    Some((&line[..=open], &line[open + 1..close], &line[close..]))
+}
+
+/// Merge the three versions of a task line field by field
+fn merge_task_line(base: &str, ours: &str, theirs: &str) -> Option<String> {
+    if let Some(line) = three_way(base, ours, theirs) {
+        return Some(line.to_string());
+    }
+
+    let (b, o, t) = (
+        split_task_line(base)?,
+        split_task_line(ours)?,
+        split_task_line(theirs)?,
+    );
+    Some(format!(
+        "{}{}{}",
+        three_way(b.0, o.0, t.0)?,
+        three_way(b.1, o.1, t.1)?,
+        three_way(b.2, o.2, t.2)?
+    ))
+}
+
+/// Whether a chunk consists of task lines only
+fn only_tasks(tokens: &[Token]) -> bool {
+    tokens.iter().all(|token| matches!(token, Token::Task(_)))
+}
+
+fn join(lines: &[&str]) -> Option<String> {
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Three-way merge plan markdown
+/// `base` is the version last loaded, `ours` the local edits and `theirs` the file on disk
+pub fn merge_plans(base: &str, ours: &str, theirs: &str) -> PlanMergeResult {
+    let (base_tokens, our_tokens, their_tokens) =
+        (tokenize(base), tokenize(ours), tokenize(theirs));
+    let our_matches = lcs_matches(&base_tokens, &our_tokens);
+    let their_matches = lcs_matches(&base_tokens, &their_tokens);
+    let (base_lines, our_lines, their_lines): (Vec<&str>, Vec<&str>, Vec<&str>) = (
+        base.lines().collect(),
+        ours.lines().collect(),
+        theirs.lines().collect(),
+    );
+
+    let mut merged: Vec<Token> = Vec::new();
+    let mut conflicts = Vec::new();
+    let (mut i, mut j, mut k) = (0, 0, 0);
+
+    loop {
+        // Next base line kept by both sides, or the end of all three documents
+        let stable =
+            (i..base_tokens.len()).find_map(|b| Some((b, our_matches[b]?, their_matches[b]?)));
+        let (b, o, t) = stable.unwrap_or((base_tokens.len(), our_tokens.len(), their_tokens.len()));
+
+        let base_chunk = &base_tokens[i..b];
+        let our_chunk = &our_tokens[j..o];
+        let their_chunk = &their_tokens[k..t];
+
+        match three_way(base_chunk, our_chunk, their_chunk) {
+            Some(chunk) => merged.extend_from_slice(chunk),
+            None if base_chunk.is_empty() && only_tasks(our_chunk) && only_tasks(their_chunk) => {
+                merged.extend_from_slice(our_chunk);
+                merged.extend_from_slice(their_chunk);
+            }
+            None => {
+                conflicts.push(MergeConflict {
+                    kind: ConflictKind::Text,
+                    task_id: None,
+                    line: Some(merged.len() + 1),
+                    base: join(&base_lines[i..b]),
+                    ours: join(&our_lines[j..o]),
+                    theirs: join(&their_lines[k..t]),
+                });
+                merged.extend_from_slice(our_chunk);
+            }
+        }
+
+        if stable.is_none() {
+            break;
+        }
+        merged.push(base_tokens[b].clone());
+        (i, j, k) = (b + 1, o + 1, t + 1);
+    }
+
+    // Resolve task lines
+    let (base_tasks, our_tasks, their_tasks) =
+        (task_lines(base), task_lines(ours), task_lines(theirs));
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+
+    for token in merged {
+        let id = match token {
+            Token::Text(line) => {
+                lines.push(line.to_string());
+                continue;
+            }
+            Token::Task(id) => id,
+        };
+
+        // A task added on both sides appears twice in the merged structure
+        if !seen.insert(id) {
+            continue;
+        }
+
+        let (b, o, t) = (base_tasks.get(id), our_tasks.get(id), their_tasks.get(id));
+        let line = match (o, t) {
+            (Some(o), Some(t)) => {
+                let resolved = match b {
+                    Some(b) => merge_task_line(b, o, t),
+                    None => (o == t).then(|| o.to_string()),
+                };
+                resolved.unwrap_or_else(|| {
+                    conflicts.push(MergeConflict {
+                        kind: ConflictKind::Task,
+                        task_id: Some(id.to_string()),
+                        line: Some(lines.len() + 1),
+                        base: b.map(|b| b.to_string()),
+                        ours: Some(o.to_string()),
+                        theirs: Some(t.to_string()),
+                    });
+                    o.to_string()
+                })
+            }
+            (Some(line), None) | (None, Some(line)) => line.to_string(),
+            (None, None) => continue,
+        };
+        lines.push(line);
+    }
+
+    // Removed on one side but modified on the other; the removal was kept
+    for (id, b) in &base_tasks {
+        let (o, t) = (our_tasks.get(id), their_tasks.get(id));
+        let modified = match (o, t) {
+            (None, Some(t)) => t != b,
+            (Some(o), None) => o != b,
+            _ => false,
+        };
+        if modified && !seen.contains(id) {
+            conflicts.push(MergeConflict {
+                kind: ConflictKind::RemovedModified,
+                task_id: Some(id.to_string()),
+                line: None,
+                base: Some(b.to_string()),
+                ours: o.map(|o| o.to_string()),
+                theirs: t.map(|t| t.to_string()),
+            });
+        }
+    }
+
+    let trailing_newline = *three_way(
+        &base.ends_with('\n'),
+        &ours.ends_with('\n'),
+        &theirs.ends_with('\n'),
+    )
+    .unwrap_or(&ours.ends_with('\n'));
+
+    let mut merged = lines.join("\n");
+    if trailing_newline && !merged.is_empty() {
+        merged.push('\n');
+    }
+
+    PlanMergeResult { merged, conflicts }
+}
+
+/// Merge local plan edits with a version changed on disk
+#[tauri::command]
+pub fn merge_plan(base: String, ours: String, theirs: String) -> PlanMergeResult {
+    merge_plans(&base, &ours, &theirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str =
+        "# Plan: Test\n\n## Phase 1 — Work\n- [ ] Build (id: t1)\n- [ ] Test (id: t2)\n";
+
+    #[test]
+    fn test_merge_independent_task_edits() {
+        // We reword t1, they complete it and add a task
+        let ours = BASE.replace("Build (id: t1)", "Build the app (id: t1)");
+        let theirs = BASE
+            .replace("- [ ] Build", "- [x] Build")
+            .replace("(id: t2)\n", "(id: t2)\n- [ ] Ship (id: t3)\n");
+
+        let result = merge_plans(BASE, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(
+            result.merged,
+            "# Plan: Test\n\n## Phase 1 — Work\n- [x] Build the app (id: t1)\n- [ ] Test (id: t2)\n- [ ] Ship (id: t3)\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_tasks_added_on_both_sides() {
+        let ours = format!("{}- [ ] Ours (id: t3)\n", BASE);
+        let theirs = format!("{}- [ ] Theirs (id: t4)\n", BASE);
+
+        let result = merge_plans(BASE, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(
+            result.merged,
+            format!("{}- [ ] Ours (id: t3)\n- [ ] Theirs (id: t4)\n", BASE)
+        );
+
+        // The same ID added with different lines is a genuine conflict
+        let theirs = format!("{}- [ ] Theirs (id: t3)\n", BASE);
+        let result = merge_plans(BASE, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].kind, ConflictKind::Task);
+        assert_eq!(result.conflicts[0].task_id.as_deref(), Some("t3"));
+        assert_eq!(result.merged, ours);
+    }
+
+    #[test]
+    fn test_merge_conflicting_status() {
+        let ours = BASE.replace("- [ ] Build", "- [-] Build");
+        let theirs = BASE.replace("- [ ] Build", "- [x] Build");
+
+        let result = merge_plans(BASE, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.kind, ConflictKind::Task);
+        assert_eq!(conflict.task_id.as_deref(), Some("t1"));
+        assert_eq!(conflict.line, Some(4));
+        assert!(result.merged.contains("- [-] Build"));
+    }
+
+    #[test]
+    fn test_merge_removed_and_modified() {
+        let ours = BASE.replace("- [ ] Test (id: t2)\n", "");
+        let theirs = BASE.replace("- [ ] Test", "- [x] Test");
+
+        let result = merge_plans(BASE, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].kind, ConflictKind::RemovedModified);
+        assert!(!result.merged.contains("(id: t2)"));
+    }
+
+    #[test]
+    fn test_merge_text_and_moves() {
+        // We rename the plan, they move t2 above t1
+        let ours = BASE.replace("Plan: Test", "Plan: Renamed");
+        let theirs = BASE.replace(
+            "- [ ] Build (id: t1)\n- [ ] Test (id: t2)\n",
+            "- [ ] Test (id: t2)\n- [ ] Build (id: t1)\n",
+        );
+
+        let result = merge_plans(BASE, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(
+            result.merged,
+            "# Plan: Renamed\n\n## Phase 1 — Work\n- [ ] Test (id: t2)\n- [ ] Build (id: t1)\n"
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Flat plan outline
+//!
+//! Phases and tasks (subtasks included) of a plan as flat lists with string
+//! statuses, derived from the `plan` parser. Used by history replay and change
+//! summaries, which only need IDs, names and statuses.
+
+use serde::{Deserialize, Serialize};
+
+use crate::plan::{self, parser};
+
+/// A phase heading found in the plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlinePhase {
+    pub id: String,
+    pub name: String,
+}
+
+/// A task found in the plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineTask {
+    pub id: String,
+    pub content: String,
+    /// "pending", "in_progress", "completed", "blocked" or "cancelled"
+    pub status: String,
+    pub phase_id: String,
+}
+
+/// Phases and tasks of a plan, in document order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanOutline {
+    pub phases: Vec<OutlinePhase>,
+    pub tasks: Vec<OutlineTask>,
+}
+
+impl PlanOutline {
+    /// Find a task by ID
+    pub fn task(&self, id: &str) -> Option<&OutlineTask> {
+        self.tasks.iter().find(|t| t.id == id)
+    }
+}
+
+/// ID of the task on a checklist line, if the line is one
+pub fn task_line_id(line: &str) -> Option<&str> {
+    parser::extract_task_id(parser::parse_checklist_line(line)?.text())
+}
+
+/// Scan plan markdown into an outline
+/// Tasks without an `(id: ...)` or outside any phase are skipped, as in the frontend
+pub fn scan(markdown: &str) -> PlanOutline {
+    let doc = plan::parse(markdown);
+    let mut outline = PlanOutline::default();
+
+    for phase in &doc.phases {
+        outline.phases.push(OutlinePhase {
+            id: phase.id.clone(),
+            name: phase.name.clone(),
+        });
+        outline.tasks.extend(phase.all_tasks().map(|task| OutlineTask {
+            id: task.id.clone(),
+            content: task.content.clone(),
+            status: task.status.as_str().to_string(),
+            phase_id: phase.id.clone(),
+        }));
+    }
+
+    outline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE_PLAN: &str = include_str!("../../src/parser/__tests__/fixtures/simple-plan.md");
+    const COMPLEX_PLAN: &str = include_str!("../../src/parser/__tests__/fixtures/complex-plan.md");
+
+    #[test]
+    fn test_scan_simple_plan() {
+        let outline = scan(SIMPLE_PLAN);
+
+        assert_eq!(outline.phases.len(), 2);
+        assert_eq!(outline.phases[0].id, "phase_0");
+        assert_eq!(outline.phases[0].name, "Setup");
+
+        assert_eq!(outline.tasks.len(), 4);
+        assert_eq!(outline.task("t1").unwrap().status, "pending");
+        assert_eq!(outline.task("t2").unwrap().status, "completed");
+        assert_eq!(outline.task("t3").unwrap().phase_id, "phase_1");
+    }
+
+    #[test]
+    fn test_scan_strips_annotations() {
+        let outline = scan(COMPLEX_PLAN);
+        let task = outline.task("t6").unwrap();
+        assert_eq!(task.content, "Add validation");
+    }
+
+    #[test]
+    fn test_scan_in_progress_and_skips() {
+        let markdown = "# Plan: X\n\n- [ ] Orphan (id: t0)\n\n## Phase 1 — Work\n- [-] Doing (id: t1)\n- [ ] No id\n";
+        let outline = scan(markdown);
+
+        assert_eq!(outline.tasks.len(), 1);
+        assert_eq!(outline.tasks[0].status, "in_progress");
+    }
+}
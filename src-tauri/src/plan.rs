@@ -0,0 +1,661 @@
+//! Native plan.md model
+//!
+//! Typed representation of the plan format shared with the frontend parser
+//! (`src/parser`): `# Plan: <title>`, `## Phase N — <name>` headings and
+//! `- [ ] task (depends: ...) (id: ...)` checklist items, which nest as
+//! subtasks when indented under another task. Besides `[ ]`, `[-]`
+//! and `[x]`, a task can be marked blocked with `[!]` or cancelled with `[~]`.
+//! Tasks may also carry metadata annotations: `(owner: @sam)`, `(est: 3h)`,
+//! `(due: 2026-11-01)`, `(tags: api, db)`, `(p1)` and, for blocked or cancelled
+//! tasks, `(reason: ...)`. They are plain parenthesized text, so other markdown
+//! readers show them as part of the task. A dependency can also point into
+//! another plan file as `path#id` (see `crossplan`), and a plan can pull in
+//! phases from other files with `<!-- include: ... -->` (see `include`).
+//! Plan-level settings can go in YAML front matter (see `frontmatter`). Tasks
+//! without an ID can be given one, and phases renumbered, by `normalize`.
+//!
+//! Every parsed element remembers its source line, so serializing a parsed
+//! document reproduces the input byte for byte and edits only touch the lines
+//! they change.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::layout::NodeInfo;
+
+pub mod crossplan;
+pub mod edit;
+pub mod frontmatter;
+pub mod graph;
+pub mod include;
+pub mod normalize;
+pub mod parser;
+pub mod serializer;
+pub mod stats;
+
+pub use frontmatter::PlanSettings;
+use include::IncludedFile;
+pub use parser::parse;
+pub use serializer::serialize;
+
+/// Title used when a plan has no `# ` heading
+pub const DEFAULT_TITLE: &str = "Untitled Plan";
+
+/// Task status; serialized the same as the frontend `Status` type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    #[default]
+    Pending,
+    InProgress,
+    Completed,
+    /// Waiting on something outside the plan (`[!]`)
+    Blocked,
+    /// Won't be done (`[~]`); satisfies dependencies like a completed task
+    Cancelled,
+}
+
+impl TaskStatus {
+    /// Status for a checkbox character
+    pub fn from_checkbox(mark: char) -> Self {
+        match mark {
+            'x' | 'X' => Self::Completed,
+            '-' => Self::InProgress,
+            '!' => Self::Blocked,
+            '~' => Self::Cancelled,
+            _ => Self::Pending,
+        }
+    }
+
+    /// Checkbox character for this status
+    pub fn checkbox(self) -> char {
+        match self {
+            Self::Completed => 'x',
+            Self::InProgress => '-',
+            Self::Pending => ' ',
+            Self::Blocked => '!',
+            Self::Cancelled => '~',
+        }
+    }
+
+    /// Serialized name ("pending", "in_progress", "completed", "blocked", "cancelled")
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+            Self::Blocked => "blocked",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether no work is left: completed or cancelled
+    pub fn is_closed(self) -> bool {
+        matches!(self, Self::Completed | Self::Cancelled)
+    }
+
+    /// Whether the status can carry a `(reason: ...)`
+    pub fn takes_reason(self) -> bool {
+        matches!(self, Self::Blocked | Self::Cancelled)
+    }
+}
+
+/// Unit of a task estimate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateUnit {
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+}
+
+impl EstimateUnit {
+    /// Unit for a suffix: `m`, `h`, `d` or `w`
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "m" => Some(Self::Minutes),
+            "h" => Some(Self::Hours),
+            "d" => Some(Self::Days),
+            "w" => Some(Self::Weeks),
+            _ => None,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Minutes => "m",
+            Self::Hours => "h",
+            Self::Days => "d",
+            Self::Weeks => "w",
+        }
+    }
+
+    /// Length in hours, counting 8-hour days and 5-day weeks
+    pub fn hours(self) -> f64 {
+        match self {
+            Self::Minutes => 1.0 / 60.0,
+            Self::Hours => 1.0,
+            Self::Days => 8.0,
+            Self::Weeks => 40.0,
+        }
+    }
+}
+
+/// Time estimate from `(est: 3h)`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Estimate {
+    pub amount: f64,
+    pub unit: EstimateUnit,
+}
+
+impl Estimate {
+    /// Parse `3h`, `1.5d`, `30m` or `2w`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let amount: f64 = text[..split].parse().ok()?;
+        let unit = EstimateUnit::from_suffix(text[split..].trim())?;
+        (amount.is_finite() && amount >= 0.0).then_some(Self { amount, unit })
+    }
+
+    pub fn hours(&self) -> f64 {
+        self.amount * self.unit.hours()
+    }
+}
+
+impl std::fmt::Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.amount, self.unit.suffix())
+    }
+}
+
+/// Optional task annotations beyond ID and dependencies
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskMeta {
+    /// Assignee from `(owner: @name)`, without the `@`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Estimate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<chrono::NaiveDate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Priority from `(pN)`; lower numbers are more urgent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Why a task is blocked or cancelled, from `(reason: ...)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl TaskMeta {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Location of an element in the parsed source
+/// Spans describe the text as parsed; they aren't updated by later edits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    /// 1-based line number
+    pub line: usize,
+    /// Byte offset of the start of the line
+    pub start: usize,
+    /// Byte offset of the end of the line (excluding the line ending)
+    pub end: usize,
+}
+
+/// How a task line was written, for reproducing it unchanged
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct TaskSource {
+    /// Indentation, bullet and opening bracket, e.g. `"  - ["`
+    pub prefix: String,
+    /// Nesting level the prefix was written for (0 for a phase's own tasks)
+    pub depth: usize,
+    pub mark: char,
+    /// Everything after the checkbox character, starting with `]`
+    pub rest: String,
+    pub status: TaskStatus,
+    pub content: String,
+    pub dependencies: Vec<String>,
+    pub meta: TaskMeta,
+    pub id: String,
+}
+
+/// How a phase heading was written
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct HeadingSource {
+    pub line: String,
+    pub number: String,
+    pub name: String,
+}
+
+/// Combined status of a group of tasks (a phase, or a task's subtasks)
+/// Cancelled tasks don't hold a group open; a group is blocked when blocked
+/// tasks are all that keep it from starting
+fn combined_status(statuses: &[TaskStatus]) -> TaskStatus {
+    let open: Vec<TaskStatus> = statuses
+        .iter()
+        .copied()
+        .filter(|&s| s != TaskStatus::Cancelled)
+        .collect();
+    if statuses.is_empty() {
+        TaskStatus::Pending
+    } else if open.is_empty() {
+        TaskStatus::Cancelled
+    } else if open.iter().all(|&s| s == TaskStatus::Completed) {
+        TaskStatus::Completed
+    } else if open
+        .iter()
+        .any(|&s| matches!(s, TaskStatus::InProgress | TaskStatus::Completed))
+    {
+        TaskStatus::InProgress
+    } else if open.contains(&TaskStatus::Blocked) {
+        TaskStatus::Blocked
+    } else {
+        TaskStatus::Pending
+    }
+}
+
+/// Append tasks and their subtasks, depth first
+fn collect_tasks<'a>(tasks: &'a [PlanTask], out: &mut Vec<&'a PlanTask>) {
+    for task in tasks {
+        out.push(task);
+        collect_tasks(&task.subtasks, out);
+    }
+}
+
+/// Find a task at any depth for editing
+fn find_task_mut<'a>(tasks: &'a mut [PlanTask], id: &str) -> Option<&'a mut PlanTask> {
+    for task in tasks {
+        if task.id == id {
+            return Some(task);
+        }
+        if let Some(found) = find_task_mut(&mut task.subtasks, id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// A task within a phase
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanTask {
+    pub id: String,
+    pub content: String,
+    pub status: TaskStatus,
+    /// IDs of tasks this depends on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+    #[serde(flatten)]
+    pub meta: TaskMeta,
+    /// Checklist items indented under this task
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subtasks: Vec<PlanTask>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    #[serde(skip)]
+    pub(crate) source: Option<TaskSource>,
+    /// Lines following the task up to its first subtask, or to the next task
+    /// or phase (notes, blank lines)
+    #[serde(skip)]
+    pub(crate) trailing: Vec<String>,
+}
+
+impl PlanTask {
+    pub fn new(id: &str, content: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Status rolled up from the subtasks, or the task's own status without any
+    pub fn rollup_status(&self) -> TaskStatus {
+        if self.subtasks.is_empty() {
+            self.status
+        } else {
+            let statuses: Vec<TaskStatus> =
+                self.subtasks.iter().map(PlanTask::rollup_status).collect();
+            combined_status(&statuses)
+        }
+    }
+
+    /// This task and its subtasks at any depth, depth first
+    pub fn walk(&self) -> impl Iterator<Item = &PlanTask> {
+        let mut tasks = vec![self];
+        collect_tasks(&self.subtasks, &mut tasks);
+        tasks.into_iter()
+    }
+
+    /// Tasks without subtasks, which hold the actual work
+    pub fn leaves(&self) -> impl Iterator<Item = &PlanTask> {
+        self.walk().filter(|task| task.subtasks.is_empty())
+    }
+}
+
+/// A phase containing tasks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanPhase {
+    /// `phase_<number>`
+    pub id: String,
+    /// Phase number as written in the heading
+    pub number: String,
+    pub name: String,
+    pub tasks: Vec<PlanTask>,
+    /// Spans of a phase from an included file, and of its tasks, refer to that file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// File the phase was included from; None for phases of the plan itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    #[serde(skip)]
+    pub(crate) source: Option<HeadingSource>,
+    /// Lines between the heading and the first task
+    #[serde(skip)]
+    pub(crate) intro: Vec<String>,
+}
+
+impl PlanPhase {
+    pub fn new(number: &str, name: &str) -> Self {
+        Self {
+            id: format!("phase_{}", number),
+            number: number.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Heading text, e.g. "Phase 1 — Setup"
+    pub fn title(&self) -> String {
+        format!("Phase {} — {}", self.number, self.name)
+    }
+
+    /// Status derived from the phase's tasks (rolled up from their subtasks)
+    pub fn status(&self) -> TaskStatus {
+        let statuses: Vec<TaskStatus> = self.tasks.iter().map(PlanTask::rollup_status).collect();
+        combined_status(&statuses)
+    }
+
+    /// Tasks and subtasks at any depth, in document order
+    pub fn all_tasks(&self) -> impl Iterator<Item = &PlanTask> {
+        let mut tasks = Vec::new();
+        collect_tasks(&self.tasks, &mut tasks);
+        tasks.into_iter()
+    }
+}
+
+/// A parsed plan document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDoc {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_span: Option<Span>,
+    /// Settings from front matter; its lines stay in `header`
+    #[serde(default, skip_serializing_if = "PlanSettings::is_empty")]
+    pub settings: PlanSettings,
+    pub phases: Vec<PlanPhase>,
+    /// Lines before the first phase heading
+    #[serde(skip)]
+    pub(crate) header: Vec<String>,
+    /// Index of the title heading in `header`, with the title as parsed
+    #[serde(skip)]
+    pub(crate) title_source: Option<(usize, String)>,
+    /// Line ending used for lines written by the serializer
+    #[serde(skip)]
+    pub(crate) line_ending: LineEnding,
+    #[serde(skip)]
+    pub(crate) trailing_newline: bool,
+    /// Checklist lines inside phases that have no valid `(id: ...)`, which the frontend ignores
+    /// Only the plan's own lines; included files aren't scanned
+    #[serde(skip)]
+    pub(crate) tasks_without_id: Vec<Span>,
+    /// Files spliced in by `<!-- include: ... -->` directives, in the order they were read
+    #[serde(skip)]
+    pub(crate) included: Vec<IncludedFile>,
+}
+
+/// Line ending style of a document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+impl Default for PlanDoc {
+    fn default() -> Self {
+        Self {
+            title: DEFAULT_TITLE.to_string(),
+            title_span: None,
+            settings: PlanSettings::default(),
+            phases: Vec::new(),
+            header: Vec::new(),
+            title_source: None,
+            line_ending: LineEnding::Lf,
+            trailing_newline: true,
+            tasks_without_id: Vec::new(),
+            included: Vec::new(),
+        }
+    }
+}
+
+impl PlanDoc {
+    /// All tasks, including subtasks, in document order
+    pub fn tasks(&self) -> impl Iterator<Item = &PlanTask> {
+        self.phases.iter().flat_map(|phase| phase.all_tasks())
+    }
+
+    /// Find a task by ID
+    pub fn task(&self, id: &str) -> Option<&PlanTask> {
+        self.tasks().find(|t| t.id == id)
+    }
+
+    /// Find a task by ID for editing
+    pub fn task_mut(&mut self, id: &str) -> Option<&mut PlanTask> {
+        self.phases
+            .iter_mut()
+            .find_map(|phase| find_task_mut(&mut phase.tasks, id))
+    }
+
+    /// The phase containing a task
+    pub fn task_phase(&self, id: &str) -> Option<&PlanPhase> {
+        self.phases
+            .iter()
+            .find(|phase| phase.all_tasks().any(|t| t.id == id))
+    }
+
+    /// The task a subtask is nested under
+    pub fn task_parent(&self, id: &str) -> Option<&PlanTask> {
+        self.tasks()
+            .find(|task| task.subtasks.iter().any(|t| t.id == id))
+    }
+
+    /// Find a phase by ID
+    pub fn phase(&self, id: &str) -> Option<&PlanPhase> {
+        self.phases.iter().find(|p| p.id == id)
+    }
+
+    /// Canvas nodes for layout merging: phases, then their tasks with
+    /// subtasks following their parent
+    pub fn nodes(&self) -> Vec<NodeInfo> {
+        fn push_tasks(nodes: &mut Vec<NodeInfo>, tasks: &[PlanTask], phase_id: &str, parent_id: Option<&str>) {
+            for task in tasks {
+                nodes.push(NodeInfo {
+                    id: task.id.clone(),
+                    node_type: "task".to_string(),
+                    phase_id: Some(phase_id.to_string()),
+                    parent_id: parent_id.map(String::from),
+                });
+                push_tasks(nodes, &task.subtasks, phase_id, Some(&task.id));
+            }
+        }
+
+        let mut nodes = Vec::new();
+        for phase in &self.phases {
+            nodes.push(NodeInfo {
+                id: phase.id.clone(),
+                node_type: "phase".to_string(),
+                phase_id: None,
+                parent_id: None,
+            });
+            push_tasks(&mut nodes, &phase.tasks, &phase.id, None);
+        }
+        nodes
+    }
+
+    /// Check IDs and dependency references, as the frontend's `validatePlan` does
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for phase in &self.phases {
+            if !seen.insert(phase.id.as_str()) {
+                errors.push(format!("Duplicate phase ID: {}", phase.id));
+            }
+            for task in phase.all_tasks() {
+                if !seen.insert(task.id.as_str()) {
+                    errors.push(format!("Duplicate task ID: {}", task.id));
+                }
+                if !parser::is_valid_id(&task.id) {
+                    errors.push(format!("Invalid task ID format: {}", task.id));
+                }
+            }
+        }
+
+        for task in self.tasks() {
+            // Cross-plan dependencies need the plan's location; the linter checks them
+            for dep in task.dependencies.iter().filter(|d| !crossplan::is_cross_plan(d)) {
+                if !seen.contains(dep.as_str()) {
+                    errors.push(format!(
+                        "Task \"{}\" references unknown dependency \"{}\"",
+                        task.id, dep
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+/// Add one canonical line per task: indentation for nesting, ID and sorted dependencies
+fn push_structure(canonical: &mut String, tasks: &[PlanTask], depth: usize) {
+    for task in tasks {
+        let mut dependencies: Vec<&str> = task.dependencies.iter().map(String::as_str).collect();
+        dependencies.sort_unstable();
+        dependencies.dedup();
+        canonical.push_str(&format!(
+            "{}task {} [{}]\n",
+            "  ".repeat(depth),
+            task.id,
+            dependencies.join(",")
+        ));
+        push_structure(canonical, &task.subtasks, depth + 1);
+    }
+}
+
+impl PlanDoc {
+    /// Hash of the plan's structure: phase and task IDs in order, nesting and dependencies
+    /// Whitespace, wording, statuses, metadata and notes don't affect it, so it only
+    /// changes when a saved layout may no longer fit the plan
+    pub fn structure_hash(&self) -> String {
+        let mut canonical = String::new();
+        for phase in &self.phases {
+            canonical.push_str(&format!("phase {}\n", phase.id));
+            push_structure(&mut canonical, &phase.tasks, 1);
+        }
+        Sha256::digest(canonical.as_bytes())
+            .iter()
+            .take(8)
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+/// Read and parse a plan file, with the phases of the files it includes
+pub fn read_plan_file(plan_path: &str) -> Result<PlanDoc, String> {
+    include::load(Path::new(plan_path))
+}
+
+/// Parse a plan file into its phases and tasks
+#[tauri::command]
+pub fn read_plan(plan_path: String) -> Result<PlanDoc, String> {
+    read_plan_file(&plan_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structure_hash_ignores_prose() {
+        let base = parse(
+            "# Plan: P\n\n## Phase 1 — A\n- [ ] One (id: t1)\n  - [ ] Sub (depends: t1, t3) (id: t2)\n- [ ] Three (id: t3)\n",
+        )
+        .structure_hash();
+        assert_eq!(base.len(), 16);
+
+        // Titles, names, wording, statuses, metadata, notes and spacing
+        let reworded = parse(
+            "# Plan: Renamed\n\nIntro.\n\n## Phase 1 — B\n\n* [x] First (owner: @sam) (id: t1)\n    - [-] Sub task (depends: t3, t1) (id: t2)\n\n  Notes here.\n- [ ]   Three (id: t3)\n",
+        )
+        .structure_hash();
+        assert_eq!(reworded, base);
+
+        // Nesting, dependencies and IDs
+        for changed in [
+            "## Phase 1 — A\n- [ ] One (id: t1)\n- [ ] Sub (depends: t1, t3) (id: t2)\n- [ ] Three (id: t3)\n",
+            "## Phase 1 — A\n- [ ] One (id: t1)\n  - [ ] Sub (depends: t1) (id: t2)\n- [ ] Three (id: t3)\n",
+            "## Phase 2 — A\n- [ ] One (id: t1)\n  - [ ] Sub (depends: t1, t3) (id: t2)\n- [ ] Three (id: t3)\n",
+        ] {
+            assert_ne!(parse(changed).structure_hash(), base, "{}", changed);
+        }
+    }
+
+    #[test]
+    fn test_nodes() {
+        let doc = parse("## Phase 1 — A\n- [ ] One (id: t1)\n  - [ ] Sub (id: t2)\n");
+        let nodes = doc.nodes();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].node_type, "phase");
+        assert_eq!(nodes[1].phase_id.as_deref(), Some("phase_1"));
+        assert_eq!(nodes[2].phase_id.as_deref(), Some("phase_1"));
+        assert_eq!(nodes[2].parent_id.as_deref(), Some("t1"));
+    }
+
+    #[test]
+    fn test_rollup_status() {
+        let doc = parse(
+            "## Phase 1 — A\n- [ ] Parent (id: t1)\n  - [x] Done (id: t2)\n  - [ ] Nested (id: t3)\n    - [~] Dropped (id: t4)\n",
+        );
+        let parent = doc.task("t1").unwrap();
+
+        assert_eq!(parent.status, TaskStatus::Pending);
+        assert_eq!(doc.task("t3").unwrap().rollup_status(), TaskStatus::Cancelled);
+        assert_eq!(parent.rollup_status(), TaskStatus::Completed);
+        assert_eq!(doc.phases[0].status(), TaskStatus::Completed);
+        assert_eq!(doc.task_parent("t4").unwrap().id, "t3");
+        assert_eq!(doc.task_phase("t4").unwrap().id, "phase_1");
+    }
+}
@@ -0,0 +1,155 @@
+//! Cross-plan dependencies
+//!
+//! A dependency written as `path#id` names a task in another plan file, e.g.
+//! `(depends: ../backend/plan.md#t12)`. The path is relative to the directory
+//! of the plan containing the dependency. Referenced plans are read as they
+//! are on disk; their own dependencies aren't followed.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::parser::{is_dependency_char, is_valid_id};
+use super::{parse, PlanDoc, PlanTask};
+
+/// A dependency on a task in another plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrossPlanRef<'a> {
+    /// Plan path as written, relative to the referencing plan's directory
+    pub path: &'a str,
+    pub task_id: &'a str,
+}
+
+impl<'a> CrossPlanRef<'a> {
+    /// Split a dependency of the form `path#id`; plain task and phase IDs give None,
+    /// as do paths that can't be written in a `(depends: ...)` list
+    pub fn parse(dependency: &'a str) -> Option<Self> {
+        let (path, task_id) = dependency.rsplit_once('#')?;
+        let path = path.trim();
+        let task_id = task_id.trim();
+        let writable = path.chars().all(|c| is_dependency_char(c) && c != ',');
+        (!path.is_empty() && writable && is_valid_id(task_id)).then_some(Self { path, task_id })
+    }
+
+    /// Location of the referenced plan for a dependency written in `plan_path`
+    pub fn resolve(&self, plan_path: &Path) -> PathBuf {
+        match plan_path.parent() {
+            Some(dir) => dir.join(self.path),
+            None => PathBuf::from(self.path),
+        }
+    }
+}
+
+/// Whether a dependency refers to a task in another plan
+pub fn is_cross_plan(dependency: &str) -> bool {
+    CrossPlanRef::parse(dependency).is_some()
+}
+
+/// Plans referenced by a plan's cross-plan dependencies, each read once
+#[derive(Debug, Default)]
+pub struct ReferencedPlans {
+    plan_path: Option<PathBuf>,
+    /// Parsed plans by resolved path; the error when a plan couldn't be read
+    plans: HashMap<PathBuf, Result<PlanDoc, String>>,
+}
+
+impl ReferencedPlans {
+    /// Read every plan that `doc`, stored at `plan_path`, depends on
+    pub fn load(plan_path: &Path, doc: &PlanDoc) -> Self {
+        let mut plans = HashMap::new();
+        for reference in doc.cross_plan_refs() {
+            plans
+                .entry(reference.resolve(plan_path))
+                .or_insert_with_key(|path| {
+                    fs::read_to_string(path)
+                        .map(|markdown| parse(&markdown))
+                        .map_err(|e| format!("Failed to read plan {}: {}", reference.path, e))
+                });
+        }
+
+        Self {
+            plan_path: Some(plan_path.to_path_buf()),
+            plans,
+        }
+    }
+
+    /// Resolved path of a referenced plan
+    pub fn resolve(&self, reference: &CrossPlanRef) -> Option<PathBuf> {
+        self.plan_path.as_deref().map(|p| reference.resolve(p))
+    }
+
+    /// The task a cross-plan dependency points at
+    pub fn task(&self, reference: &CrossPlanRef) -> Result<&PlanTask, String> {
+        let path = self
+            .resolve(reference)
+            .ok_or_else(|| format!("Can't resolve {} without a plan path", reference.path))?;
+        let doc = self
+            .plans
+            .get(&path)
+            .ok_or_else(|| format!("Plan not loaded: {}", reference.path))?
+            .as_ref()
+            .map_err(Clone::clone)?;
+        doc.task(reference.task_id)
+            .ok_or_else(|| format!("No task \"{}\" in {}", reference.task_id, reference.path))
+    }
+}
+
+impl PlanDoc {
+    /// Cross-plan dependencies of all tasks, in document order
+    pub fn cross_plan_refs(&self) -> impl Iterator<Item = CrossPlanRef<'_>> {
+        self.tasks()
+            .flat_map(|task| &task.dependencies)
+            .filter_map(|dep| CrossPlanRef::parse(dep))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_reference() {
+        let reference = CrossPlanRef::parse("../backend/plan.md#t12").unwrap();
+        assert_eq!(reference.path, "../backend/plan.md");
+        assert_eq!(reference.task_id, "t12");
+
+        assert!(CrossPlanRef::parse("t12").is_none());
+        assert!(CrossPlanRef::parse("#t12").is_none());
+        assert!(CrossPlanRef::parse("plan.md#").is_none());
+        assert!(!is_cross_plan("phase_1"));
+        assert!(!is_cross_plan("a.md), (owner: x#t1"));
+        assert!(!is_cross_plan("a.md, b.md#t1"));
+    }
+
+    #[test]
+    fn test_referenced_plans() {
+        let temp = tempdir().unwrap();
+        fs::create_dir(temp.path().join("backend")).unwrap();
+        fs::create_dir(temp.path().join("frontend")).unwrap();
+        fs::write(
+            temp.path().join("backend/plan.md"),
+            "## Phase 1 — API\n- [x] Endpoints (id: t12)\n",
+        )
+        .unwrap();
+
+        let plan_path = temp.path().join("frontend/plan.md");
+        let doc = parse(
+            "## Phase 1 — UI\n- [ ] Screens (depends: ../backend/plan.md#t12, ../backend/plan.md#t99, ../mobile/plan.md#t1) (id: t1)\n",
+        );
+        let plans = ReferencedPlans::load(&plan_path, &doc);
+        let refs: Vec<CrossPlanRef> = doc.cross_plan_refs().collect();
+        assert_eq!(refs.len(), 3);
+
+        assert_eq!(plans.task(&refs[0]).unwrap().content, "Endpoints");
+        assert!(plans
+            .task(&refs[1])
+            .unwrap_err()
+            .contains("No task \"t99\""));
+        assert!(plans
+            .task(&refs[2])
+            .unwrap_err()
+            .contains("../mobile/plan.md"));
+        assert!(ReferencedPlans::default().task(&refs[0]).is_err());
+    }
+}
@@ -0,0 +1,900 @@
+//! Plan mutations
+//!
+//! Edits to a `PlanDoc` and the commands that apply them to plan.md. Each
+//! command snapshots the current plan to history, writes only the lines the
+//! edit changes, and merges the layout so new nodes get positions. Changes to
+//! phases from included files are written to those files.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::iter;
+use std::path::Path;
+
+use super::crossplan::is_cross_plan;
+use super::{read_plan_file, serialize, PlanDoc, PlanPhase, PlanTask, TaskMeta, TaskStatus};
+use crate::layout::{self, MergeResult};
+use crate::{history, watcher};
+
+/// Result of a plan edit command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanEditResult {
+    /// The plan after the edit
+    pub plan: PlanDoc,
+    /// Content hash of the new plan.md, as the `newHash` of file change events
+    /// (not the structural hash layouts are saved with)
+    pub content_hash: String,
+    /// Layout after merging in added and removed nodes
+    pub layout: MergeResult,
+}
+
+/// Split a task's trailing lines into its own indented notes and what follows
+/// (blank lines, prose) which belongs to the surrounding document
+fn split_trailing(task: &mut PlanTask) -> Vec<String> {
+    let notes = task
+        .trailing
+        .iter()
+        .take_while(|line| !line.trim().is_empty() && line.starts_with([' ', '\t']))
+        .count();
+    task.trailing.split_off(notes)
+}
+
+/// The deepest last subtask of a task, which owns the lines after the task's block
+fn last_descendant_mut(task: &mut PlanTask) -> &mut PlanTask {
+    if task.subtasks.is_empty() {
+        task
+    } else {
+        last_descendant_mut(task.subtasks.last_mut().expect("non-empty subtasks"))
+    }
+}
+
+/// Insert a task into a list of siblings at `index` (end when None), keeping
+/// separating lines after the last task
+fn insert_task(tasks: &mut Vec<PlanTask>, index: Option<usize>, mut task: PlanTask) {
+    let index = index.map_or(tasks.len(), |i| i.min(tasks.len()));
+    if index > 0 {
+        let following = split_trailing(last_descendant_mut(&mut tasks[index - 1]));
+        last_descendant_mut(&mut task).trailing.extend(following);
+    }
+    tasks.insert(index, task);
+}
+
+/// Remove a task from a list of siblings, handing separating lines to whatever
+/// preceded it (`intro` when it was the first)
+fn take_task(intro: &mut Vec<String>, tasks: &mut Vec<PlanTask>, index: usize) -> PlanTask {
+    let mut task = tasks.remove(index);
+    let following = split_trailing(last_descendant_mut(&mut task));
+    match index.checked_sub(1) {
+        Some(previous) => last_descendant_mut(&mut tasks[previous])
+            .trailing
+            .extend(following),
+        None => intro.extend(following),
+    }
+    task
+}
+
+/// Append separating lines, without doubling a blank line already at the end
+fn append_following(lines: &mut Vec<String>, following: Vec<String>) {
+    let doubled = match lines.last() {
+        Some(last) if last.trim().is_empty() => following
+            .iter()
+            .take_while(|line| line.trim().is_empty())
+            .count(),
+        _ => 0,
+    };
+    lines.extend(following.into_iter().skip(doubled));
+}
+
+/// Remove dependencies on any of `ids` from tasks at any depth
+fn drop_dependencies(tasks: &mut [PlanTask], ids: &[String]) {
+    for task in tasks {
+        task.dependencies.retain(|dep| !ids.contains(dep));
+        drop_dependencies(&mut task.subtasks, ids);
+    }
+}
+
+/// Indices leading to a task through nested subtasks
+fn task_path(tasks: &[PlanTask], task_id: &str) -> Option<Vec<usize>> {
+    tasks.iter().enumerate().find_map(|(i, task)| {
+        if task.id == task_id {
+            return Some(vec![i]);
+        }
+        let mut path = task_path(&task.subtasks, task_id)?;
+        path.insert(0, i);
+        Some(path)
+    })
+}
+
+impl PlanDoc {
+    /// Locate a task as (phase index, indices through its parents down to the task)
+    fn locate_task(&self, task_id: &str) -> Result<(usize, Vec<usize>), String> {
+        self.phases
+            .iter()
+            .enumerate()
+            .find_map(|(p, phase)| Some((p, task_path(&phase.tasks, task_id)?)))
+            .ok_or_else(|| format!("Task not found: {}", task_id))
+    }
+
+    /// The lines before the first of a list of siblings, and the siblings:
+    /// a phase's intro and tasks, or a parent task's notes and subtasks
+    fn siblings_mut(
+        &mut self,
+        phase: usize,
+        parent_path: &[usize],
+    ) -> (&mut Vec<String>, &mut Vec<PlanTask>) {
+        let phase = &mut self.phases[phase];
+        match parent_path.split_first() {
+            None => (&mut phase.intro, &mut phase.tasks),
+            Some((&first, rest)) => {
+                let mut parent = &mut phase.tasks[first];
+                for &i in rest {
+                    parent = &mut parent.subtasks[i];
+                }
+                (&mut parent.trailing, &mut parent.subtasks)
+            }
+        }
+    }
+
+    /// Remove a task (with its subtasks) from wherever it is nested
+    fn detach_task(&mut self, task_id: &str) -> Result<PlanTask, String> {
+        let (p, mut path) = self.locate_task(task_id)?;
+        let index = path.pop().expect("non-empty task path");
+        let (intro, tasks) = self.siblings_mut(p, &path);
+        let task = take_task(intro, tasks, index);
+        self.roll_up(p, &path);
+        Ok(task)
+    }
+
+    /// Update the status of each task along `parent_path`, nearest first,
+    /// from its subtasks
+    fn roll_up(&mut self, phase: usize, parent_path: &[usize]) {
+        for depth in (1..=parent_path.len()).rev() {
+            let (_, siblings) = self.siblings_mut(phase, &parent_path[..depth - 1]);
+            let parent = &mut siblings[parent_path[depth - 1]];
+            if !parent.subtasks.is_empty() {
+                parent.status = parent.rollup_status();
+                if !parent.status.takes_reason() {
+                    parent.meta.reason = None;
+                }
+            }
+        }
+    }
+
+    fn locate_phase(&self, phase_id: &str) -> Result<usize, String> {
+        self.phases
+            .iter()
+            .position(|p| p.id == phase_id)
+            .ok_or_else(|| format!("Phase not found: {}", phase_id))
+    }
+
+    /// Lines before the first phase of the plan, or of an included file
+    fn header_mut(&mut self, file: Option<&Path>) -> Option<&mut Vec<String>> {
+        match file {
+            None => Some(&mut self.header),
+            Some(path) => self
+                .included
+                .iter_mut()
+                .find(|included| included.path == path)
+                .map(|included| &mut included.template.header),
+        }
+    }
+
+    fn existing_task_mut(&mut self, task_id: &str) -> Result<&mut PlanTask, String> {
+        self.task_mut(task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))
+    }
+
+    /// Next unused `t<n>` task ID
+    pub fn next_task_id(&self) -> String {
+        let max = self
+            .tasks()
+            .filter_map(|t| t.id.strip_prefix('t')?.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("t{}", max + 1)
+    }
+
+    /// Set a task's status and roll it up to its parents
+    /// The reason is dropped unless the task is blocked or cancelled
+    pub fn set_task_status(&mut self, task_id: &str, status: TaskStatus) -> Result<(), String> {
+        let task = self.existing_task_mut(task_id)?;
+        task.status = status;
+        if !status.takes_reason() {
+            task.meta.reason = None;
+        }
+
+        let (p, mut path) = self.locate_task(task_id)?;
+        path.pop();
+        self.roll_up(p, &path);
+        Ok(())
+    }
+
+    /// Explain why a blocked or cancelled task is in that state
+    pub fn set_status_reason(&mut self, task_id: &str, reason: &str) -> Result<(), String> {
+        let reason = reason.trim();
+        if reason.contains(['\n', ')']) {
+            return Err("Reason must be a single line without ')'".to_string());
+        }
+        let task = self.existing_task_mut(task_id)?;
+        if !task.status.takes_reason() {
+            return Err(format!("Task {} is not blocked or cancelled", task_id));
+        }
+        task.meta.reason = (!reason.is_empty()).then(|| reason.to_string());
+        Ok(())
+    }
+
+    pub fn set_task_content(&mut self, task_id: &str, content: &str) -> Result<(), String> {
+        let content = content.trim();
+        check_task_text(content)?;
+        self.existing_task_mut(task_id)?.content = content.to_string();
+        Ok(())
+    }
+
+    /// Replace a task's owner, estimate, due date, tags and priority
+    pub fn set_task_meta(&mut self, task_id: &str, mut meta: TaskMeta) -> Result<(), String> {
+        if let Some(owner) = &mut meta.owner {
+            let name = owner.trim().trim_start_matches('@');
+            if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == ')') {
+                return Err(format!("Invalid owner: {}", owner));
+            }
+            *owner = name.to_string();
+        }
+        meta.tags = meta
+            .tags
+            .iter()
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        if let Some(tag) = meta.tags.iter().find(|tag| tag.contains([',', ')'])) {
+            return Err(format!("Invalid tag: {}", tag));
+        }
+        if let Some(reason) = &meta.reason {
+            if reason.contains(['\n', ')']) {
+                return Err("Reason must be a single line without ')'".to_string());
+            }
+        }
+        self.existing_task_mut(task_id)?.meta = meta;
+        Ok(())
+    }
+
+    /// Check that a task can be added: valid text, valid unused ID and known dependencies
+    /// Cross-plan dependencies are only checked for form; the linter resolves them
+    fn check_new_task(&self, task: &PlanTask) -> Result<(), String> {
+        check_task_text(&task.content)?;
+        if !super::parser::is_valid_id(&task.id) {
+            return Err(format!("Invalid task ID: {}", task.id));
+        }
+        if self.task(&task.id).is_some() || self.phase(&task.id).is_some() {
+            return Err(format!("ID already in use: {}", task.id));
+        }
+        for dep in &task.dependencies {
+            if self.task(dep).is_none() && !is_cross_plan(dep) {
+                return Err(format!("Unknown dependency: {}", dep));
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a task to a phase at `index` (end when None)
+    pub fn add_task(
+        &mut self,
+        phase_id: &str,
+        task: PlanTask,
+        index: Option<usize>,
+    ) -> Result<(), String> {
+        self.check_new_task(&task)?;
+        let p = self.locate_phase(phase_id)?;
+        insert_task(&mut self.phases[p].tasks, index, task);
+        Ok(())
+    }
+
+    /// Add a subtask under a task at `index` (end when None)
+    pub fn add_subtask(
+        &mut self,
+        parent_id: &str,
+        task: PlanTask,
+        index: Option<usize>,
+    ) -> Result<(), String> {
+        self.check_new_task(&task)?;
+        let (p, path) = self.locate_task(parent_id)?;
+        let (_, siblings) = self.siblings_mut(p, &path[..path.len() - 1]);
+        insert_task(&mut siblings[path[path.len() - 1]].subtasks, index, task);
+        self.roll_up(p, &path);
+        Ok(())
+    }
+
+    /// Remove a task with its subtasks, and any dependencies on them
+    pub fn remove_task(&mut self, task_id: &str) -> Result<PlanTask, String> {
+        let task = self.detach_task(task_id)?;
+        let removed: Vec<String> = task.walk().map(|t| t.id.clone()).collect();
+        for phase in &mut self.phases {
+            drop_dependencies(&mut phase.tasks, &removed);
+        }
+        Ok(task)
+    }
+
+    /// Move a task, with its subtasks, to `index` in a phase (end when None),
+    /// keeping its notes; a subtask moved this way becomes a top-level task
+    pub fn move_task(
+        &mut self,
+        task_id: &str,
+        phase_id: &str,
+        index: Option<usize>,
+    ) -> Result<(), String> {
+        let target = self.locate_phase(phase_id)?;
+        let task = self.detach_task(task_id)?;
+        insert_task(&mut self.phases[target].tasks, index, task);
+        Ok(())
+    }
+
+    /// Append a phase numbered after the last one; returns its ID
+    pub fn add_phase(&mut self, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        check_phase_name(name)?;
+        let number = self
+            .phases
+            .iter()
+            .filter_map(|p| p.number.parse::<u64>().ok())
+            .max()
+            .map_or(0, |n| n + 1);
+
+        let phase = PlanPhase::new(&number.to_string(), name);
+        let id = phase.id.clone();
+        self.phases.push(phase);
+        Ok(id)
+    }
+
+    /// Remove a phase with its tasks, and any dependencies on them
+    /// Lines after the phase's own (blank lines, include directives) are handed
+    /// to whatever preceded it in the same file
+    pub fn remove_phase(&mut self, phase_id: &str) -> Result<PlanPhase, String> {
+        let p = self.locate_phase(phase_id)?;
+        let mut phase = self.phases.remove(p);
+        let following = match phase.tasks.last_mut() {
+            Some(last) => split_trailing(last_descendant_mut(last)),
+            None => {
+                let own = phase
+                    .intro
+                    .iter()
+                    .take_while(|line| !line.trim().is_empty())
+                    .count();
+                phase.intro.split_off(own)
+            }
+        };
+
+        let previous = self.phases[..p]
+            .iter()
+            .rposition(|other| other.file == phase.file);
+        match previous {
+            Some(i) => match self.phases[i].tasks.last_mut() {
+                Some(last) => append_following(&mut last_descendant_mut(last).trailing, following),
+                None => append_following(&mut self.phases[i].intro, following),
+            },
+            None => {
+                if let Some(header) = self.header_mut(phase.file.as_deref()) {
+                    append_following(header, following);
+                }
+            }
+        }
+
+        let removed: Vec<String> = iter::once(phase.id.clone())
+            .chain(phase.all_tasks().map(|t| t.id.clone()))
+            .collect();
+        for phase in &mut self.phases {
+            drop_dependencies(&mut phase.tasks, &removed);
+        }
+        Ok(phase)
+    }
+
+    pub fn rename_phase(&mut self, phase_id: &str, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        check_phase_name(name)?;
+        let p = self.locate_phase(phase_id)?;
+        self.phases[p].name = name.to_string();
+        Ok(())
+    }
+
+    pub fn add_dependency(&mut self, task_id: &str, depends_on: &str) -> Result<(), String> {
+        if task_id == depends_on {
+            return Err(format!("Task {} can't depend on itself", task_id));
+        }
+        if self.task(depends_on).is_none() && !is_cross_plan(depends_on) {
+            return Err(format!("Task not found: {}", depends_on));
+        }
+        let task = self.existing_task_mut(task_id)?;
+        if !task.dependencies.iter().any(|d| d == depends_on) {
+            task.dependencies.push(depends_on.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn remove_dependency(&mut self, task_id: &str, depends_on: &str) -> Result<(), String> {
+        self.existing_task_mut(task_id)?
+            .dependencies
+            .retain(|d| d != depends_on);
+        Ok(())
+    }
+}
+
+/// Check that task text is one non-empty line that won't be read back as annotations
+fn check_task_text(content: &str) -> Result<(), String> {
+    if content.is_empty() || content.contains(['\n', '\r']) {
+        return Err("Task text must be a single non-empty line".to_string());
+    }
+    if super::parser::has_annotation_syntax(content) {
+        return Err("Task text must not contain annotations like (key: ...) or (pN)".to_string());
+    }
+    Ok(())
+}
+
+/// Check that a phase name fits on its heading line
+fn check_phase_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['\n', '\r']) {
+        return Err("Phase name must be a single non-empty line".to_string());
+    }
+    Ok(())
+}
+
+/// Apply an edit to a plan file
+/// Snapshots the current plan, writes the changed lines and merges the layout
+fn edit_plan(
+    plan_path: &str,
+    edit: impl FnOnce(&mut PlanDoc) -> Result<(), String>,
+) -> Result<PlanEditResult, String> {
+    let mut doc = read_plan_file(plan_path)?;
+    edit(&mut doc)?;
+    save_plan(plan_path, &doc)
+}
+
+/// Write one of a plan's files if its content changed
+fn write_plan_file(path: &Path, updated: &str) -> Result<(), String> {
+    if fs::read_to_string(path).is_ok_and(|current| current == updated) {
+        return Ok(());
+    }
+
+    // The caller gets the new plan back; don't echo the write as an external change
+    watcher::record_self_write(path, updated.as_bytes());
+    fs::write(path, updated).map_err(|e| format!("Failed to write plan file: {}", e))
+}
+
+/// Write a plan and the files it includes, each only where it changed,
+/// then merge and write the layout
+pub(crate) fn save_plan(plan_path: &str, doc: &PlanDoc) -> Result<PlanEditResult, String> {
+    save_plan_renaming(plan_path, doc, &HashMap::new())
+}
+
+/// Save as `save_plan` does, moving the layout entries of renamed nodes to
+/// their new IDs; the layout is only written once the plan files are
+pub(crate) fn save_plan_renaming(
+    plan_path: &str,
+    doc: &PlanDoc,
+    renamed: &HashMap<String, String>,
+) -> Result<PlanEditResult, String> {
+    let updated = serialize(doc);
+
+    // Keep the previous plan for progress replay; the edit goes ahead without it
+    if fs::read_to_string(plan_path).is_ok_and(|current| current != updated) {
+        if let Err(e) = history::record_plan_snapshot(plan_path) {
+            eprintln!("Failed to record plan snapshot: {}", e);
+        }
+    }
+    write_plan_file(Path::new(plan_path), &updated)?;
+    for (path, content) in doc.serialize_included() {
+        write_plan_file(&path, &content)?;
+    }
+
+    let doc = read_plan_file(plan_path)?;
+    let content_hash = watcher::content_hash(updated.as_bytes());
+    let merged = layout::merge_layout_renaming(plan_path.to_string(), doc.nodes(), renamed)?;
+    layout::write_layout(plan_path.to_string(), merged.layout.clone())?;
+
+    Ok(PlanEditResult {
+        plan: doc,
+        content_hash,
+        layout: merged,
+    })
+}
+
+/// Set a task's status, with an optional reason for blocked and cancelled tasks
+#[tauri::command]
+pub fn set_task_status(
+    plan_path: String,
+    task_id: String,
+    status: TaskStatus,
+    reason: Option<String>,
+) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| {
+        doc.set_task_status(&task_id, status)?;
+        match reason {
+            Some(reason) => doc.set_status_reason(&task_id, &reason),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Replace a task's text (annotations are kept)
+#[tauri::command]
+pub fn set_task_text(
+    plan_path: String,
+    task_id: String,
+    content: String,
+) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| doc.set_task_content(&task_id, &content))
+}
+
+/// Set a task's owner, estimate, due date, tags and priority
+#[tauri::command]
+pub fn set_task_meta(
+    plan_path: String,
+    task_id: String,
+    meta: TaskMeta,
+) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| doc.set_task_meta(&task_id, meta))
+}
+
+/// Add a task to a phase, or as a subtask of `parent_id` within that phase
+/// The ID is generated when omitted; `index` defaults to the end of the phase
+/// or of the parent's subtasks
+#[tauri::command]
+pub fn add_task(
+    plan_path: String,
+    phase_id: String,
+    content: String,
+    id: Option<String>,
+    dependencies: Option<Vec<String>>,
+    index: Option<usize>,
+    parent_id: Option<String>,
+) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| {
+        let id = id.unwrap_or_else(|| doc.next_task_id());
+        let mut task = PlanTask::new(&id, content.trim());
+        task.dependencies = dependencies.unwrap_or_default();
+        match parent_id {
+            Some(parent_id) => {
+                if doc.task_phase(&parent_id).map(|p| p.id.as_str()) != Some(phase_id.as_str()) {
+                    return Err(format!("Task {} is not in {}", parent_id, phase_id));
+                }
+                doc.add_subtask(&parent_id, task, index)
+            }
+            None => doc.add_task(&phase_id, task, index),
+        }
+    })
+}
+
+/// Remove a task with its subtasks, and any dependencies on them
+#[tauri::command]
+pub fn remove_task(plan_path: String, task_id: String) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| doc.remove_task(&task_id).map(|_| ()))
+}
+
+/// Move a task within or between phases
+#[tauri::command]
+pub fn move_task(
+    plan_path: String,
+    task_id: String,
+    phase_id: String,
+    index: Option<usize>,
+) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| doc.move_task(&task_id, &phase_id, index))
+}
+
+/// Append a new phase
+#[tauri::command]
+pub fn add_phase(plan_path: String, name: String) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| doc.add_phase(&name).map(|_| ()))
+}
+
+/// Remove a phase with its tasks, and any dependencies on them
+#[tauri::command]
+pub fn remove_phase(plan_path: String, phase_id: String) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| doc.remove_phase(&phase_id).map(|_| ()))
+}
+
+/// Rename a phase
+#[tauri::command]
+pub fn rename_phase(
+    plan_path: String,
+    phase_id: String,
+    name: String,
+) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| doc.rename_phase(&phase_id, &name))
+}
+
+/// Make a task depend on another, here or in another plan (`path#id`)
+#[tauri::command]
+pub fn add_dependency(
+    plan_path: String,
+    task_id: String,
+    depends_on: String,
+) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| doc.add_dependency(&task_id, &depends_on))
+}
+
+/// Remove a dependency between two tasks
+#[tauri::command]
+pub fn remove_dependency(
+    plan_path: String,
+    task_id: String,
+    depends_on: String,
+) -> Result<PlanEditResult, String> {
+    edit_plan(&plan_path, |doc| {
+        doc.remove_dependency(&task_id, &depends_on)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::parse;
+    use tempfile::tempdir;
+
+    const PLAN: &str = "# Plan: Test\n\nSome prose the user wrote.\n\n## Phase 1 — Work\n* [ ] Build  (id: t1)\n    - a note\n- [ ] Test (depends: t1) (id: t2)\n\n## Phase 2 — Ship\n- [ ] Release (id: t3)\n";
+
+    fn write_plan() -> (tempfile::TempDir, String) {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        fs::write(&plan_path, PLAN).unwrap();
+        (temp, plan_path.to_string_lossy().to_string())
+    }
+
+    #[test]
+    fn test_set_task_status_patches_one_line() {
+        let (_temp, plan_path) = write_plan();
+
+        let result =
+            set_task_status(plan_path.clone(), "t1".to_string(), TaskStatus::Completed, None)
+                .unwrap();
+
+        let markdown = fs::read_to_string(&plan_path).unwrap();
+        assert_eq!(markdown, PLAN.replace("* [ ] Build", "* [x] Build"));
+        assert_eq!(
+            result.plan.task("t1").unwrap().status,
+            TaskStatus::Completed
+        );
+        assert_eq!(
+            result.content_hash,
+            watcher::content_hash(markdown.as_bytes())
+        );
+
+        // History holds the previous version and the layout covers every node
+        let snapshots = history::load_plan_snapshots(&plan_path).unwrap();
+        assert_eq!(snapshots.last().unwrap().content, PLAN);
+        assert_eq!(result.layout.layout.layouts.len(), 5);
+    }
+
+    #[test]
+    fn test_remove_task_drops_dependencies() {
+        let mut doc = parse(PLAN);
+        doc.remove_task("t1").unwrap();
+
+        assert!(doc.task("t2").unwrap().dependencies.is_empty());
+        assert_eq!(
+            serialize(&doc),
+            "# Plan: Test\n\nSome prose the user wrote.\n\n## Phase 1 — Work\n- [ ] Test (id: t2)\n\n## Phase 2 — Ship\n- [ ] Release (id: t3)\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_phase() {
+        let mut doc = parse(PLAN);
+        doc.remove_phase("phase_1").unwrap();
+        assert!(doc.task("t1").is_none());
+        assert_eq!(
+            serialize(&doc),
+            "# Plan: Test\n\nSome prose the user wrote.\n\n## Phase 2 — Ship\n- [ ] Release (id: t3)\n"
+        );
+
+        let mut doc = parse(PLAN);
+        doc.add_dependency("t3", "t2").unwrap();
+        doc.remove_phase("phase_1").unwrap();
+        assert!(doc.task("t3").unwrap().dependencies.is_empty());
+        assert!(doc.remove_phase("phase_9").is_err());
+    }
+
+    #[test]
+    fn test_remove_phase_keeps_include_directives() {
+        let temp = tempfile::tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let part_path = temp.path().join("part.md");
+        fs::write(
+            &plan_path,
+            "# Plan: P\n\n## Phase 1 — A\n- [ ] One (id: t1)\n\n<!-- include: part.md -->\n\n## Phase 3 — C\n- [ ] Three (id: t3)\n",
+        )
+        .unwrap();
+        fs::write(
+            &part_path,
+            "## Phase 2 — B\n- [ ] Two (depends: t1) (id: t2)\n",
+        )
+        .unwrap();
+        let path = plan_path.to_string_lossy().to_string();
+
+        let result = remove_phase(path.clone(), "phase_1".to_string()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&plan_path).unwrap(),
+            "# Plan: P\n\n<!-- include: part.md -->\n\n## Phase 3 — C\n- [ ] Three (id: t3)\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&part_path).unwrap(),
+            "## Phase 2 — B\n- [ ] Two (id: t2)\n"
+        );
+        let layouts = &result.layout.layout.layouts;
+        assert!(layouts.contains_key("t2") && !layouts.contains_key("t1"));
+        // Only the plan itself is snapshotted
+        let part = part_path.to_string_lossy();
+        assert!(history::load_plan_snapshots(&part).unwrap().is_empty());
+
+        // Removing an included phase empties its file but keeps the directive
+        remove_phase(path, "phase_2".to_string()).unwrap();
+        assert_eq!(fs::read_to_string(&part_path).unwrap(), "");
+        assert!(fs::read_to_string(&plan_path)
+            .unwrap()
+            .contains("<!-- include: part.md -->"));
+    }
+
+    #[test]
+    fn test_move_task_keeps_notes_and_spacing() {
+        let mut doc = parse(PLAN);
+        doc.move_task("t1", "phase_2", None).unwrap();
+
+        assert_eq!(
+            serialize(&doc),
+            "# Plan: Test\n\nSome prose the user wrote.\n\n## Phase 1 — Work\n- [ ] Test (depends: t1) (id: t2)\n\n## Phase 2 — Ship\n- [ ] Release (id: t3)\n* [ ] Build  (id: t1)\n    - a note\n"
+        );
+    }
+
+    #[test]
+    fn test_add_task_and_phase() {
+        let mut doc = parse(PLAN);
+        let id = doc.next_task_id();
+        assert_eq!(id, "t4");
+
+        let mut task = PlanTask::new(&id, "Document");
+        task.dependencies = vec!["t2".to_string()];
+        doc.add_task("phase_1", task, None).unwrap();
+        let phase_id = doc.add_phase("Follow-up").unwrap();
+        assert_eq!(phase_id, "phase_3");
+
+        let markdown = serialize(&doc);
+        assert!(markdown.contains(
+            "- [ ] Test (depends: t1) (id: t2)\n- [ ] Document (depends: t2) (id: t4)\n\n## Phase 2"
+        ));
+        assert!(markdown.ends_with("- [ ] Release (id: t3)\n\n## Phase 3 — Follow-up\n"));
+
+        assert!(doc
+            .add_task("phase_1", PlanTask::new("t1", "Dup"), None)
+            .is_err());
+        assert!(doc
+            .add_task("phase_9", PlanTask::new("t9", "X"), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_rename_phase_and_dependencies() {
+        let mut doc = parse(PLAN);
+        doc.rename_phase("phase_2", "Release").unwrap();
+        doc.add_dependency("t3", "t2").unwrap();
+        doc.remove_dependency("t2", "t1").unwrap();
+        assert!(doc.add_dependency("t3", "t3").is_err());
+        assert!(doc.add_dependency("t3", "missing").is_err());
+
+        let markdown = serialize(&doc);
+        assert!(markdown.contains("## Phase 2 — Release\n- [ ] Release (depends: t2) (id: t3)\n"));
+        assert!(markdown.contains("- [ ] Test (id: t2)\n"));
+        assert!(markdown.contains("Some prose the user wrote."));
+    }
+
+    #[test]
+    fn test_subtask_edits() {
+        let markdown = "## Phase 1 — Work\n- [ ] Parent (id: t1)\n  - [x] Done (id: t2)\n  - [ ] Open (id: t3)\n    note\n\n## Phase 2 — Ship\n- [ ] Release (depends: t3) (id: t4)\n";
+        let mut doc = parse(markdown);
+
+        // Finishing the last open subtask completes the parent
+        doc.set_task_status("t3", TaskStatus::Completed).unwrap();
+        assert_eq!(doc.task("t1").unwrap().status, TaskStatus::Completed);
+
+        // A new open subtask reopens it
+        doc.add_subtask("t1", PlanTask::new("t5", "More"), None).unwrap();
+        assert_eq!(doc.task("t1").unwrap().status, TaskStatus::InProgress);
+        assert_eq!(
+            serialize(&doc),
+            "## Phase 1 — Work\n- [-] Parent (id: t1)\n  - [x] Done (id: t2)\n  - [x] Open (id: t3)\n    note\n  - [ ] More (id: t5)\n\n## Phase 2 — Ship\n- [ ] Release (depends: t3) (id: t4)\n"
+        );
+
+        // Removing a parent takes its subtasks and their dependents' references
+        doc.remove_task("t1").unwrap();
+        assert!(doc.task("t3").is_none());
+        assert!(doc.task("t4").unwrap().dependencies.is_empty());
+        assert_eq!(
+            serialize(&doc),
+            "## Phase 1 — Work\n\n## Phase 2 — Ship\n- [ ] Release (id: t4)\n"
+        );
+    }
+
+    #[test]
+    fn test_status_reason() {
+        let mut doc = parse(PLAN);
+        assert!(doc.set_status_reason("t3", "vendor").is_err());
+
+        doc.set_task_status("t3", TaskStatus::Blocked).unwrap();
+        doc.set_status_reason("t3", "waiting on vendor").unwrap();
+        assert!(serialize(&doc).ends_with("- [!] Release (reason: waiting on vendor) (id: t3)\n"));
+
+        doc.set_task_status("t3", TaskStatus::Completed).unwrap();
+        assert!(serialize(&doc).ends_with("- [x] Release (id: t3)\n"));
+    }
+
+    #[test]
+    fn test_set_task_meta() {
+        let mut doc = parse(PLAN);
+        let meta = TaskMeta {
+            owner: Some("@sam".to_string()),
+            tags: vec![" api ".to_string(), String::new()],
+            priority: Some(1),
+            ..Default::default()
+        };
+        doc.set_task_meta("t3", meta).unwrap();
+
+        assert!(serialize(&doc).ends_with("- [ ] Release (owner: @sam) (tags: api) (p1) (id: t3)\n"));
+        let bad_owner = TaskMeta {
+            owner: Some("two words".to_string()),
+            ..Default::default()
+        };
+        assert!(doc.set_task_meta("t3", bad_owner).is_err());
+    }
+
+    #[test]
+    fn test_add_task_rejects_unsafe_text() {
+        let (_temp, plan_path) = write_plan();
+        let add = |content: &str| {
+            add_task(
+                plan_path.clone(),
+                "phase_1".to_string(),
+                content.to_string(),
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+        assert!(add("x\n- [x] Fake (id: t99)").is_err());
+        assert!(add("  ").is_err());
+        assert!(add("Ship (depends: t1)").is_err());
+        assert_eq!(fs::read_to_string(&plan_path).unwrap(), PLAN);
+
+        let mut doc = parse(PLAN);
+        assert!(doc.add_phase("Next\n- [ ] Fake (id: t99)").is_err());
+        assert!(doc.rename_phase("phase_1", "Work\n## Phase 9 — X").is_err());
+        assert!(doc.add_dependency("t3", "a.md), (owner: x#t1").is_err());
+        assert!(doc.add_dependency("t3", "a.md, b.md#t1").is_err());
+        assert_eq!(serialize(&doc), PLAN);
+    }
+
+    #[test]
+    fn test_edited_text_round_trips() {
+        let mut doc = parse(PLAN);
+        for content in [
+            "Ship (depends: t1) (p1)",
+            "Done (id: t7)",
+            "Call (owner: @x)",
+        ] {
+            assert!(doc.set_task_content("t3", content).is_err());
+        }
+
+        doc.set_task_content("t3", "Release v2 (final) on p2: 10:00")
+            .unwrap();
+        doc.add_dependency("t3", "../api/plan.md#t4").unwrap();
+        let reread = parse(&serialize(&doc));
+        let task = reread.task("t3").unwrap();
+        assert_eq!(task.content, "Release v2 (final) on p2: 10:00");
+        assert_eq!(task.dependencies, vec!["../api/plan.md#t4"]);
+        assert_eq!(task.meta, TaskMeta::default());
+    }
+
+    #[test]
+    fn test_edit_errors_leave_file_untouched() {
+        let (_temp, plan_path) = write_plan();
+        assert!(set_task_text(plan_path.clone(), "missing".to_string(), "X".to_string()).is_err());
+        assert_eq!(fs::read_to_string(&plan_path).unwrap(), PLAN);
+    }
+}
@@ -0,0 +1,239 @@
+//! Plan front matter
+//!
+//! Optional plan-level settings in a YAML block at the very top of plan.md:
+//!
+//! ```text
+//! ---
+//! owner: "@sam"
+//! repository: github.com/acme/app
+//! agent: codex
+//! cwd: ../app
+//! layout: column
+//! id_scheme: slug
+//! status_labels:
+//!   pending: To do
+//!   in_progress: Doing
+//! ---
+//! ```
+//!
+//! Only this flat subset of YAML is read: `key: value` pairs, optionally
+//! quoted, and one level of nesting for `status_labels`. Unknown keys and
+//! lines that don't fit are ignored. The block itself stays part of the
+//! document header, so it round-trips unchanged.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::normalize::IdScheme;
+use super::TaskStatus;
+use crate::layout::LayoutStrategy;
+
+/// Plan-level settings from front matter
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// Agent to preselect when the plan is opened (`agent` or `default_agent`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Working directory for agents, relative to the plan's directory
+    /// (`cwd` or `working_directory`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// How new nodes are placed on the canvas (`layout` or `layout_strategy`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<LayoutStrategy>,
+    /// How normalization generates task IDs (`id_scheme`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_scheme: Option<IdScheme>,
+    /// Display names for statuses
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub status_labels: HashMap<TaskStatus, String>,
+}
+
+impl PlanSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Display name for a status: its custom label, or the serialized name
+    pub fn status_label(&self, status: TaskStatus) -> &str {
+        self.status_labels
+            .get(&status)
+            .map_or(status.as_str(), String::as_str)
+    }
+
+    /// Working directory resolved against the directory of the plan at `plan_path`
+    pub fn resolve_cwd(&self, plan_path: &Path) -> Option<PathBuf> {
+        let cwd = Path::new(self.cwd.as_deref()?);
+        match plan_path.parent() {
+            Some(dir) if cwd.is_relative() => Some(dir.join(cwd)),
+            _ => Some(cwd.to_path_buf()),
+        }
+    }
+}
+
+/// Whether a line delimits front matter (`---`, or `...` to close it)
+fn is_delimiter(line: &str, closing: bool) -> bool {
+    let line = line.trim_end();
+    line == "---" || (closing && line == "...")
+}
+
+/// Number of lines taken by front matter at the start of `lines`, delimiters included
+/// 0 when the document doesn't open with a closed `---` block
+pub fn front_matter_len(lines: &[&str]) -> usize {
+    if !lines.first().is_some_and(|line| is_delimiter(line, false)) {
+        return 0;
+    }
+    lines[1..]
+        .iter()
+        .position(|line| is_delimiter(line, true))
+        .map_or(0, |end| end + 2)
+}
+
+/// A scalar value: unquoted with trailing comments removed, or the inside of quotes
+/// Empty values, `~` and `null` give None
+fn parse_scalar(value: &str) -> Option<String> {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return Some(inner.to_string());
+        }
+    }
+
+    let value = match value.find(" #") {
+        Some(comment) => value[..comment].trim_end(),
+        None if value.starts_with('#') => "",
+        None => value,
+    };
+    match value {
+        "" | "~" | "null" => None,
+        _ => Some(value.to_string()),
+    }
+}
+
+/// Status for a `status_labels` key, e.g. "in_progress" or "in-progress"
+fn parse_status_key(key: &str) -> Option<TaskStatus> {
+    let key = key.replace('-', "_");
+    [
+        TaskStatus::Pending,
+        TaskStatus::InProgress,
+        TaskStatus::Completed,
+        TaskStatus::Blocked,
+        TaskStatus::Cancelled,
+    ]
+    .into_iter()
+    .find(|status| status.as_str() == key)
+}
+
+/// Parse the lines between the front matter delimiters
+pub fn parse_front_matter(lines: &[&str]) -> PlanSettings {
+    let mut settings = PlanSettings::default();
+    let mut in_labels = false;
+
+    for line in lines {
+        let line = line.trim_end_matches('\r');
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = parse_scalar(value);
+
+        let nested = line.starts_with([' ', '\t']);
+        if nested {
+            if in_labels {
+                if let (Some(status), Some(label)) = (parse_status_key(key), value) {
+                    settings.status_labels.insert(status, label);
+                }
+            }
+            continue;
+        }
+
+        in_labels = false;
+        match key {
+            "owner" => settings.owner = value,
+            "repository" => settings.repository = value,
+            "agent" | "default_agent" => settings.agent = value,
+            "cwd" | "working_directory" => settings.cwd = value,
+            "layout" | "layout_strategy" => {
+                settings.layout = value.and_then(|v| LayoutStrategy::parse(&v));
+            }
+            "id_scheme" => settings.id_scheme = value.and_then(|v| IdScheme::parse(&v)),
+            "status_labels" => in_labels = value.is_none(),
+            _ => {}
+        }
+    }
+
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_front_matter_len() {
+        assert_eq!(
+            front_matter_len(&["---", "owner: sam", "---", "# Plan: A"]),
+            3
+        );
+        assert_eq!(front_matter_len(&["---", "owner: sam", "..."]), 3);
+        // Unclosed blocks and a rule further down aren't front matter
+        assert_eq!(front_matter_len(&["---", "owner: sam"]), 0);
+        assert_eq!(front_matter_len(&["# Plan: A", "---", "---"]), 0);
+    }
+
+    #[test]
+    fn test_parse_front_matter() {
+        let settings = parse_front_matter(&[
+            "owner: \"@sam\"",
+            "repository: github.com/acme/app # main repo",
+            "default_agent: codex",
+            "working_directory: '../app'",
+            "layout: column",
+            "id_scheme: slug",
+            "status_labels:",
+            "  pending: To do",
+            "  in-progress: Doing",
+            "  unknown: Ignored",
+            "extra: ignored",
+            "  completed: Not a label",
+        ]);
+
+        assert_eq!(settings.owner.as_deref(), Some("@sam"));
+        assert_eq!(settings.repository.as_deref(), Some("github.com/acme/app"));
+        assert_eq!(settings.agent.as_deref(), Some("codex"));
+        assert_eq!(settings.cwd.as_deref(), Some("../app"));
+        assert_eq!(settings.layout, Some(LayoutStrategy::Column));
+        assert_eq!(settings.id_scheme, Some(IdScheme::Slug));
+        assert_eq!(settings.status_labels.len(), 2);
+        assert_eq!(settings.status_label(TaskStatus::InProgress), "Doing");
+        assert_eq!(settings.status_label(TaskStatus::Completed), "completed");
+    }
+
+    #[test]
+    fn test_resolve_cwd() {
+        let settings = PlanSettings {
+            cwd: Some("../app".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.resolve_cwd(Path::new("/work/plans/plan.md")),
+            Some(PathBuf::from("/work/plans/../app"))
+        );
+        assert!(PlanSettings::default()
+            .resolve_cwd(Path::new("/work/plan.md"))
+            .is_none());
+    }
+}
@@ -0,0 +1,456 @@
+//! Dependency graph analysis
+//!
+//! Computes with the `(depends: ...)` annotations of a parsed plan: which
+//! tasks can be started now, which are waiting and on what, an execution
+//! order and the critical path through the remaining work.
+//!
+//! A dependency may name a task or a phase; a phase dependency stands for all
+//! of that phase's tasks. Unknown dependencies are ignored here (the linter
+//! reports them). A task with subtasks implicitly depends on them and is
+//! finished once they are.
+//!
+//! Cross-plan dependencies block a task until the referenced task is finished.
+//! They don't take part in ordering or the critical path, which only cover
+//! this plan; unfinished ones are listed as cross-plan blockers instead.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use super::crossplan::{CrossPlanRef, ReferencedPlans};
+use super::{read_plan_file, PlanDoc, PlanTask, TaskStatus};
+
+/// A task waiting on unfinished dependencies or marked blocked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedTask {
+    pub task_id: String,
+    /// Dependencies, as written, that aren't finished yet
+    pub blocked_by: Vec<String>,
+    /// Marked `[!]`: blocked on something outside the plan
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub external: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// An unfinished task in another plan that tasks here wait on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossPlanBlocker {
+    /// The dependency as written, e.g. "../backend/plan.md#t12"
+    pub dependency: String,
+    /// Resolved path of the other plan
+    pub plan_path: String,
+    pub task_id: String,
+    pub content: String,
+    pub status: TaskStatus,
+    /// Tasks in this plan waiting on it, in document order
+    pub blocks: Vec<String>,
+}
+
+/// The longest chain of remaining work
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CriticalPath {
+    /// Task IDs from first to last
+    pub task_ids: Vec<String>,
+    /// Sum of the task weights along the path
+    pub length: f64,
+}
+
+/// Result of analyzing a plan's dependency graph
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphAnalysis {
+    /// Unfinished tasks whose dependencies are all finished, in document order
+    /// Tasks with subtasks are never listed; their subtasks are
+    pub ready: Vec<String>,
+    /// Unfinished tasks waiting on other tasks, in document order
+    pub blocked: Vec<BlockedTask>,
+    /// All tasks in an order that respects dependencies, ties broken by document order
+    pub order: Vec<String>,
+    /// Tasks that can't be ordered because they are on or behind a dependency cycle
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unordered: Vec<String>,
+    pub critical_path: CriticalPath,
+    /// Unfinished tasks in other plans, in order of first mention
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cross_plan_blockers: Vec<CrossPlanBlocker>,
+}
+
+/// Whether a task counts as done for its dependents
+pub(crate) fn is_finished(task: &PlanTask) -> bool {
+    task.rollup_status().is_closed()
+}
+
+/// Weight of a task on the critical path: its estimate in hours, or 1 without one
+/// A task with subtasks weighs nothing itself; its subtasks carry the work
+pub(crate) fn task_weight(task: &PlanTask) -> f64 {
+    if !task.subtasks.is_empty() {
+        return 0.0;
+    }
+    task.meta.estimate.map_or(1.0, |estimate| estimate.hours())
+}
+
+/// Dependency graph over the tasks of a plan, indexed in document order
+struct TaskGraph<'a> {
+    tasks: Vec<&'a PlanTask>,
+    /// For each task, the tasks it depends on
+    dependencies: Vec<Vec<usize>>,
+}
+
+impl<'a> TaskGraph<'a> {
+    fn new(doc: &'a PlanDoc) -> Self {
+        let tasks: Vec<&PlanTask> = doc.tasks().collect();
+
+        // First occurrence wins for duplicate IDs, as in `PlanDoc::task`
+        let mut index: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, task) in tasks.iter().enumerate() {
+            index.entry(task.id.as_str()).or_insert_with(|| vec![i]);
+        }
+        let mut offset = 0;
+        for phase in &doc.phases {
+            let count = phase.all_tasks().count();
+            index
+                .entry(phase.id.as_str())
+                .or_insert_with(|| (offset..offset + count).collect());
+            offset += count;
+        }
+
+        let dependencies = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| {
+                let subtasks = task.subtasks.iter().map(|t| &t.id);
+                let mut deps: Vec<usize> = task
+                    .dependencies
+                    .iter()
+                    .chain(subtasks)
+                    .filter_map(|dep| index.get(dep.as_str()))
+                    .flatten()
+                    .copied()
+                    .filter(|&d| d != i)
+                    .collect();
+                deps.sort_unstable();
+                deps.dedup();
+                deps
+            })
+            .collect();
+
+        Self {
+            tasks,
+            dependencies,
+        }
+    }
+
+    /// Dependencies of a task, as written, that aren't finished
+    fn unfinished_dependencies(
+        &self,
+        doc: &PlanDoc,
+        task: &PlanTask,
+        referenced: &ReferencedPlans,
+    ) -> Vec<String> {
+        task.dependencies
+            .iter()
+            .filter(|dep| {
+                if let Some(reference) = CrossPlanRef::parse(dep) {
+                    return referenced.task(&reference).is_ok_and(|t| !is_finished(t));
+                }
+                match (doc.task(dep), doc.phase(dep)) {
+                    (Some(t), _) => !is_finished(t),
+                    (None, Some(phase)) => !phase.tasks.iter().all(is_finished),
+                    (None, None) => false,
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Kahn's algorithm, always taking the earliest available task in document order
+    fn topological_order(&self) -> (Vec<usize>, Vec<usize>) {
+        let count = self.tasks.len();
+        let mut pending: Vec<usize> = self.dependencies.iter().map(Vec::len).collect();
+        let mut dependents = vec![Vec::new(); count];
+        for (task, deps) in self.dependencies.iter().enumerate() {
+            for &dep in deps {
+                dependents[dep].push(task);
+            }
+        }
+
+        let mut available: BTreeSet<usize> = (0..count).filter(|&i| pending[i] == 0).collect();
+        let mut order = Vec::with_capacity(count);
+        while let Some(task) = available.pop_first() {
+            order.push(task);
+            for &dependent in &dependents[task] {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 {
+                    available.insert(dependent);
+                }
+            }
+        }
+
+        let unordered = (0..count).filter(|&i| pending[i] > 0).collect();
+        (order, unordered)
+    }
+
+    /// Longest weighted chain of unfinished tasks, following `order`
+    fn critical_path(&self, order: &[usize]) -> CriticalPath {
+        let count = self.tasks.len();
+        let mut length = vec![0.0_f64; count];
+        let mut previous: Vec<Option<usize>> = vec![None; count];
+        let mut best: Option<usize> = None;
+
+        for &task in order {
+            if is_finished(self.tasks[task]) {
+                continue;
+            }
+            let before = self.dependencies[task]
+                .iter()
+                .copied()
+                .filter(|&dep| !is_finished(self.tasks[dep]))
+                .max_by(|&a, &b| length[a].total_cmp(&length[b]).then(b.cmp(&a)));
+
+            length[task] = task_weight(self.tasks[task]) + before.map_or(0.0, |dep| length[dep]);
+            previous[task] = before;
+            if best.is_none_or(|b| length[task] > length[b]) {
+                best = Some(task);
+            }
+        }
+
+        let Some(end) = best else {
+            return CriticalPath::default();
+        };
+        let mut task_ids = Vec::new();
+        let mut current = Some(end);
+        while let Some(task) = current {
+            task_ids.push(self.tasks[task].id.clone());
+            current = previous[task];
+        }
+        task_ids.reverse();
+
+        CriticalPath {
+            task_ids,
+            length: length[end],
+        }
+    }
+}
+
+/// Record that `task_id` waits on the unfinished cross-plan dependencies in `blocked_by`
+fn add_cross_plan_blockers(
+    blockers: &mut Vec<CrossPlanBlocker>,
+    task_id: &str,
+    blocked_by: &[String],
+    referenced: &ReferencedPlans,
+) {
+    for dep in blocked_by {
+        let Some(reference) = CrossPlanRef::parse(dep) else {
+            continue;
+        };
+        let (Some(plan_path), Ok(task)) = (referenced.resolve(&reference), referenced.task(&reference))
+        else {
+            continue;
+        };
+        let plan_path = plan_path.to_string_lossy().to_string();
+
+        let index = match blockers
+            .iter()
+            .position(|b| b.plan_path == plan_path && b.task_id == task.id)
+        {
+            Some(index) => index,
+            None => {
+                blockers.push(CrossPlanBlocker {
+                    dependency: dep.clone(),
+                    plan_path,
+                    task_id: task.id.clone(),
+                    content: task.content.clone(),
+                    status: task.rollup_status(),
+                    blocks: Vec::new(),
+                });
+                blockers.len() - 1
+            }
+        };
+        if !blockers[index].blocks.iter().any(|id| id == task_id) {
+            blockers[index].blocks.push(task_id.to_string());
+        }
+    }
+}
+
+impl PlanDoc {
+    /// Analyze the dependency graph of this plan
+    /// Cross-plan dependencies are ignored; see `analyze_with`
+    pub fn analyze(&self) -> GraphAnalysis {
+        self.analyze_with(&ReferencedPlans::default())
+    }
+
+    /// Analyze the dependency graph, checking cross-plan dependencies against `referenced`
+    pub fn analyze_with(&self, referenced: &ReferencedPlans) -> GraphAnalysis {
+        let graph = TaskGraph::new(self);
+        let mut analysis = GraphAnalysis::default();
+
+        for task in graph.tasks.iter().filter(|t| !is_finished(t)) {
+            let blocked_by = graph.unfinished_dependencies(self, task, referenced);
+            add_cross_plan_blockers(
+                &mut analysis.cross_plan_blockers,
+                &task.id,
+                &blocked_by,
+                referenced,
+            );
+            let external = task.status == TaskStatus::Blocked;
+            if blocked_by.is_empty() && !external {
+                if task.subtasks.is_empty() {
+                    analysis.ready.push(task.id.clone());
+                }
+            } else {
+                analysis.blocked.push(BlockedTask {
+                    task_id: task.id.clone(),
+                    blocked_by,
+                    external,
+                    reason: task.meta.reason.clone(),
+                });
+            }
+        }
+
+        let (order, unordered) = graph.topological_order();
+        analysis.critical_path = graph.critical_path(&order);
+        analysis.order = order.iter().map(|&i| graph.tasks[i].id.clone()).collect();
+        analysis.unordered = unordered
+            .iter()
+            .map(|&i| graph.tasks[i].id.clone())
+            .collect();
+        analysis
+    }
+}
+
+/// Analyze the dependency graph of a plan file, including its cross-plan dependencies
+pub fn analyze_plan_file(plan_path: &str) -> Result<GraphAnalysis, String> {
+    let doc = read_plan_file(plan_path)?;
+    let referenced = ReferencedPlans::load(Path::new(plan_path), &doc);
+    Ok(doc.analyze_with(&referenced))
+}
+
+/// Analyze the dependency graph of a plan file
+#[tauri::command]
+pub fn analyze_plan(plan_path: String) -> Result<GraphAnalysis, String> {
+    analyze_plan_file(&plan_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse;
+
+    const COMPLEX_PLAN: &str =
+        include_str!("../../../src/parser/__tests__/fixtures/complex-plan.md");
+
+    #[test]
+    fn test_ready_and_blocked() {
+        let analysis = parse(COMPLEX_PLAN).analyze();
+
+        assert_eq!(analysis.ready, vec!["t4"]);
+        let blocked: Vec<&str> = analysis
+            .blocked
+            .iter()
+            .map(|b| b.task_id.as_str())
+            .collect();
+        assert_eq!(blocked, vec!["t5", "t6", "t7", "t8"]);
+        assert_eq!(analysis.blocked[1].blocked_by, vec!["t4", "t5"]);
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let markdown = "## Phase 1 — A\n- [ ] Last (depends: t3) (id: t1)\n- [ ] Free (id: t2)\n- [ ] First (id: t3)\n";
+        let analysis = parse(markdown).analyze();
+
+        assert_eq!(analysis.order, vec!["t2", "t3", "t1"]);
+        assert!(analysis.unordered.is_empty());
+    }
+
+    #[test]
+    fn test_phase_dependency() {
+        let markdown = "## Phase 1 — A\n- [x] One (id: t1)\n- [ ] Two (id: t2)\n\n## Phase 2 — B\n- [ ] Three (depends: phase_1) (id: t3)\n";
+        let analysis = parse(markdown).analyze();
+
+        assert_eq!(analysis.ready, vec!["t2"]);
+        assert_eq!(analysis.blocked[0].blocked_by, vec!["phase_1"]);
+        assert_eq!(analysis.order, vec!["t1", "t2", "t3"]);
+        assert_eq!(analysis.critical_path.task_ids, vec!["t2", "t3"]);
+    }
+
+    #[test]
+    fn test_critical_path_skips_finished_tasks() {
+        let analysis = parse(COMPLEX_PLAN).analyze();
+
+        assert_eq!(
+            analysis.critical_path.task_ids,
+            vec!["t4", "t5", "t6", "t7", "t8"]
+        );
+        assert_eq!(analysis.critical_path.length, 5.0);
+    }
+
+    #[test]
+    fn test_cancelled_and_blocked_tasks() {
+        let markdown = "## Phase 1 — A\n- [~] Dropped (id: t1)\n- [!] Vendor (depends: t1) (reason: no keys) (id: t2)\n- [ ] Next (depends: t1) (id: t3)\n";
+        let analysis = parse(markdown).analyze();
+
+        assert_eq!(analysis.ready, vec!["t3"]);
+        assert_eq!(analysis.blocked.len(), 1);
+        assert!(analysis.blocked[0].external);
+        assert!(analysis.blocked[0].blocked_by.is_empty());
+        assert_eq!(analysis.blocked[0].reason.as_deref(), Some("no keys"));
+    }
+
+    #[test]
+    fn test_subtasks() {
+        let markdown = "## Phase 1 — A\n- [ ] Parent (id: t1)\n  - [x] Done (id: t2)\n  - [ ] Open (est: 2h) (id: t3)\n- [ ] After (depends: t1) (id: t4)\n";
+        let analysis = parse(markdown).analyze();
+
+        assert_eq!(analysis.ready, vec!["t3"]);
+        assert_eq!(analysis.blocked[0].task_id, "t4");
+        assert_eq!(analysis.order, vec!["t2", "t3", "t1", "t4"]);
+        assert_eq!(analysis.critical_path.task_ids, vec!["t3", "t1", "t4"]);
+        assert_eq!(analysis.critical_path.length, 3.0);
+    }
+
+    #[test]
+    fn test_cross_plan_blockers() {
+        let temp = tempfile::tempdir().unwrap();
+        let backend = temp.path().join("backend");
+        std::fs::create_dir(&backend).unwrap();
+        std::fs::write(
+            backend.join("plan.md"),
+            "## Phase 1 — API\n- [x] Auth (id: t11)\n- [-] Endpoints (id: t12)\n",
+        )
+        .unwrap();
+        let plan_path = temp.path().join("plan.md");
+        std::fs::write(
+            &plan_path,
+            "## Phase 1 — UI\n- [ ] Login (depends: backend/plan.md#t11) (id: t1)\n- [ ] Screens (depends: t1, backend/plan.md#t12) (id: t2)\n- [ ] List (depends: backend/plan.md#t12) (id: t3)\n",
+        )
+        .unwrap();
+
+        let analysis = super::analyze_plan_file(&plan_path.to_string_lossy()).unwrap();
+        assert_eq!(analysis.ready, vec!["t1"]);
+        assert_eq!(analysis.blocked[0].blocked_by, vec!["t1", "backend/plan.md#t12"]);
+        assert_eq!(analysis.order, vec!["t1", "t2", "t3"]);
+
+        assert_eq!(analysis.cross_plan_blockers.len(), 1);
+        let blocker = &analysis.cross_plan_blockers[0];
+        assert_eq!(blocker.task_id, "t12");
+        assert_eq!(blocker.status, super::TaskStatus::InProgress);
+        assert_eq!(blocker.blocks, vec!["t2", "t3"]);
+
+        // Without the referenced plans, cross-plan dependencies are ignored
+        let analysis = parse(&std::fs::read_to_string(&plan_path).unwrap()).analyze();
+        assert_eq!(analysis.ready, vec!["t1", "t3"]);
+    }
+
+    #[test]
+    fn test_cycle_is_unordered() {
+        let markdown = "## Phase 1 — A\n- [ ] One (depends: t2) (id: t1)\n- [ ] Two (depends: t1) (id: t2)\n- [ ] Three (depends: t2) (id: t3)\n- [ ] Four (id: t4)\n";
+        let analysis = parse(markdown).analyze();
+
+        assert_eq!(analysis.order, vec!["t4"]);
+        assert_eq!(analysis.unordered, vec!["t1", "t2", "t3"]);
+        assert_eq!(analysis.ready, vec!["t4"]);
+    }
+}
@@ -0,0 +1,289 @@
+//! Plan includes
+//!
+//! A line `<!-- include: phases/03-auth.md -->` splices the phases of another
+//! file into the plan where the directive stands. The path is relative to the
+//! directory of the file containing the directive, and included files may
+//! include further files. Lines of an included file before its first phase
+//! stay with that file but don't show up in the plan.
+//!
+//! Each spliced phase remembers its file (`PlanPhase::file`): `serialize`
+//! writes only the plan's own phases, next to the directives, and
+//! `serialize_included` gives the content of every included file. A file that
+//! can't be read, or that is already part of the plan, is left out; the linter
+//! reports both.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::parser::is_fence;
+use super::{parse, serialize, PlanDoc, PlanPhase, Span};
+
+/// An included file, as parsed without its phases
+/// Keeps the file's own header, line endings and final newline for writing it back
+#[derive(Debug, Clone)]
+pub(crate) struct IncludedFile {
+    pub path: PathBuf,
+    pub template: PlanDoc,
+}
+
+/// An `<!-- include: path -->` line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncludeDirective<'a> {
+    pub span: Span,
+    /// Path as written, relative to the including file's directory
+    pub path: &'a str,
+}
+
+impl IncludeDirective<'_> {
+    /// Location of the included file for a directive written in `file`
+    pub fn resolve(&self, file: &Path) -> PathBuf {
+        match file.parent() {
+            Some(dir) => dir.join(self.path),
+            None => PathBuf::from(self.path),
+        }
+    }
+}
+
+/// Path of an include directive line
+fn parse_directive(line: &str) -> Option<&str> {
+    let path = line
+        .trim()
+        .strip_prefix("<!--")?
+        .strip_suffix("-->")?
+        .trim()
+        .strip_prefix("include:")?
+        .trim();
+    (!path.is_empty()).then_some(path)
+}
+
+/// Include directives outside fenced code blocks, in document order
+pub fn find_includes(markdown: &str) -> Vec<IncludeDirective<'_>> {
+    let mut directives = Vec::new();
+    let mut in_fence = false;
+    let mut start = 0;
+
+    for (index, line) in markdown.split('\n').enumerate() {
+        let end = start + line.len();
+        let text = line.trim_end_matches('\r');
+        if is_fence(text) {
+            in_fence = !in_fence;
+        } else if let Some(path) = parse_directive(text).filter(|_| !in_fence) {
+            directives.push(IncludeDirective {
+                span: Span {
+                    line: index + 1,
+                    start,
+                    end: start + text.len(),
+                },
+                path,
+            });
+        }
+        start = end + 1;
+    }
+    directives
+}
+
+/// Identity of a file for detecting repeated and cyclic includes
+fn file_key(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Reads files for a plan and the files it includes
+struct Loader<'a> {
+    read: &'a dyn Fn(&Path) -> io::Result<String>,
+    /// Files already part of the plan, the plan itself included
+    seen: HashSet<PathBuf>,
+    included: Vec<IncludedFile>,
+}
+
+impl Loader<'_> {
+    /// Phases of `file`, parsed from `markdown`, with included phases spliced in
+    /// after the phase whose lines hold the directive (or first, for directives
+    /// before any phase)
+    fn splice(&mut self, file: &Path, markdown: &str, phases: Vec<PlanPhase>) -> Vec<PlanPhase> {
+        let mut directives = find_includes(markdown).into_iter().peekable();
+        let mut spliced = Vec::new();
+        for phase in phases {
+            let start = phase.span.map_or(0, |span| span.line);
+            while let Some(directive) = directives.next_if(|d| d.span.line < start) {
+                spliced.extend(self.include(&directive.resolve(file)));
+            }
+            spliced.push(phase);
+        }
+        for directive in directives {
+            spliced.extend(self.include(&directive.resolve(file)));
+        }
+        spliced
+    }
+
+    /// Phases of an included file and the files it includes in turn
+    fn include(&mut self, path: &Path) -> Vec<PlanPhase> {
+        if !self.seen.insert(file_key(path)) {
+            return Vec::new();
+        }
+        let Ok(markdown) = (self.read)(path) else {
+            return Vec::new();
+        };
+
+        let mut template = parse(&markdown);
+        let mut phases = std::mem::take(&mut template.phases);
+        for phase in &mut phases {
+            phase.file = Some(path.to_path_buf());
+        }
+
+        self.included.push(IncludedFile {
+            path: path.to_path_buf(),
+            template,
+        });
+        self.splice(path, &markdown, phases)
+    }
+}
+
+/// Read a plan file and splice in the phases of the files it includes
+pub fn load(plan_path: &Path) -> Result<PlanDoc, String> {
+    load_with(plan_path, &|path| fs::read_to_string(path))
+}
+
+/// Like `load`, reading every file through `read`
+pub(crate) fn load_with(
+    plan_path: &Path,
+    read: &dyn Fn(&Path) -> io::Result<String>,
+) -> Result<PlanDoc, String> {
+    let markdown = read(plan_path).map_err(|e| format!("Failed to read plan file: {}", e))?;
+    let mut doc = parse(&markdown);
+    if find_includes(&markdown).is_empty() {
+        return Ok(doc);
+    }
+
+    let mut loader = Loader {
+        read,
+        seen: HashSet::from([file_key(plan_path)]),
+        included: Vec::new(),
+    };
+    let phases = std::mem::take(&mut doc.phases);
+    doc.phases = loader.splice(plan_path, &markdown, phases);
+    doc.included = loader.included;
+    Ok(doc)
+}
+
+impl PlanDoc {
+    /// Files included by the plan, directly or through other included files
+    pub fn included_files(&self) -> impl Iterator<Item = &Path> {
+        self.included.iter().map(|file| file.path.as_path())
+    }
+
+    /// Content of every included file, with the phases that came from it
+    pub fn serialize_included(&self) -> Vec<(PathBuf, String)> {
+        self.included
+            .iter()
+            .map(|file| {
+                let mut doc = file.template.clone();
+                doc.phases = self
+                    .phases
+                    .iter()
+                    .filter(|phase| phase.file.as_deref() == Some(file.path.as_path()))
+                    .map(|phase| PlanPhase {
+                        file: None,
+                        ..phase.clone()
+                    })
+                    .collect();
+                (file.path.clone(), serialize(&doc))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::TaskStatus;
+    use tempfile::tempdir;
+
+    const PLAN: &str = "# Plan: Big\n\n## Phase 1 — Setup\n- [ ] Init (id: t1)\n\n<!-- include: phases/02-auth.md -->\n\n## Phase 4 — Release\n- [ ] Ship (depends: t3) (id: t4)\n";
+    const AUTH: &str =
+        "Auth phases\n\n## Phase 2 — Auth\n- [ ] Login (id: t2)\n<!-- include: 03-tokens.md -->\n";
+    const TOKENS: &str = "## Phase 3 — Tokens\n- [ ] Refresh (depends: t2) (id: t3)\n";
+
+    fn write_plan() -> (tempfile::TempDir, PathBuf) {
+        let temp = tempdir().unwrap();
+        fs::create_dir(temp.path().join("phases")).unwrap();
+        fs::write(temp.path().join("phases/02-auth.md"), AUTH).unwrap();
+        fs::write(temp.path().join("phases/03-tokens.md"), TOKENS).unwrap();
+        let plan_path = temp.path().join("plan.md");
+        fs::write(&plan_path, PLAN).unwrap();
+        (temp, plan_path)
+    }
+
+    #[test]
+    fn test_find_includes() {
+        let markdown = "<!-- include: a.md -->\n```\n<!-- include: b.md -->\n```\n  <!--include:c.md-->\r\n<!-- include: -->\n";
+        let directives = find_includes(markdown);
+        let paths: Vec<&str> = directives.iter().map(|d| d.path).collect();
+        assert_eq!(paths, vec!["a.md", "c.md"]);
+        assert_eq!(directives[1].span.line, 5);
+        assert_eq!(
+            &markdown[directives[1].span.start..directives[1].span.end],
+            "  <!--include:c.md-->"
+        );
+    }
+
+    #[test]
+    fn test_load_splices_phases() {
+        let (temp, plan_path) = write_plan();
+        let doc = load(&plan_path).unwrap();
+
+        let ids: Vec<&str> = doc.phases.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["phase_1", "phase_2", "phase_3", "phase_4"]);
+        assert!(doc.phases[0].file.is_none());
+        assert_eq!(
+            doc.phases[2].file.as_deref(),
+            Some(temp.path().join("phases/03-tokens.md").as_path())
+        );
+        assert!(doc.validate().is_empty());
+        assert_eq!(doc.included_files().count(), 2);
+
+        // Unchanged, every file serializes back to what was read
+        assert_eq!(serialize(&doc), PLAN);
+        let included = doc.serialize_included();
+        assert_eq!(included[0].1, AUTH);
+        assert_eq!(included[1].1, TOKENS);
+    }
+
+    #[test]
+    fn test_edits_go_to_source_file() {
+        let (_temp, plan_path) = write_plan();
+        let mut doc = load(&plan_path).unwrap();
+        doc.task_mut("t3").unwrap().status = TaskStatus::Completed;
+        doc.move_task("t1", "phase_2", Some(0)).unwrap();
+
+        assert_eq!(serialize(&doc), PLAN.replace("- [ ] Init (id: t1)\n", ""));
+        let included = doc.serialize_included();
+        assert_eq!(
+            included[0].1,
+            AUTH.replace(
+                "- [ ] Login (id: t2)\n",
+                "- [ ] Init (id: t1)\n- [ ] Login (id: t2)\n"
+            )
+        );
+        assert_eq!(included[1].1, TOKENS.replace("[ ] Refresh", "[x] Refresh"));
+    }
+
+    #[test]
+    fn test_missing_and_repeated_includes() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        fs::write(
+            temp.path().join("a.md"),
+            "## Phase 2 — A\n<!-- include: plan.md -->\n",
+        )
+        .unwrap();
+        let plan = "## Phase 1 — Main\n<!-- include: a.md -->\n<!-- include: a.md -->\n<!-- include: missing.md -->\n";
+        fs::write(&plan_path, plan).unwrap();
+
+        let doc = load(&plan_path).unwrap();
+        let ids: Vec<&str> = doc.phases.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["phase_1", "phase_2"]);
+        assert_eq!(serialize(&doc), plan);
+    }
+}
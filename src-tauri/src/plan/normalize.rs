@@ -0,0 +1,534 @@
+//! Plan normalization
+//!
+//! Gives every task without an `(id: ...)` a stable, unique ID and renumbers
+//! phases consecutively, rewriting dependencies on renumbered phases. Every
+//! change is reported, and a normalized plan normalizes to itself.
+//!
+//! Included files are normalized along with the plan: IDs are unique across
+//! all of them and phases are numbered in the order they appear.
+//!
+//! New IDs follow the plan's `id_scheme` front matter setting unless one is
+//! given: `sequential` (`t7`, the default) or `slug` (from the task text,
+//! e.g. `write-migration`).
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use super::edit::{save_plan_renaming, PlanEditResult};
+use super::include::load_with;
+use super::parser::{clean_task_content, parse_checklist_line};
+use super::{parse, read_plan_file, serialize, PlanDoc, PlanTask};
+
+/// Longest slug generated from task text, before any `-2` suffix
+const MAX_SLUG_LEN: usize = 24;
+
+/// How new task IDs are generated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdScheme {
+    /// `t` followed by the next free number
+    #[default]
+    Sequential,
+    /// Lowercase words of the task text joined by `-`
+    Slug,
+}
+
+impl IdScheme {
+    /// Scheme for a setting value: "sequential" or "slug"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "sequential" => Some(Self::Sequential),
+            "slug" => Some(Self::Slug),
+            _ => None,
+        }
+    }
+}
+
+/// Hands out task IDs not yet used by any task or phase
+pub(crate) struct IdAllocator {
+    scheme: IdScheme,
+    used: HashSet<String>,
+    next: u64,
+}
+
+impl IdAllocator {
+    pub fn new(doc: &PlanDoc, scheme: IdScheme) -> Self {
+        let used: HashSet<String> = doc
+            .tasks()
+            .map(|t| t.id.clone())
+            .chain(doc.phases.iter().map(|p| p.id.clone()))
+            .collect();
+        let next = doc
+            .next_task_id()
+            .trim_start_matches('t')
+            .parse()
+            .unwrap_or(1);
+        Self { scheme, used, next }
+    }
+
+    /// A new ID for a task with the given content
+    pub fn allocate(&mut self, content: &str) -> String {
+        match self.scheme {
+            IdScheme::Slug => match slugify(content) {
+                Some(slug) => self.allocate_slug(&slug),
+                None => self.allocate_sequential(),
+            },
+            IdScheme::Sequential => self.allocate_sequential(),
+        }
+    }
+
+    fn allocate_sequential(&mut self) -> String {
+        loop {
+            let id = format!("t{}", self.next);
+            self.next += 1;
+            if self.used.insert(id.clone()) {
+                return id;
+            }
+        }
+    }
+
+    fn allocate_slug(&mut self, slug: &str) -> String {
+        if self.used.insert(slug.to_string()) {
+            return slug.to_string();
+        }
+        (2..)
+            .map(|n| format!("{}-{}", slug, n))
+            .find(|id| self.used.insert(id.clone()))
+            .expect("unbounded suffixes")
+    }
+}
+
+/// Slug of task text: ASCII letters and digits, lowercased, with runs of anything
+/// else turned into `-`; cut at a word boundary to stay short
+/// None when the text has no letters or digits
+fn slugify(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect();
+
+    let mut slug = String::new();
+    for word in &words {
+        if !slug.is_empty() {
+            if slug.len() + 1 + word.len() > MAX_SLUG_LEN {
+                break;
+            }
+            slug.push('-');
+        }
+        slug.push_str(word);
+    }
+    if slug.len() > MAX_SLUG_LEN {
+        slug.truncate(MAX_SLUG_LEN);
+    }
+    (!slug.is_empty()).then_some(slug)
+}
+
+/// A change made by normalization
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NormalizeChange {
+    /// A task without an ID was given one
+    #[serde(rename_all = "camelCase")]
+    AssignedId {
+        /// Included file the task is in; None for the plan itself
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        file: Option<PathBuf>,
+        line: usize,
+        task_id: String,
+        content: String,
+    },
+    /// A phase was renumbered, which changes its ID
+    #[serde(rename_all = "camelCase")]
+    RenumberedPhase {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        file: Option<PathBuf>,
+        line: usize,
+        from: String,
+        to: String,
+    },
+    /// A dependency on a renumbered phase was rewritten
+    #[serde(rename_all = "camelCase")]
+    UpdatedDependency {
+        task_id: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl NormalizeChange {
+    /// One-line description, e.g. "line 4: assigned t7 to \"Write tests\""
+    pub fn describe(&self) -> String {
+        let location = |file: &Option<PathBuf>, line: &usize| match file {
+            Some(file) => format!("{} line {}", file.display(), line),
+            None => format!("line {}", line),
+        };
+        match self {
+            Self::AssignedId {
+                file,
+                line,
+                task_id,
+                content,
+            } => format!(
+                "{}: assigned {} to \"{}\"",
+                location(file, line),
+                task_id,
+                content
+            ),
+            Self::RenumberedPhase {
+                file,
+                line,
+                from,
+                to,
+            } => format!("{}: renumbered {} to {}", location(file, line), from, to),
+            Self::UpdatedDependency { task_id, from, to } => {
+                format!("{}: dependency {} is now {}", task_id, from, to)
+            }
+        }
+    }
+}
+
+/// Normalized markdown and what changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Normalized {
+    pub markdown: String,
+    pub changes: Vec<NormalizeChange>,
+}
+
+/// Add `(id: ...)` to every task line without a valid ID, leaving other lines untouched
+/// `file` is the included file `markdown` was read from, if it isn't the plan itself
+fn assign_ids(markdown: &str, ids: &mut IdAllocator, file: Option<&Path>) -> Normalized {
+    let doc = parse(markdown);
+    let mut inserts = Vec::new();
+    let mut changes = Vec::new();
+
+    for span in &doc.tasks_without_id {
+        let text = &markdown[span.start..span.end];
+        let content = parse_checklist_line(text)
+            .map(|line| clean_task_content(line.text()))
+            .unwrap_or_default();
+        let task_id = ids.allocate(&content);
+        inserts.push((span.start + text.trim_end().len(), task_id.clone()));
+        changes.push(NormalizeChange::AssignedId {
+            file: file.map(Path::to_path_buf),
+            line: span.line,
+            task_id,
+            content,
+        });
+    }
+
+    let mut markdown = markdown.to_string();
+    for (at, task_id) in inserts.into_iter().rev() {
+        markdown.insert_str(at, &format!(" (id: {})", task_id));
+    }
+    Normalized { markdown, changes }
+}
+
+/// Rewrite dependencies on renumbered phases
+fn rename_dependencies(
+    tasks: &mut [PlanTask],
+    renamed: &HashMap<String, String>,
+    changes: &mut Vec<NormalizeChange>,
+) {
+    for task in tasks {
+        for dep in &mut task.dependencies {
+            if let Some(to) = renamed.get(dep) {
+                changes.push(NormalizeChange::UpdatedDependency {
+                    task_id: task.id.clone(),
+                    from: dep.clone(),
+                    to: to.clone(),
+                });
+                *dep = to.clone();
+            }
+        }
+        rename_dependencies(&mut task.subtasks, renamed, changes);
+    }
+}
+
+/// Number phases consecutively from the first phase's number (or 1)
+fn renumber_phases(doc: &mut PlanDoc) -> Vec<NormalizeChange> {
+    let first = doc
+        .phases
+        .first()
+        .and_then(|p| p.number.parse::<u64>().ok())
+        .unwrap_or(1);
+
+    let mut changes = Vec::new();
+    let mut renamed = HashMap::new();
+    let mut seen = HashSet::new();
+    for (offset, phase) in doc.phases.iter_mut().enumerate() {
+        // With duplicate numbers, dependencies mean the first phase using the ID
+        let first_use = seen.insert(phase.id.clone());
+        let number = (first + offset as u64).to_string();
+        if phase.number == number {
+            continue;
+        }
+        let id = format!("phase_{}", number);
+        changes.push(NormalizeChange::RenumberedPhase {
+            file: phase.file.clone(),
+            line: phase.span.map_or(0, |span| span.line),
+            from: phase.id.clone(),
+            to: id.clone(),
+        });
+        if first_use {
+            renamed.insert(phase.id.clone(), id.clone());
+        }
+        phase.number = number;
+        phase.id = id;
+    }
+
+    if !renamed.is_empty() {
+        for phase in &mut doc.phases {
+            rename_dependencies(&mut phase.tasks, &renamed, &mut changes);
+        }
+    }
+    changes
+}
+
+/// Assign IDs to tasks without one and renumber phases
+pub fn normalize(markdown: &str, scheme: IdScheme) -> Normalized {
+    let mut ids = IdAllocator::new(&parse(markdown), scheme);
+    let Normalized {
+        markdown,
+        mut changes,
+    } = assign_ids(markdown, &mut ids, None);
+
+    let mut doc = parse(&markdown);
+    let renumbered = renumber_phases(&mut doc);
+    if renumbered.is_empty() {
+        return Normalized { markdown, changes };
+    }
+    changes.extend(renumbered);
+    Normalized {
+        markdown: serialize(&doc),
+        changes,
+    }
+}
+
+/// Result of normalizing a plan: the changes made and the updated plan
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizeResult {
+    pub changes: Vec<NormalizeChange>,
+    #[serde(flatten)]
+    pub result: PlanEditResult,
+}
+
+/// Normalize a plan file and the files it includes, using the plan's front matter
+/// scheme unless `scheme` is given
+/// New IDs are unique across all files, and phases are numbered across them in order
+pub fn normalize_file(
+    plan_path: &str,
+    scheme: Option<IdScheme>,
+) -> Result<NormalizeResult, String> {
+    let path = Path::new(plan_path);
+    let doc = read_plan_file(plan_path)?;
+    let scheme = scheme.or(doc.settings.id_scheme).unwrap_or_default();
+    let mut ids = IdAllocator::new(&doc, scheme);
+
+    let mut changes = Vec::new();
+    let mut files = HashMap::new();
+    for file in iter::once(path).chain(doc.included_files()) {
+        let markdown =
+            fs::read_to_string(file).map_err(|e| format!("Failed to read plan file: {}", e))?;
+        let assigned = assign_ids(&markdown, &mut ids, (file != path).then_some(file));
+        changes.extend(assigned.changes);
+        files.insert(file.to_path_buf(), assigned.markdown);
+    }
+
+    let mut doc = load_with(path, &|file| match files.get(file) {
+        Some(markdown) => Ok(markdown.clone()),
+        None => fs::read_to_string(file),
+    })?;
+    let phase_ids: Vec<String> = doc.phases.iter().map(|p| p.id.clone()).collect();
+    changes.extend(renumber_phases(&mut doc));
+
+    // Keep renumbered phases where they were on the canvas; with duplicate
+    // IDs, the layout entry belongs to the first phase using the ID
+    let mut seen = HashSet::new();
+    let renamed: HashMap<String, String> = phase_ids
+        .into_iter()
+        .zip(doc.phases.iter().map(|p| p.id.clone()))
+        .filter(|(from, to)| seen.insert(from.clone()) && from != to)
+        .collect();
+
+    Ok(NormalizeResult {
+        changes,
+        result: save_plan_renaming(plan_path, &doc, &renamed)?,
+    })
+}
+
+/// Assign IDs to tasks without one and renumber phases consecutively
+/// `scheme` overrides the plan's `id_scheme` setting
+#[tauri::command]
+pub fn normalize_plan(
+    plan_path: String,
+    scheme: Option<IdScheme>,
+) -> Result<NormalizeResult, String> {
+    normalize_file(&plan_path, scheme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout;
+
+    #[test]
+    fn test_assign_sequential_ids() {
+        let markdown =
+            "## Phase 1 — A\n- [ ] One (id: t3)\n- [ ] Two\n  - [x] Nested (depends: t3)  \n";
+        let normalized = normalize(markdown, IdScheme::Sequential);
+
+        assert_eq!(
+            normalized.markdown,
+            "## Phase 1 — A\n- [ ] One (id: t3)\n- [ ] Two (id: t4)\n  - [x] Nested (depends: t3) (id: t5)  \n"
+        );
+        assert_eq!(
+            normalized.changes[1],
+            NormalizeChange::AssignedId {
+                file: None,
+                line: 4,
+                task_id: "t5".to_string(),
+                content: "Nested".to_string(),
+            }
+        );
+        let doc = parse(&normalized.markdown);
+        assert_eq!(doc.task("t4").unwrap().subtasks[0].id, "t5");
+    }
+
+    #[test]
+    fn test_assign_slug_ids() {
+        let markdown = "## Phase 1 — A\n- [ ] Set up the database schema & migrations\n- [ ] Set up the database schema & migrations\n- [ ] ???\n";
+        let normalized = normalize(markdown, IdScheme::Slug);
+        let ids: Vec<String> = parse(&normalized.markdown)
+            .tasks()
+            .map(|t| t.id.clone())
+            .collect();
+        assert_eq!(
+            ids,
+            vec!["set-up-the-database", "set-up-the-database-2", "t1"]
+        );
+    }
+
+    #[test]
+    fn test_renumber_phases() {
+        let markdown = "# Plan: P\n\n## Phase 1 — A\n- [ ] One (id: t1)\n\n## Phase 3 — B\n- [ ] Two (depends: phase_3, t1) (id: t2)\n";
+        let normalized = normalize(markdown, IdScheme::Sequential);
+
+        assert_eq!(
+            normalized.markdown,
+            "# Plan: P\n\n## Phase 1 — A\n- [ ] One (id: t1)\n\n## Phase 2 — B\n- [ ] Two (depends: phase_2, t1) (id: t2)\n"
+        );
+        assert_eq!(
+            normalized.changes,
+            vec![
+                NormalizeChange::RenumberedPhase {
+                    file: None,
+                    line: 6,
+                    from: "phase_3".to_string(),
+                    to: "phase_2".to_string(),
+                },
+                NormalizeChange::UpdatedDependency {
+                    task_id: "t2".to_string(),
+                    from: "phase_3".to_string(),
+                    to: "phase_2".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_is_idempotent() {
+        let markdown = "## Phase 2 — A\n- [ ] One\n## Phase 2 — B\n- [ ] Two (depends: phase_2)\n";
+        let once = normalize(markdown, IdScheme::Slug);
+        let twice = normalize(&once.markdown, IdScheme::Slug);
+        assert_eq!(twice.markdown, once.markdown);
+        assert!(twice.changes.is_empty());
+        assert!(parse(&once.markdown).validate().is_empty());
+    }
+
+    #[test]
+    fn test_normalize_file_with_includes() {
+        let temp = tempfile::tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let part_path = temp.path().join("part.md");
+        fs::write(
+            &plan_path,
+            "## Phase 1 — A\n- [ ] One (id: t1)\n<!-- include: part.md -->\n## Phase 5 — C\n- [ ] Three (depends: phase_4)\n",
+        )
+        .unwrap();
+        fs::write(&part_path, "## Phase 4 — B\n- [ ] Two\n").unwrap();
+
+        let result = normalize_file(&plan_path.to_string_lossy(), None).unwrap();
+        assert!(result.changes.contains(&NormalizeChange::AssignedId {
+            file: Some(part_path.clone()),
+            line: 2,
+            task_id: "t3".to_string(),
+            content: "Two".to_string(),
+        }));
+        assert_eq!(
+            fs::read_to_string(&plan_path).unwrap(),
+            "## Phase 1 — A\n- [ ] One (id: t1)\n<!-- include: part.md -->\n## Phase 3 — C\n- [ ] Three (depends: phase_2) (id: t2)\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&part_path).unwrap(),
+            "## Phase 2 — B\n- [ ] Two (id: t3)\n"
+        );
+        assert!(result.result.plan.validate().is_empty());
+    }
+
+    #[test]
+    fn test_normalize_file_keeps_layout_of_renumbered_phases() {
+        let temp = tempfile::tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        fs::write(
+            &plan_path,
+            "## Phase 1 — A\n- [ ] One (id: t1)\n## Phase 3 — B\n- [ ] Two (id: t2)\n",
+        )
+        .unwrap();
+        let at = |x: f64, y: f64| layout::NodeLayout {
+            x,
+            y,
+            width: 300.0,
+            height: 50.0,
+        };
+        let mut existing = layout::LayoutFile::default();
+        existing.layouts = HashMap::from([
+            ("phase_1".to_string(), at(10.0, 20.0)),
+            ("phase_3".to_string(), at(700.0, 900.0)),
+        ]);
+        fs::write(
+            temp.path().join("plan.md.layout.json"),
+            serde_json::to_string(&existing).unwrap(),
+        )
+        .unwrap();
+
+        let result = normalize_file(&plan_path.to_string_lossy(), None).unwrap();
+        let layouts = &result.result.layout.layout.layouts;
+        let phase_2 = layouts.get("phase_2").unwrap();
+        assert_eq!((phase_2.x, phase_2.y), (700.0, 900.0));
+        assert_eq!(layouts.get("phase_1").unwrap().x, 10.0);
+        assert!(!layouts.contains_key("phase_3"));
+        assert!(!result
+            .result
+            .layout
+            .removed_nodes
+            .contains(&"phase_3".to_string()));
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(
+            slugify("Write API docs (v2)").as_deref(),
+            Some("write-api-docs-v2")
+        );
+        assert_eq!(
+            slugify("Supercalifragilisticexpialidocious-ness").as_deref(),
+            Some("supercalifragilisticexpi")
+        );
+        assert!(slugify("—").is_none());
+    }
+}
@@ -0,0 +1,685 @@
+//! plan.md parser
+//!
+//! Line-based equivalent of the frontend's remark parser. Lines that aren't a
+//! title, phase heading or task (prose, blank lines, tasks without IDs, code
+//! blocks) are kept verbatim so the document can be written back unchanged.
+//!
+//! A task indented further than the task above it becomes that task's
+//! subtask. Items under a checklist line without an ID attach to the nearest
+//! task above with less indentation; unindented prose ends the nesting.
+//!
+//! Front matter at the top of the document is kept as header text too, after
+//! reading its settings.
+
+use chrono::NaiveDate;
+
+use super::{
+    frontmatter, Estimate, HeadingSource, LineEnding, PlanDoc, PlanPhase, PlanTask, Span, TaskMeta,
+    TaskSource, TaskStatus,
+};
+
+/// Whether a task ID has the allowed format (`[a-zA-Z0-9_-]+`)
+pub fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// A checklist line split into its parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecklistLine<'a> {
+    /// Indentation, bullet and opening bracket
+    pub prefix: &'a str,
+    pub mark: char,
+    /// Everything after the checkbox character, starting with `]`
+    pub rest: &'a str,
+}
+
+impl<'a> ChecklistLine<'a> {
+    /// Task text after the checkbox
+    pub fn text(&self) -> &'a str {
+        self.rest[1..].trim()
+    }
+}
+
+/// Split a `- [ ] text` line; the mark must be one of ` `, `x`, `X`, `-`, `!` or `~`
+pub fn parse_checklist_line(line: &str) -> Option<ChecklistLine<'_>> {
+    let indent = line.len() - line.trim_start().len();
+    let after_bullet = line[indent..].strip_prefix(['-', '*', '+'])?;
+    if !after_bullet.starts_with([' ', '\t']) {
+        return None;
+    }
+    let open = line.len() - after_bullet.trim_start().len();
+    if !line[open..].starts_with('[') {
+        return None;
+    }
+
+    let mark = line[open + 1..].chars().next()?;
+    if !matches!(mark, ' ' | 'x' | 'X' | '-' | '!' | '~') {
+        return None;
+    }
+    let rest = &line[open + 1 + mark.len_utf8()..];
+    if !rest.starts_with(']') {
+        return None;
+    }
+
+    Some(ChecklistLine {
+        prefix: &line[..=open],
+        mark,
+        rest,
+    })
+}
+
+/// Byte range of the `(id: ...)` annotation ending the text, and the ID
+fn find_task_id(text: &str) -> Option<(usize, &str)> {
+    let inner = text.trim_end().strip_suffix(')')?;
+    let start = inner.rfind("(id:")?;
+    let id = inner[start + 4..].trim_start();
+    is_valid_id(id).then_some((start, id))
+}
+
+/// Extract the task ID from `(id: ...)` at the end of the text
+pub fn extract_task_id(text: &str) -> Option<&str> {
+    find_task_id(text).map(|(_, id)| id)
+}
+
+/// Whether a character can appear in a `(depends: ...)` list
+pub(crate) fn is_dependency_char(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || c == '_'
+        || c == ','
+        || c == '-'
+        || c.is_whitespace()
+        || matches!(c, '.' | '/' | '#')
+}
+
+/// Byte range of the first `(depends: ...)` annotation, and its inner list
+fn find_dependencies(text: &str) -> Option<(usize, usize, &str)> {
+    let mut from = 0;
+    while let Some(offset) = text[from..].find("(depends:") {
+        let start = from + offset;
+        let list_start = start + "(depends:".len();
+        let list_len = text[list_start..]
+            .find(|c: char| !is_dependency_char(c))
+            .unwrap_or(text.len() - list_start);
+        let list = &text[list_start..list_start + list_len];
+        if !list.is_empty() && text[list_start + list_len..].starts_with(')') {
+            return Some((start, list_start + list_len + 1, list));
+        }
+        from = list_start;
+    }
+    None
+}
+
+/// Extract dependency IDs from `(depends: a, b)`
+/// Entries may also be cross-plan references like `../api/plan.md#t3`
+pub fn extract_dependencies(text: &str) -> Vec<String> {
+    find_dependencies(text)
+        .map(|(_, _, list)| {
+            list.split(',')
+                .map(|d| d.trim())
+                .filter(|d| !d.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Byte range of the first `(key: value)` annotation with a value that parses, and the value
+fn find_annotation<'a, T>(
+    text: &'a str,
+    key: &str,
+    parse_value: impl Fn(&'a str) -> Option<T>,
+) -> Option<(usize, usize, T)> {
+    let open = format!("({}:", key);
+    let mut from = 0;
+    while let Some(offset) = text[from..].find(&open) {
+        let start = from + offset;
+        let value_start = start + open.len();
+        if let Some(len) = text[value_start..].find(')') {
+            if let Some(value) = parse_value(text[value_start..value_start + len].trim()) {
+                return Some((start, value_start + len + 1, value));
+            }
+        }
+        from = value_start;
+    }
+    None
+}
+
+/// Owner name from `@name` (the `@` is optional)
+fn parse_owner(value: &str) -> Option<String> {
+    let name = value.strip_prefix('@').unwrap_or(value);
+    (!name.is_empty() && !name.contains(char::is_whitespace)).then(|| name.to_string())
+}
+
+fn parse_due(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// Reason text; it runs to the first `)`, so it can't contain one
+fn parse_reason(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn parse_tags(value: &str) -> Option<Vec<String>> {
+    let tags: Vec<String> = value
+        .split(',')
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+        .collect();
+    (!tags.is_empty()).then_some(tags)
+}
+
+/// Byte range of the first `(pN)` priority annotation, and N
+fn find_priority(text: &str) -> Option<(usize, usize, u8)> {
+    let mut from = 0;
+    while let Some(offset) = text[from..].find("(p") {
+        let start = from + offset;
+        let digits_start = start + 2;
+        let after = &text[digits_start..];
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0 && after[digits..].starts_with(')') {
+            if let Ok(priority) = after[..digits].parse() {
+                return Some((start, digits_start + digits + 1, priority));
+            }
+        }
+        from = digits_start;
+    }
+    None
+}
+
+/// Byte ranges of the metadata annotations present in the text
+fn find_meta_annotations(text: &str) -> Vec<(usize, usize)> {
+    [
+        find_annotation(text, "owner", parse_owner).map(|(s, e, _)| (s, e)),
+        find_annotation(text, "est", Estimate::parse).map(|(s, e, _)| (s, e)),
+        find_annotation(text, "due", parse_due).map(|(s, e, _)| (s, e)),
+        find_annotation(text, "tags", parse_tags).map(|(s, e, _)| (s, e)),
+        find_priority(text).map(|(s, e, _)| (s, e)),
+        find_annotation(text, "reason", parse_reason).map(|(s, e, _)| (s, e)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Extract owner, estimate, due date, tags, priority and reason annotations
+pub fn extract_meta(text: &str) -> TaskMeta {
+    TaskMeta {
+        owner: find_annotation(text, "owner", parse_owner).map(|(_, _, v)| v),
+        estimate: find_annotation(text, "est", Estimate::parse).map(|(_, _, v)| v),
+        due: find_annotation(text, "due", parse_due).map(|(_, _, v)| v),
+        tags: find_annotation(text, "tags", parse_tags)
+            .map(|(_, _, v)| v)
+            .unwrap_or_default(),
+        priority: find_priority(text).map(|(_, _, v)| v),
+        reason: find_annotation(text, "reason", parse_reason).map(|(_, _, v)| v),
+    }
+}
+
+/// Whether text would be read back as annotations: a `(key:` opening or a `(pN)` priority
+pub fn has_annotation_syntax(text: &str) -> bool {
+    let has_key = text.match_indices('(').any(|(start, _)| {
+        let after = &text[start + 1..];
+        let key = after.len()
+            - after
+                .trim_start_matches(|c: char| c.is_ascii_alphanumeric() || c == '_')
+                .len();
+        key > 0 && after[key..].starts_with(':')
+    });
+    has_key || find_priority(text).is_some()
+}
+
+/// Remove the ID, dependency and metadata annotations to get the task content
+pub fn clean_task_content(text: &str) -> String {
+    let mut content = text.to_string();
+    if let Some((start, _)) = find_task_id(&content) {
+        content.truncate(start);
+    }
+
+    let mut ranges = find_meta_annotations(&content);
+    ranges.extend(find_dependencies(&content).map(|(start, end, _)| (start, end)));
+    ranges.sort_unstable();
+    // An annotation can only appear inside another's value when that one is malformed
+    let mut last_end = 0;
+    ranges.retain(|&(start, end)| {
+        let keep = start >= last_end;
+        last_end = last_end.max(end);
+        keep
+    });
+    for (start, end) in ranges.into_iter().rev() {
+        content.replace_range(start..end, "");
+    }
+    content.trim().to_string()
+}
+
+/// ATX heading level and text (closing `#`s removed)
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    let text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    let text = if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        without_closing.trim_end()
+    } else {
+        text
+    };
+    Some((level, text))
+}
+
+/// Parse `Phase N — name` heading text into (number, name)
+pub fn parse_phase_heading(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix("Phase")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    let (number, rest) = rest.split_at(digits);
+    let name = rest.trim_start().strip_prefix(['—', '–', '-'])?.trim();
+    (!name.is_empty()).then_some((number, name))
+}
+
+/// Title from `# Plan: <title>` heading text, or the whole text
+fn parse_title(text: &str) -> String {
+    match text.strip_prefix("Plan:") {
+        Some(title) if !title.trim().is_empty() => title.trim().to_string(),
+        _ => text.to_string(),
+    }
+}
+
+/// Whether a line opens or closes a fenced code block
+pub(crate) fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    line.len() - trimmed.len() <= 3 && (trimmed.starts_with("```") || trimmed.starts_with("~~~"))
+}
+
+/// Indentation width in columns, counting a tab as four
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// The most recently parsed task in a list, which owns the lines that follow it
+fn last_task_mut(tasks: &mut [PlanTask]) -> Option<&mut PlanTask> {
+    let last = tasks.last_mut()?;
+    if last.subtasks.is_empty() {
+        Some(last)
+    } else {
+        last_task_mut(&mut last.subtasks)
+    }
+}
+
+/// Parse a task from a checklist line
+fn parse_task(line: &str, span: Span) -> Option<PlanTask> {
+    let checklist = parse_checklist_line(line)?;
+    let text = checklist.text();
+    let id = extract_task_id(text)?;
+
+    let status = TaskStatus::from_checkbox(checklist.mark);
+    let content = clean_task_content(text);
+    let dependencies = extract_dependencies(text);
+    let meta = extract_meta(text);
+
+    Some(PlanTask {
+        id: id.to_string(),
+        content: content.clone(),
+        status,
+        dependencies: dependencies.clone(),
+        meta: meta.clone(),
+        subtasks: Vec::new(),
+        span: Some(span),
+        source: Some(TaskSource {
+            prefix: checklist.prefix.to_string(),
+            depth: 0,
+            mark: checklist.mark,
+            rest: checklist.rest.to_string(),
+            status,
+            content,
+            dependencies,
+            meta,
+            id: id.to_string(),
+        }),
+        trailing: Vec::new(),
+    })
+}
+
+/// Where a non-structural line belongs
+fn push_raw(doc: &mut PlanDoc, line: &str) {
+    let line = line.to_string();
+    match doc.phases.last_mut() {
+        None => doc.header.push(line),
+        Some(phase) => match last_task_mut(&mut phase.tasks) {
+            Some(task) => task.trailing.push(line),
+            None => phase.intro.push(line),
+        },
+    }
+}
+
+/// Parse plan markdown
+/// Never fails: anything that isn't plan structure is kept as text
+pub fn parse(markdown: &str) -> PlanDoc {
+    let mut doc = PlanDoc {
+        trailing_newline: markdown.is_empty() || markdown.ends_with('\n'),
+        ..Default::default()
+    };
+
+    let mut lines: Vec<&str> = markdown.split('\n').collect();
+    if doc.trailing_newline {
+        lines.pop();
+    }
+
+    // CRLF only when every terminated line uses it, so mixed files still round-trip
+    let terminated = if doc.trailing_newline {
+        lines.len()
+    } else {
+        lines.len().saturating_sub(1)
+    };
+    let crlf = terminated > 0 && lines[..terminated].iter().all(|l| l.ends_with('\r'));
+    if crlf {
+        doc.line_ending = LineEnding::CrLf;
+    }
+
+    // Front matter lines are kept as header text; only their settings are read
+    let front_matter = frontmatter::front_matter_len(&lines);
+    if front_matter > 0 {
+        doc.settings = frontmatter::parse_front_matter(&lines[1..front_matter - 1]);
+    }
+
+    let mut offset = 0;
+    let mut in_fence = false;
+    // Indentation of the last task at each nesting level
+    let mut open_tasks: Vec<usize> = Vec::new();
+
+    for (index, raw) in lines.iter().enumerate() {
+        let line = if crlf && index < terminated {
+            raw.strip_suffix('\r').unwrap_or(raw)
+        } else {
+            raw
+        };
+        let span = Span {
+            line: index + 1,
+            start: offset,
+            end: offset + line.len(),
+        };
+        offset += raw.len() + 1;
+
+        if index < front_matter {
+            push_raw(&mut doc, line);
+            continue;
+        }
+        if is_fence(line) {
+            in_fence = !in_fence;
+            push_raw(&mut doc, line);
+            continue;
+        }
+        if in_fence {
+            push_raw(&mut doc, line);
+            continue;
+        }
+
+        if let Some((level, text)) = parse_heading(line) {
+            if level == 1 {
+                doc.title = parse_title(text);
+                doc.title_span = Some(span);
+                if doc.phases.is_empty() {
+                    doc.title_source = Some((doc.header.len(), doc.title.clone()));
+                }
+                push_raw(&mut doc, line);
+                continue;
+            }
+
+            if level == 2 {
+                if let Some((number, name)) = parse_phase_heading(text) {
+                    let mut phase = PlanPhase::new(number, name);
+                    phase.span = Some(span);
+                    phase.source = Some(HeadingSource {
+                        line: line.to_string(),
+                        number: number.to_string(),
+                        name: name.to_string(),
+                    });
+                    doc.phases.push(phase);
+                    open_tasks.clear();
+                    continue;
+                }
+            }
+        }
+
+        // Tasks only count inside a phase, as in the frontend
+        if let Some(phase) = doc.phases.last_mut() {
+            let indent = indent_width(line);
+            if let Some(mut task) = parse_task(line, span) {
+                while open_tasks.last().is_some_and(|&open| open >= indent) {
+                    open_tasks.pop();
+                }
+                if let Some(source) = &mut task.source {
+                    source.depth = open_tasks.len();
+                }
+
+                let mut siblings = &mut phase.tasks;
+                for _ in 0..open_tasks.len() {
+                    siblings = &mut siblings.last_mut().expect("open task").subtasks;
+                }
+                siblings.push(task);
+                open_tasks.push(indent);
+                continue;
+            }
+
+            if parse_checklist_line(line).is_some() {
+                doc.tasks_without_id.push(span);
+                // Items nested under a task without an ID can't be attached to it
+                while open_tasks.last().is_some_and(|&open| open >= indent) {
+                    open_tasks.pop();
+                }
+            } else if indent == 0 && !line.trim().is_empty() {
+                open_tasks.clear();
+            }
+        }
+
+        push_raw(&mut doc, line);
+    }
+
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE_PLAN: &str = include_str!("../../../src/parser/__tests__/fixtures/simple-plan.md");
+    const COMPLEX_PLAN: &str =
+        include_str!("../../../src/parser/__tests__/fixtures/complex-plan.md");
+
+    #[test]
+    fn test_annotations() {
+        assert_eq!(extract_task_id("Do something (id: task_1)"), Some("task_1"));
+        assert_eq!(extract_task_id("No id here"), None);
+        assert_eq!(extract_task_id("Bad (id: a b)"), None);
+
+        assert_eq!(
+            extract_dependencies("Task (depends: t1, t2) (id: t3)"),
+            vec!["t1", "t2"]
+        );
+        assert!(extract_dependencies("Task (id: t1)").is_empty());
+        assert_eq!(
+            extract_dependencies("Task (depends: t1, ../api/plan.md#t3) (id: t4)"),
+            vec!["t1", "../api/plan.md#t3"]
+        );
+
+        assert_eq!(clean_task_content("Task (depends: t1) (id: t2)"), "Task");
+
+        assert!(has_annotation_syntax("Ship (depends: t1)"));
+        assert!(has_annotation_syntax("Done (id: t7)"));
+        assert!(has_annotation_syntax("Urgent (p1)"));
+        assert!(!has_annotation_syntax("Fix login (again) in p2: 10:00"));
+    }
+
+    #[test]
+    fn test_meta_annotations() {
+        let text = "Build API (owner: @sam) (est: 1.5d) (due: 2026-11-01) (tags: api, db) (p1) (depends: t1) (id: t2)";
+        let meta = extract_meta(text);
+
+        assert_eq!(meta.owner.as_deref(), Some("sam"));
+        assert_eq!(meta.estimate.unwrap().hours(), 12.0);
+        assert_eq!(meta.due, NaiveDate::from_ymd_opt(2026, 11, 1));
+        assert_eq!(meta.tags, vec!["api", "db"]);
+        assert_eq!(meta.priority, Some(1));
+        assert_eq!(clean_task_content(text), "Build API");
+
+        // Malformed annotations stay part of the content
+        let text = "Call (php) about (est: soon) (due: tomorrow) (id: t3)";
+        assert!(extract_meta(text).is_empty());
+        assert_eq!(
+            clean_task_content(text),
+            "Call (php) about (est: soon) (due: tomorrow)"
+        );
+    }
+
+    #[test]
+    fn test_phase_heading() {
+        assert_eq!(parse_phase_heading("Phase 1 — Setup"), Some(("1", "Setup")));
+        assert_eq!(parse_phase_heading("Phase 2 - Build"), Some(("2", "Build")));
+        assert_eq!(parse_phase_heading("Phase 3 – Test"), Some(("3", "Test")));
+        assert_eq!(parse_phase_heading("Notes"), None);
+    }
+
+    #[test]
+    fn test_checklist_line() {
+        let line = parse_checklist_line("  * [X] Done (id: t1)").unwrap();
+        assert_eq!(line.prefix, "  * [");
+        assert_eq!(line.mark, 'X');
+        assert_eq!(line.text(), "Done (id: t1)");
+
+        assert!(parse_checklist_line("-[ ] no space").is_none());
+        assert!(parse_checklist_line("- [?] unknown").is_none());
+
+        let blocked = "- [!] Deploy (reason: waiting on vendor) (id: t2)";
+        let doc = parse(&format!("## Phase 1 — A\n{}\n- [~] Drop (id: t3)\n", blocked));
+        let task = doc.task("t2").unwrap();
+        assert_eq!(task.status, TaskStatus::Blocked);
+        assert_eq!(task.content, "Deploy");
+        assert_eq!(task.meta.reason.as_deref(), Some("waiting on vendor"));
+        assert_eq!(doc.task("t3").unwrap().status, TaskStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_parse_simple_plan() {
+        let doc = parse(SIMPLE_PLAN);
+
+        assert_eq!(doc.title, "Simple Test Plan");
+        assert_eq!(doc.phases.len(), 2);
+        assert_eq!(doc.phases[0].id, "phase_0");
+        assert_eq!(doc.phases[0].title(), "Phase 0 — Setup");
+        assert_eq!(doc.phases[0].tasks.len(), 2);
+        assert_eq!(doc.phases[1].tasks.len(), 2);
+
+        let task = doc.task("t1").unwrap();
+        assert_eq!(task.content, "Initialize project");
+        assert_eq!(task.status, TaskStatus::Pending);
+        assert_eq!(doc.task("t2").unwrap().status, TaskStatus::Completed);
+    }
+
+    #[test]
+    fn test_parse_complex_plan() {
+        let doc = parse(COMPLEX_PLAN);
+
+        assert_eq!(doc.title, "Complex Test Plan");
+        assert_eq!(doc.phases.len(), 3);
+        assert_eq!(doc.tasks().count(), 8);
+
+        let task = doc.task("t6").unwrap();
+        assert_eq!(task.content, "Add validation");
+        assert_eq!(task.dependencies, vec!["t4", "t5"]);
+        assert_eq!(doc.task_phase("t6").unwrap().id, "phase_1");
+
+        assert_eq!(doc.phases[0].status(), TaskStatus::Completed);
+        assert_eq!(doc.phases[1].status(), TaskStatus::Pending);
+        assert!(doc.validate().is_empty());
+    }
+
+    #[test]
+    fn test_parse_spans() {
+        let doc = parse(SIMPLE_PLAN);
+        let span = doc.task("t1").unwrap().span.unwrap();
+
+        assert_eq!(span.line, 4);
+        assert_eq!(
+            &SIMPLE_PLAN[span.start..span.end],
+            "- [ ] Initialize project (id: t1)"
+        );
+        assert_eq!(doc.title_span.unwrap().line, 1);
+        assert_eq!(doc.phases[1].span.unwrap().line, 7);
+    }
+
+    #[test]
+    fn test_parse_skips_non_tasks() {
+        let markdown = "# Plan: X\n\n- [ ] Orphan (id: t0)\n\n## Phase 1 — Work\n- [-] Doing (id: t1)\n- [ ] No id\n```\n- [ ] In code (id: t2)\n```\n";
+        let doc = parse(markdown);
+
+        assert_eq!(doc.tasks().count(), 1);
+        assert_eq!(doc.task("t1").unwrap().status, TaskStatus::InProgress);
+    }
+
+    #[test]
+    fn test_parse_subtasks() {
+        let markdown = "## Phase 1 — A\n- [ ] Parent (id: t1)\n  note\n  - [ ] Child (id: t2)\n    - [x] Grandchild (id: t3)\n  - [ ] Sibling (id: t4)\n- [ ] Top (id: t5)\n  - [ ] No id\n    - [ ] Orphan (id: t6)\n\nProse\n  - [ ] After prose (id: t7)\n";
+        let doc = parse(markdown);
+        let phase = &doc.phases[0];
+
+        let top: Vec<&str> = phase.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(top, vec!["t1", "t5", "t7"]);
+        let parent = doc.task("t1").unwrap();
+        assert_eq!(parent.subtasks.len(), 2);
+        assert_eq!(parent.subtasks[0].subtasks[0].id, "t3");
+        assert_eq!(doc.task("t5").unwrap().subtasks[0].id, "t6");
+
+        let ids: Vec<&str> = doc.tasks().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["t1", "t2", "t3", "t4", "t5", "t6", "t7"]);
+        assert!(doc.validate().is_empty());
+    }
+
+    #[test]
+    fn test_parse_front_matter() {
+        let markdown = "---\nagent: codex\n# Plan: Not a title\n---\n# Plan: Real\n\n## Phase 1 — A\n- [ ] One (id: t1)\n";
+        let doc = parse(markdown);
+
+        assert_eq!(doc.settings.agent.as_deref(), Some("codex"));
+        assert_eq!(doc.title, "Real");
+        assert_eq!(doc.title_span.unwrap().line, 5);
+        assert_eq!(super::super::serialize(&doc), markdown);
+
+        // A leading rule without a closing one is just markdown
+        let doc = parse("---\n# Plan: Open\n");
+        assert!(doc.settings.is_empty());
+        assert_eq!(doc.title, "Open");
+    }
+
+    #[test]
+    fn test_validate_reports_problems() {
+        let doc = parse("## Phase 1 — A\n- [ ] One (id: t1)\n- [ ] Two (depends: t9) (id: t1)\n");
+        let errors = doc.validate();
+
+        assert!(errors.contains(&"Duplicate task ID: t1".to_string()));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("unknown dependency \"t9\"")));
+    }
+}
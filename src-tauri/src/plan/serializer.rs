@@ -0,0 +1,251 @@
+//! plan.md serializer
+//!
+//! Writes a `PlanDoc` back to markdown. Elements that are unchanged since
+//! parsing are emitted exactly as they were read; changed or new elements are
+//! rendered in the canonical format used by the frontend serializer.
+
+use std::borrow::Cow;
+
+use super::{PlanDoc, PlanPhase, PlanTask, DEFAULT_TITLE};
+
+/// Canonical `# Plan: <title>` line
+fn render_title(title: &str) -> String {
+    format!("# Plan: {}", title)
+}
+
+/// Canonical phase heading
+fn render_heading(phase: &PlanPhase) -> String {
+    format!("## {}", phase.title())
+}
+
+/// Canonical task text after the checkbox, starting with `]`
+fn render_task_rest(task: &PlanTask) -> String {
+    let mut rest = format!("] {}", task.content);
+    let meta = &task.meta;
+    if let Some(owner) = &meta.owner {
+        rest.push_str(&format!(" (owner: @{})", owner));
+    }
+    if let Some(estimate) = &meta.estimate {
+        rest.push_str(&format!(" (est: {})", estimate));
+    }
+    if let Some(due) = &meta.due {
+        rest.push_str(&format!(" (due: {})", due.format("%Y-%m-%d")));
+    }
+    if !meta.tags.is_empty() {
+        rest.push_str(&format!(" (tags: {})", meta.tags.join(", ")));
+    }
+    if let Some(priority) = meta.priority {
+        rest.push_str(&format!(" (p{})", priority));
+    }
+    if let Some(reason) = &meta.reason {
+        rest.push_str(&format!(" (reason: {})", reason));
+    }
+    if !task.dependencies.is_empty() {
+        rest.push_str(&format!(" (depends: {})", task.dependencies.join(", ")));
+    }
+    rest.push_str(&format!(" (id: {})", task.id));
+    rest
+}
+
+/// Canonical indentation, bullet and opening bracket for a nesting level
+fn render_prefix(depth: usize) -> String {
+    format!("{}- [", "  ".repeat(depth))
+}
+
+/// Task line, reusing as much of the source line as is still accurate
+fn task_line(task: &PlanTask, depth: usize) -> String {
+    let Some(source) = &task.source else {
+        return format!(
+            "{}{}{}",
+            render_prefix(depth),
+            task.status.checkbox(),
+            render_task_rest(task)
+        );
+    };
+
+    let mark = if task.status == source.status {
+        source.mark
+    } else {
+        task.status.checkbox()
+    };
+    let text_unchanged = task.content == source.content
+        && task.dependencies == source.dependencies
+        && task.meta == source.meta
+        && task.id == source.id;
+    let rest = if text_unchanged {
+        Cow::Borrowed(source.rest.as_str())
+    } else {
+        Cow::Owned(render_task_rest(task))
+    };
+
+    let prefix = if depth == source.depth {
+        Cow::Borrowed(source.prefix.as_str())
+    } else {
+        Cow::Owned(render_prefix(depth))
+    };
+
+    format!("{}{}{}", prefix, mark, rest)
+}
+
+/// Lines of tasks, their notes and their subtasks
+fn push_tasks<'a>(lines: &mut Vec<Cow<'a, str>>, tasks: &'a [PlanTask], depth: usize) {
+    for task in tasks {
+        lines.push(Cow::Owned(task_line(task, depth)));
+        lines.extend(
+            task.trailing
+                .iter()
+                .map(|line| Cow::Borrowed(line.as_str())),
+        );
+        push_tasks(lines, &task.subtasks, depth + 1);
+    }
+}
+
+/// Phase heading line, reusing the source line when number and name are unchanged
+fn heading_line(phase: &PlanPhase) -> Cow<'_, str> {
+    match &phase.source {
+        Some(source) if source.number == phase.number && source.name == phase.name => {
+            Cow::Borrowed(source.line.as_str())
+        }
+        _ => Cow::Owned(render_heading(phase)),
+    }
+}
+
+/// Serialize a plan document to markdown
+/// Only the plan's own phases are written; see `PlanDoc::serialize_included`
+pub fn serialize(doc: &PlanDoc) -> String {
+    let mut lines: Vec<Cow<str>> = Vec::new();
+
+    match &doc.title_source {
+        Some(_) => {}
+        None if doc.title_span.is_none() && doc.title != DEFAULT_TITLE => {
+            // Title set on a document that had none
+            lines.push(Cow::Owned(render_title(&doc.title)));
+            lines.push(Cow::Borrowed(""));
+        }
+        None => {}
+    }
+
+    for (index, line) in doc.header.iter().enumerate() {
+        match &doc.title_source {
+            Some((title_index, title)) if *title_index == index && *title != doc.title => {
+                lines.push(Cow::Owned(render_title(&doc.title)));
+            }
+            _ => lines.push(Cow::Borrowed(line.as_str())),
+        }
+    }
+
+    // Phases from included files are written back to those files
+    for phase in doc.phases.iter().filter(|phase| phase.file.is_none()) {
+        // Separate new phases from the preceding content
+        let follows_text = lines.last().is_some_and(|line| !line.trim().is_empty());
+        if phase.source.is_none() && follows_text {
+            lines.push(Cow::Borrowed(""));
+        }
+
+        lines.push(heading_line(phase));
+        lines.extend(phase.intro.iter().map(|line| Cow::Borrowed(line.as_str())));
+
+        push_tasks(&mut lines, &phase.tasks, 0);
+    }
+
+    let line_ending = doc.line_ending.as_str();
+    let mut markdown = lines.join(line_ending);
+    if doc.trailing_newline && !lines.is_empty() {
+        markdown.push_str(line_ending);
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::{parse, PlanTask, TaskStatus};
+
+    const SIMPLE_PLAN: &str = include_str!("../../../src/parser/__tests__/fixtures/simple-plan.md");
+    const COMPLEX_PLAN: &str =
+        include_str!("../../../src/parser/__tests__/fixtures/complex-plan.md");
+
+    #[test]
+    fn test_round_trip_fixtures() {
+        for fixture in [SIMPLE_PLAN, COMPLEX_PLAN] {
+            assert_eq!(serialize(&parse(fixture)), fixture);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_irregular_formatting() {
+        let markdown = "Intro text\r\n# Plan:   Spaced  \r\n\r\n## Phase 1 -- Odd\r\n  * [X] Mixed  (id: t1)  \r\n    notes\r\n```\r\n- [ ] code\r\n```\r\n## Other heading\r\n- [ ] no id";
+        assert_eq!(serialize(&parse(markdown)), markdown);
+        assert_eq!(serialize(&parse("")), "");
+        assert_eq!(serialize(&parse("\n\n")), "\n\n");
+    }
+
+    #[test]
+    fn test_edits_touch_only_changed_lines() {
+        let mut doc = parse(COMPLEX_PLAN);
+        doc.task_mut("t4").unwrap().status = TaskStatus::InProgress;
+        doc.task_mut("t7").unwrap().content = "Unit tests for parser".to_string();
+
+        let expected = COMPLEX_PLAN
+            .replace("- [ ] Build parser", "- [-] Build parser")
+            .replace("Unit tests (depends", "Unit tests for parser (depends");
+        assert_eq!(serialize(&doc), expected);
+    }
+
+    #[test]
+    fn test_subtasks_round_trip_and_reindent() {
+        let markdown = "## Phase 1 — Work\n- [ ] Parent (id: t1)\n    notes\n    * [ ] Child (id: t2)\n\t- [x] Tabbed (id: t3)\n- [ ] Next (id: t4)\n";
+        let mut doc = parse(markdown);
+        assert_eq!(serialize(&doc), markdown);
+
+        // Promoting a subtask rewrites its indentation; new subtasks use two spaces per level
+        let child = doc.phases[0].tasks[0].subtasks.remove(0);
+        doc.phases[0].tasks.push(child);
+        doc.task_mut("t4").unwrap().subtasks.push(PlanTask::new("t5", "New"));
+        assert_eq!(
+            serialize(&doc),
+            "## Phase 1 — Work\n- [ ] Parent (id: t1)\n    notes\n\t- [x] Tabbed (id: t3)\n- [ ] Next (id: t4)\n  - [ ] New (id: t5)\n- [ ] Child (id: t2)\n"
+        );
+    }
+
+    #[test]
+    fn test_meta_round_trip() {
+        let markdown = "## Phase 1 — Work\n- [ ] API (p2) (tags: api,db) (owner: sam) (id: t1)\n";
+        let mut doc = parse(markdown);
+        assert_eq!(serialize(&doc), markdown);
+
+        let meta = &mut doc.task_mut("t1").unwrap().meta;
+        meta.estimate = crate::plan::Estimate::parse("3h");
+        meta.due = chrono::NaiveDate::from_ymd_opt(2026, 11, 1);
+        assert_eq!(
+            serialize(&doc),
+            "## Phase 1 — Work\n- [ ] API (owner: @sam) (est: 3h) (due: 2026-11-01) (tags: api, db) (p2) (id: t1)\n"
+        );
+        assert_eq!(parse(&serialize(&doc)).task("t1").unwrap().meta, doc.task("t1").unwrap().meta);
+    }
+
+    #[test]
+    fn test_serialize_new_elements() {
+        let mut doc = parse(SIMPLE_PLAN);
+        doc.title = "Renamed".to_string();
+        doc.phases[1].name = "Build".to_string();
+
+        let mut phase = crate::plan::PlanPhase::new("2", "Release");
+        let mut task = PlanTask::new("t5", "Ship it");
+        task.dependencies = vec!["t3".to_string(), "t4".to_string()];
+        phase.tasks.push(task);
+        doc.phases.push(phase);
+
+        let markdown = serialize(&doc);
+        assert!(markdown.starts_with("# Plan: Renamed\n"));
+        assert!(markdown.contains("## Phase 1 — Build\n"));
+        assert!(markdown.ends_with(
+            "- [ ] Write tests (id: t4)\n\n## Phase 2 — Release\n- [ ] Ship it (depends: t3, t4) (id: t5)\n"
+        ));
+
+        // The canonical output parses back to the same structure
+        let reparsed = parse(&markdown);
+        assert_eq!(reparsed.task("t5").unwrap().dependencies, vec!["t3", "t4"]);
+        assert_eq!(serialize(&reparsed), markdown);
+    }
+}
@@ -0,0 +1,321 @@
+//! Progress statistics
+//!
+//! Completion metrics for a plan and each of its phases. Counting works on the
+//! parsed plan alone, so it is cheap enough to redo on every `file-changed`
+//! event; recent completions additionally read plan history and are only
+//! computed when asked for.
+
+use serde::{Deserialize, Serialize};
+
+use super::graph::{is_finished, task_weight};
+use super::{read_plan_file, PlanDoc, PlanTask, TaskStatus};
+use crate::history::replay::{build_timeline, load_snapshots_with_current, TransitionKind};
+use crate::history::PlanSnapshot;
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Number of tasks in each status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusCounts {
+    pub pending: usize,
+    pub in_progress: usize,
+    pub completed: usize,
+    pub blocked: usize,
+    pub cancelled: usize,
+    pub total: usize,
+}
+
+impl StatusCounts {
+    fn add(&mut self, task: &PlanTask) {
+        match task.status {
+            TaskStatus::Pending => self.pending += 1,
+            TaskStatus::InProgress => self.in_progress += 1,
+            TaskStatus::Completed => self.completed += 1,
+            TaskStatus::Blocked => self.blocked += 1,
+            TaskStatus::Cancelled => self.cancelled += 1,
+        }
+        self.total += 1;
+    }
+
+    /// Tasks that still count towards completion (all but cancelled ones)
+    pub fn planned(&self) -> usize {
+        self.total - self.cancelled
+    }
+
+    /// Percentage of planned tasks completed, 0 when there are none
+    pub fn percent_complete(&self) -> f64 {
+        if self.planned() == 0 {
+            0.0
+        } else {
+            self.completed as f64 * 100.0 / self.planned() as f64
+        }
+    }
+}
+
+/// Progress of one phase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseStats {
+    pub phase_id: String,
+    pub name: String,
+    pub counts: StatusCounts,
+    pub percent_complete: f64,
+    /// Estimated hours of unfinished tasks, counting one per task without an estimate
+    pub remaining_work: f64,
+}
+
+/// Tasks completed within a recent window, from plan history
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentCompletions {
+    pub days: u32,
+    /// Tasks marked completed in the window that are still completed, in document order
+    pub task_ids: Vec<String>,
+}
+
+/// Progress of a whole plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanStats {
+    pub counts: StatusCounts,
+    pub percent_complete: f64,
+    pub remaining_work: f64,
+    pub phases: Vec<PhaseStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recent: Option<RecentCompletions>,
+}
+
+fn remaining_work<'a>(tasks: impl Iterator<Item = &'a PlanTask>) -> f64 {
+    tasks.filter(|t| !is_finished(t)).map(task_weight).sum()
+}
+
+fn phases_remaining(phases: &[PhaseStats]) -> f64 {
+    phases.iter().map(|phase| phase.remaining_work).sum()
+}
+
+impl PlanDoc {
+    /// Completion metrics for the plan and each phase
+    /// Only tasks without subtasks are counted, so work isn't counted twice
+    pub fn stats(&self) -> PlanStats {
+        let mut counts = StatusCounts::default();
+        let phases: Vec<PhaseStats> = self
+            .phases
+            .iter()
+            .map(|phase| {
+                let leaves = || phase.tasks.iter().flat_map(PlanTask::leaves);
+                let mut phase_counts = StatusCounts::default();
+                for task in leaves() {
+                    phase_counts.add(task);
+                    counts.add(task);
+                }
+                PhaseStats {
+                    phase_id: phase.id.clone(),
+                    name: phase.name.clone(),
+                    counts: phase_counts,
+                    percent_complete: phase_counts.percent_complete(),
+                    remaining_work: remaining_work(leaves()),
+                }
+            })
+            .collect();
+
+        PlanStats {
+            counts,
+            percent_complete: counts.percent_complete(),
+            remaining_work: phases_remaining(&phases),
+            phases,
+            recent: None,
+        }
+    }
+
+    /// Tasks that history shows being completed at or after `since` (ms) and that are
+    /// still completed
+    pub fn recent_completions(&self, snapshots: &[PlanSnapshot], since: u64) -> Vec<String> {
+        let timeline = build_timeline(snapshots, Some(since));
+        let completed: Vec<&str> = timeline
+            .transitions
+            .iter()
+            .filter(|t| t.kind == TransitionKind::StatusChanged)
+            .filter(|t| t.to.as_deref() == Some(TaskStatus::Completed.as_str()))
+            .map(|t| t.task_id.as_str())
+            .collect();
+
+        self.tasks()
+            .filter(|task| task.status == TaskStatus::Completed)
+            .filter(|task| completed.contains(&task.id.as_str()))
+            .map(|task| task.id.clone())
+            .collect()
+    }
+}
+
+/// Compute progress statistics for a plan file
+/// With `recent_days`, also count tasks completed in that many days using plan history
+pub fn plan_stats(plan_path: &str, recent_days: Option<u32>) -> Result<PlanStats, String> {
+    let doc = read_plan_file(plan_path)?;
+    let mut stats = doc.stats();
+
+    if let Some(days) = recent_days {
+        let snapshots = load_snapshots_with_current(plan_path)?;
+        let now = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        let since = now.saturating_sub(u64::from(days) * DAY_MS);
+        stats.recent = Some(RecentCompletions {
+            days,
+            task_ids: doc.recent_completions(&snapshots, since),
+        });
+    }
+
+    Ok(stats)
+}
+
+/// Plain-text summary of statistics, one line per phase
+pub fn format_stats(stats: &PlanStats) -> String {
+    let line = |name: &str, counts: &StatusCounts, percent: f64, remaining: f64| {
+        let mut line = format!(
+            "{}: {}/{} completed ({:.0}%), {} in progress, {} pending",
+            name,
+            counts.completed,
+            counts.planned(),
+            percent,
+            counts.in_progress,
+            counts.pending
+        );
+        if counts.blocked > 0 {
+            line.push_str(&format!(", {} blocked", counts.blocked));
+        }
+        if counts.cancelled > 0 {
+            line.push_str(&format!(", {} cancelled", counts.cancelled));
+        }
+        line.push_str(&format!(", {} remaining\n", remaining));
+        line
+    };
+
+    let mut text = line(
+        "Plan",
+        &stats.counts,
+        stats.percent_complete,
+        stats.remaining_work,
+    );
+    for phase in &stats.phases {
+        text.push_str("  ");
+        text.push_str(&line(
+            &phase.name,
+            &phase.counts,
+            phase.percent_complete,
+            phase.remaining_work,
+        ));
+    }
+    if let Some(recent) = &stats.recent {
+        text.push_str(&format!(
+            "Completed in the last {} days: {}\n",
+            recent.days,
+            recent.task_ids.len()
+        ));
+    }
+    text
+}
+
+/// Get progress statistics for a plan
+#[tauri::command]
+pub fn get_plan_stats(plan_path: String, recent_days: Option<u32>) -> Result<PlanStats, String> {
+    plan_stats(&plan_path, recent_days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse;
+    use super::*;
+
+    const COMPLEX_PLAN: &str =
+        include_str!("../../../src/parser/__tests__/fixtures/complex-plan.md");
+
+    fn snapshot(timestamp: u64, tasks: &str) -> PlanSnapshot {
+        PlanSnapshot {
+            timestamp,
+            content: format!("# Plan: Test\n\n## Phase 0 — Work\n{}", tasks),
+        }
+    }
+
+    #[test]
+    fn test_plan_stats() {
+        let stats = parse(COMPLEX_PLAN).stats();
+
+        assert_eq!(stats.counts.total, 8);
+        assert_eq!(stats.counts.completed, 3);
+        assert_eq!(stats.counts.pending, 5);
+        assert_eq!(stats.percent_complete, 37.5);
+        assert_eq!(stats.remaining_work, 5.0);
+
+        assert_eq!(stats.phases.len(), 3);
+        assert_eq!(stats.phases[0].percent_complete, 100.0);
+        assert_eq!(stats.phases[0].remaining_work, 0.0);
+        assert_eq!(stats.phases[2].counts.total, 2);
+    }
+
+    #[test]
+    fn test_cancelled_tasks_leave_the_total() {
+        let markdown =
+            "## Phase 1 — A\n- [x] One (id: t1)\n- [~] Two (id: t2)\n- [!] Three (id: t3)\n";
+        let stats = parse(markdown).stats();
+
+        assert_eq!(stats.counts.cancelled, 1);
+        assert_eq!(stats.counts.blocked, 1);
+        assert_eq!(stats.percent_complete, 50.0);
+        assert_eq!(stats.remaining_work, 1.0);
+        assert_eq!(
+            format_stats(&stats).lines().next(),
+            Some("Plan: 1/2 completed (50%), 0 in progress, 0 pending, 1 blocked, 1 cancelled, 1 remaining")
+        );
+    }
+
+    #[test]
+    fn test_subtasks_count_once() {
+        let markdown = "## Phase 1 — A\n- [ ] Parent (id: t1)\n  - [x] Done (id: t2)\n  - [ ] Open (id: t3)\n- [ ] Other (id: t4)\n";
+        let stats = parse(markdown).stats();
+
+        assert_eq!(stats.counts.total, 3);
+        assert_eq!(stats.counts.completed, 1);
+        assert_eq!(stats.remaining_work, 2.0);
+    }
+
+    #[test]
+    fn test_empty_plan_stats() {
+        let stats = parse("# Plan: Empty\n").stats();
+        assert_eq!(stats.counts, StatusCounts::default());
+        assert_eq!(stats.percent_complete, 0.0);
+    }
+
+    #[test]
+    fn test_recent_completions() {
+        let snapshots = vec![
+            snapshot(
+                100,
+                "- [ ] A (id: t1)\n- [ ] B (id: t2)\n- [ ] C (id: t3)\n",
+            ),
+            snapshot(
+                200,
+                "- [x] A (id: t1)\n- [ ] B (id: t2)\n- [ ] C (id: t3)\n",
+            ),
+            snapshot(
+                300,
+                "- [x] A (id: t1)\n- [x] B (id: t2)\n- [x] C (id: t3)\n- [x] D (id: t4)\n",
+            ),
+            snapshot(
+                400,
+                "- [x] A (id: t1)\n- [x] B (id: t2)\n- [ ] C (id: t3)\n- [~] D (id: t4)\n",
+            ),
+        ];
+        let doc = parse(&snapshots[3].content);
+
+        // Reopened (t3) and later cancelled (t4) tasks aren't completed any more
+        assert_eq!(doc.recent_completions(&snapshots, 250), vec!["t2"]);
+        assert_eq!(doc.recent_completions(&snapshots, 0), vec!["t1", "t2"]);
+    }
+
+    #[test]
+    fn test_stats_serialization() {
+        let json = serde_json::to_string(&parse(COMPLEX_PLAN).stats()).unwrap();
+        assert!(json.contains("percentComplete"));
+        assert!(json.contains("inProgress"));
+        assert!(!json.contains("recent"));
+    }
+}
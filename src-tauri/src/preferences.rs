@@ -4,11 +4,22 @@
 //! - Last opened plan file
 //! - Last-used agent per plan
 //! - Recent plans list
+//! - History storage location
+//! - File watcher backend
+//!
+//! A plan's front matter supplies per-plan defaults (agent, working directory)
+//! that apply until the user picks something else.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::history::HistoryStorage;
+use crate::plan::{self, PlanSettings};
+use crate::watcher::polling::{DEFAULT_POLL_INTERVAL_MS, MIN_POLL_INTERVAL_MS};
+use crate::watcher::WatcherBackend;
 
 /// Maximum number of recent plans to keep
 const MAX_RECENT_PLANS: usize = 10;
@@ -28,6 +39,16 @@ pub struct PlanPreferences {
     pub last_opened: Option<i64>,
 }
 
+/// Agent and working directory to use when opening a plan
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDefaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+}
+
 /// Global user preferences
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -44,6 +65,15 @@ pub struct UserPreferences {
     /// Default agent to use
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_agent: Option<String>,
+    /// Where plan history is stored
+    #[serde(default)]
+    pub history_storage: HistoryStorage,
+    /// How plan files are watched for changes
+    #[serde(default)]
+    pub watcher_backend: WatcherBackend,
+    /// Interval for the polling watcher backend (ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poll_interval_ms: Option<u64>,
 }
 
 /// Get the preferences file path
@@ -85,6 +115,46 @@ fn write_preferences(prefs: &UserPreferences) -> Result<(), String> {
     Ok(())
 }
 
+/// Get the configured history storage location
+pub fn get_history_storage() -> HistoryStorage {
+    read_preferences().history_storage
+}
+
+/// Get the configured watcher backend and polling interval
+pub fn get_watcher_settings() -> (WatcherBackend, Duration) {
+    let prefs = read_preferences();
+    let interval = prefs
+        .poll_interval_ms
+        .unwrap_or(DEFAULT_POLL_INTERVAL_MS)
+        .max(MIN_POLL_INTERVAL_MS);
+    (prefs.watcher_backend, Duration::from_millis(interval))
+}
+
+/// Front matter settings of a plan; empty when it can't be read
+fn plan_settings(plan_path: &str) -> PlanSettings {
+    plan::read_plan_file(plan_path)
+        .map(|doc| doc.settings)
+        .unwrap_or_default()
+}
+
+/// Defaults for a plan: the last-used agent, then the front matter agent, then the
+/// default agent; the front matter working directory, resolved against the plan
+fn plan_defaults(prefs: &UserPreferences, plan_path: &str, settings: PlanSettings) -> PlanDefaults {
+    let last_agent = prefs
+        .plan_preferences
+        .get(plan_path)
+        .and_then(|p| p.last_agent.clone());
+
+    PlanDefaults {
+        cwd: settings
+            .resolve_cwd(Path::new(plan_path))
+            .map(|p| p.to_string_lossy().to_string()),
+        agent: last_agent
+            .or(settings.agent)
+            .or_else(|| prefs.default_agent.clone()),
+    }
+}
+
 /// Get all user preferences
 #[tauri::command]
 pub fn get_preferences() -> UserPreferences {
@@ -121,7 +191,7 @@ pub fn set_plan_agent(plan_path: String, agent: String) -> Result<(), String> {
     write_preferences(&prefs)
 }
 
-/// Get the last-used agent for a plan
+/// Get the last-used agent for a plan, or the agent from its front matter
 #[tauri::command]
 pub fn get_plan_agent(plan_path: String) -> Option<String> {
     let prefs = read_preferences();
@@ -129,6 +199,13 @@ pub fn get_plan_agent(plan_path: String) -> Option<String> {
         .plan_preferences
         .get(&plan_path)
         .and_then(|p| p.last_agent.clone())
+        .or_else(|| plan_settings(&plan_path).agent)
+}
+
+/// Get the agent and working directory to use when opening a plan
+#[tauri::command]
+pub fn get_plan_defaults(plan_path: String) -> PlanDefaults {
+    plan_defaults(&read_preferences(), &plan_path, plan_settings(&plan_path))
 }
 
 /// Set the default agent
@@ -139,6 +216,28 @@ pub fn set_default_agent(agent: String) -> Result<(), String> {
     write_preferences(&prefs)
 }
 
+/// Set where plan history is stored
+/// Existing in-repository history can be moved with `migrate_history`
+#[tauri::command]
+pub fn set_history_storage(storage: HistoryStorage) -> Result<(), String> {
+    let mut prefs = read_preferences();
+    prefs.history_storage = storage;
+    write_preferences(&prefs)
+}
+
+/// Set how plan files are watched
+/// Applies to watches started afterwards
+#[tauri::command]
+pub fn set_watcher_backend(
+    backend: WatcherBackend,
+    poll_interval_ms: Option<u64>,
+) -> Result<(), String> {
+    let mut prefs = read_preferences();
+    prefs.watcher_backend = backend;
+    prefs.poll_interval_ms = poll_interval_ms;
+    write_preferences(&prefs)
+}
+
 /// Get recent plans list
 #[tauri::command]
 pub fn get_recent_plans() -> Vec<String> {
@@ -191,4 +290,58 @@ mod tests {
         assert!(json.contains("lastAgent"));
         assert!(json.contains("lastOpened"));
     }
+
+    #[test]
+    fn test_plan_defaults() {
+        let settings = PlanSettings {
+            agent: Some("codex".to_string()),
+            cwd: Some("app".to_string()),
+            ..Default::default()
+        };
+        let mut prefs = UserPreferences {
+            default_agent: Some("opencode".to_string()),
+            ..Default::default()
+        };
+
+        let defaults = plan_defaults(&prefs, "/work/plan.md", settings.clone());
+        assert_eq!(defaults.agent.as_deref(), Some("codex"));
+        assert_eq!(defaults.cwd.as_deref(), Some("/work/app"));
+
+        // The user's last choice for the plan wins over front matter
+        prefs.plan_preferences.insert(
+            "/work/plan.md".to_string(),
+            PlanPreferences {
+                last_agent: Some("claude_code".to_string()),
+                last_opened: None,
+            },
+        );
+        let defaults = plan_defaults(&prefs, "/work/plan.md", settings);
+        assert_eq!(defaults.agent.as_deref(), Some("claude_code"));
+
+        let defaults = plan_defaults(&prefs, "/other/plan.md", PlanSettings::default());
+        assert_eq!(defaults.agent.as_deref(), Some("opencode"));
+        assert!(defaults.cwd.is_none());
+    }
+
+    #[test]
+    fn test_history_storage_defaults_to_app_data() {
+        let prefs: UserPreferences = serde_json::from_str("{}").unwrap();
+        assert_eq!(prefs.history_storage, HistoryStorage::AppData);
+
+        let prefs: UserPreferences =
+            serde_json::from_str(r#"{"historyStorage":"repository"}"#).unwrap();
+        assert_eq!(prefs.history_storage, HistoryStorage::Repository);
+    }
+
+    #[test]
+    fn test_watcher_backend_serialization() {
+        let prefs: UserPreferences = serde_json::from_str("{}").unwrap();
+        assert_eq!(prefs.watcher_backend, WatcherBackend::Auto);
+        assert!(prefs.poll_interval_ms.is_none());
+
+        let prefs: UserPreferences =
+            serde_json::from_str(r#"{"watcherBackend":"polling","pollIntervalMs":2000}"#).unwrap();
+        assert_eq!(prefs.watcher_backend, WatcherBackend::Polling);
+        assert_eq!(prefs.poll_interval_ms, Some(2000));
+    }
 }
@@ -1,33 +1,353 @@
 //! File watcher module for monitoring plan.md and layout.json changes
 //!
 //! Uses notify crate with debouncing to emit Tauri events when files change.
+//! Several plans can be watched at once, each shared by the windows using it.
+//!
+//! Plans named by a watched plan's cross-plan dependencies are polled as
+//! well, since they may live anywhere; a status change of a referenced task
+//! is reported as a `file-changed` event of type "reference".
+//!
+//! Files spliced into the plan with `<!-- include: ... -->` are polled too,
+//! and their changes reported as events of type "include".
 
 use notify::RecommendedWatcher;
 use notify_debouncer_mini::{new_debouncer, DebouncedEventKind, Debouncer};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Window};
+
+use crate::plan;
+use crate::{history, outline, preferences};
+
+pub mod changes;
+pub mod polling;
+use changes::{FileChanges, PlanChanges, StatusChange};
+use polling::Poller;
 
 /// Debounce duration for file change events (ms)
 const DEBOUNCE_MS: u64 = 500;
 
+/// Window label used when a command isn't tied to a specific window
+const DEFAULT_OWNER: &str = "main";
+
+/// Hash of the last content the app itself wrote, keyed by resolved file path
+/// Watcher events whose on-disk content still matches are our own writes coming back
+static SELF_WRITES: LazyLock<Mutex<HashMap<PathBuf, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 /// Event payload for file change notifications
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FileChangeEvent {
     /// The path of the changed file
     pub path: String,
-    /// The type of file: "plan" or "layout"
+    /// The type of file: "plan", "layout", "include" (a file spliced into the plan)
+    /// or "reference" (a plan this one depends on)
     pub file_type: String,
+    /// The watched plan this change belongs to
+    pub plan_path: String,
+    /// Content hash before the change, if the previous content was known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_hash: Option<String>,
+    /// Content hash after the change; equal to old_hash for no-op events
+    #[serde(default)]
+    pub new_hash: String,
+    /// What changed, when the previous content was known and parseable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changes: Option<FileChanges>,
+}
+
+/// What happened to a watched plan file itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanLifecycleKind {
+    Deleted,
+    /// Renamed within its directory; `new_path` holds the new location
+    Moved,
+    /// Reappeared after being deleted or moved away
+    Recreated,
+}
+
+/// Event payload emitted as `plan-lifecycle` when a watched plan disappears or reappears
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanLifecycleEvent {
+    pub plan_path: String,
+    pub kind: PlanLifecycleKind,
+    /// New location of a moved plan, when it could be detected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_path: Option<String>,
+}
+
+/// Events produced by a plan watch
+#[derive(Debug, Clone)]
+enum WatchEvent {
+    Changed(FileChangeEvent),
+    Lifecycle(PlanLifecycleEvent),
+}
+
+/// Whether a watched plan file currently exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchStatus {
+    #[default]
+    Watching,
+    /// The plan was deleted or moved; the watch stays armed for its return
+    Missing,
+}
+
+/// How plan files are watched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatcherBackend {
+    /// Native watching, polling instead on network, FUSE and VM-shared filesystems
+    /// (detected on Linux only) or when native watching fails
+    #[default]
+    Auto,
+    /// OS file events (inotify, FSEvents, ReadDirectoryChangesW)
+    Native,
+    /// Periodic mtime/size checks, for NFS, SSHFS and container mounts
+    Polling,
+}
+
+/// The backend driving a running watch; dropping it stops watching
+enum ActiveBackend {
+    Native(Debouncer<RecommendedWatcher>),
+    Polling(Poller),
+}
+
+/// A running watch on one plan's directory
+struct FileWatch {
+    backend: ActiveBackend,
+    status: Arc<Mutex<WatchStatus>>,
+}
+
+impl FileWatch {
+    fn status(&self) -> WatchStatus {
+        self.status.lock().map(|s| *s).unwrap_or_default()
+    }
+
+    /// The backend actually in use (never `Auto`)
+    fn backend(&self) -> WatcherBackend {
+        match self.backend {
+            ActiveBackend::Native(_) => WatcherBackend::Native,
+            ActiveBackend::Polling(_) => WatcherBackend::Polling,
+        }
+    }
+}
+
+/// A watched plan and the windows interested in it
+struct PlanWatch {
+    watch: FileWatch,
+    windows: BTreeSet<String>,
+}
+
+/// Summary of a watched plan returned by list_watched_plans
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchInfo {
+    pub plan_path: String,
+    /// Labels of the windows watching this plan
+    pub windows: Vec<String>,
+    pub status: WatchStatus,
+    pub backend: WatcherBackend,
 }
 
 /// Global state for the file watcher
+/// Holds one watch per plan path, shared by every window that asked for it
 #[derive(Default)]
 pub struct WatcherState {
-    debouncer: Option<Debouncer<RecommendedWatcher>>,
-    watched_plan: Option<String>,
+    watches: HashMap<String, PlanWatch>,
+}
+
+impl WatcherState {
+    /// Plans watched on behalf of a window
+    fn plans_for_window(&self, window: &str) -> Vec<String> {
+        self.watches
+            .iter()
+            .filter(|(_, watch)| watch.windows.contains(window))
+            .map(|(plan_path, _)| plan_path.clone())
+            .collect()
+    }
+
+    /// Add a window to a plan's watch, creating the watch if needed
+    fn attach(
+        &mut self,
+        plan_path: &str,
+        window: &str,
+        create: impl FnOnce() -> Result<FileWatch, String>,
+    ) -> Result<(), String> {
+        if let Some(watch) = self.watches.get_mut(plan_path) {
+            watch.windows.insert(window.to_string());
+            return Ok(());
+        }
+
+        let watch = PlanWatch {
+            watch: create()?,
+            windows: BTreeSet::from([window.to_string()]),
+        };
+        self.watches.insert(plan_path.to_string(), watch);
+        Ok(())
+    }
+
+    /// Remove a window from a plan's watch, dropping the watch once unused
+    fn detach(&mut self, plan_path: &str, window: &str) {
+        if let Some(watch) = self.watches.get_mut(plan_path) {
+            watch.windows.remove(window);
+            if watch.windows.is_empty() {
+                self.watches.remove(plan_path);
+            }
+        }
+    }
+
+    /// Summaries of all watched plans, sorted by path
+    fn list(&self) -> Vec<WatchInfo> {
+        let mut watches: Vec<WatchInfo> = self
+            .watches
+            .iter()
+            .map(|(plan_path, watch)| WatchInfo {
+                plan_path: plan_path.clone(),
+                windows: watch.windows.iter().cloned().collect(),
+                status: watch.watch.status(),
+                backend: watch.watch.backend(),
+            })
+            .collect();
+        watches.sort_by(|a, b| a.plan_path.cmp(&b.plan_path));
+        watches
+    }
+}
+
+/// Hex SHA-256 of file content
+pub fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Resolve a file path through its canonical parent directory
+/// Works for files that don't exist yet, and matches the paths watcher events report
+fn resolve_file_path(path: &Path) -> Result<PathBuf, String> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path: {:?}", path))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let dir = parent
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve directory of {:?}: {}", path, e))?;
+    Ok(dir.join(file_name))
+}
+
+/// Remember content the app is about to write so the resulting event can be dropped
+pub fn record_self_write(path: &Path, content: &[u8]) {
+    let Ok(resolved) = resolve_file_path(path) else {
+        return;
+    };
+    if let Ok(mut writes) = SELF_WRITES.lock() {
+        writes.insert(resolved, content_hash(content));
+    }
+}
+
+/// Whether content with this hash is what the app last wrote to a file
+/// The recorded write is forgotten on the first check, so later external
+/// writes of the same content are still reported
+fn is_self_write(path: &Path, hash: &str) -> bool {
+    SELF_WRITES
+        .lock()
+        .is_ok_and(|mut writes| writes.remove(path).is_some_and(|expected| expected == hash))
+}
+
+/// Last seen content of a watched file
+struct KnownContent {
+    hash: String,
+    content: String,
+}
+
+impl KnownContent {
+    fn read(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        Some(Self {
+            hash: content_hash(content.as_bytes()),
+            content,
+        })
+    }
+}
+
+/// Find a Markdown file next to the plan with the given content hash
+/// Used to tell a rename within the directory apart from a deletion
+fn find_moved_plan(dir: &Path, exclude: &Path, previous: &KnownContent) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path != exclude && path.extension().is_some_and(|ext| ext == "md"))
+        .filter(|path| {
+            fs::metadata(path)
+                .is_ok_and(|m| m.is_file() && m.len() == previous.content.len() as u64)
+        })
+        .find(|path| KnownContent::read(path).is_some_and(|c| c.hash == previous.hash))
+}
+
+/// Files belonging to a watched plan
+/// Paths are resolved against the canonical parent directory so they match watcher events
+struct WatchTargets {
+    dir: PathBuf,
+    plan: PathBuf,
+    layout: PathBuf,
+    /// Files the plan includes, directly or through other included files
+    includes: BTreeSet<PathBuf>,
+}
+
+impl WatchTargets {
+    fn new(plan_path: &str) -> Result<Self, String> {
+        let plan = resolve_file_path(Path::new(plan_path))?;
+        let dir = plan.parent().unwrap_or(Path::new("/")).to_path_buf();
+
+        Ok(Self {
+            layout: PathBuf::from(get_layout_path(&plan.to_string_lossy())),
+            plan,
+            dir,
+            includes: BTreeSet::new(),
+        })
+    }
+
+    /// Classify an event path as "plan", "layout" or "include"; other files are ignored
+    fn classify(&self, path: &Path) -> Option<&'static str> {
+        if path == self.plan {
+            Some("plan")
+        } else if path == self.layout {
+            Some("layout")
+        } else if self.includes.contains(path) {
+            Some("include")
+        } else {
+            None
+        }
+    }
+}
+
+/// Tasks a plan depends on in other plans, by resolved plan path
+/// References to the plan itself or into directories that don't exist are left out
+fn referenced_tasks(plan_path: &Path, markdown: &str) -> HashMap<PathBuf, BTreeSet<String>> {
+    let doc = plan::parse(markdown);
+    let mut references: HashMap<PathBuf, BTreeSet<String>> = HashMap::new();
+    for reference in doc.cross_plan_refs() {
+        let resolved = resolve_file_path(&reference.resolve(plan_path)).ok();
+        if let Some(path) = resolved.filter(|path| path != plan_path) {
+            references
+                .entry(path)
+                .or_default()
+                .insert(reference.task_id.to_string());
+        }
+    }
+    references
 }
 
 /// Get the layout file path for a given plan path
@@ -35,111 +355,509 @@ fn get_layout_path(plan_path: &str) -> String {
     format!("{}.layout.json", plan_path)
 }
 
-/// Start watching a plan file and its associated layout file
-#[tauri::command]
-pub fn start_watching(
-    app: AppHandle,
-    plan_path: String,
-    state: tauri::State<'_, Mutex<WatcherState>>,
-) -> Result<(), String> {
-    let mut watcher_state = state.lock().map_err(|e| e.to_string())?;
+/// Turns "this file may have changed" notifications into watch events
+/// Shared by the native and polling backends so both report the same events
+struct PlanEventHandler {
+    watched_plan: String,
+    targets: WatchTargets,
+    /// Content as of the last event, so the next change can be summarized
+    known: HashMap<PathBuf, KnownContent>,
+    status: Arc<Mutex<WatchStatus>>,
+    on_event: Box<dyn Fn(WatchEvent) + Send>,
+    /// Last seen status of each task the plan depends on in other plans,
+    /// by resolved plan path; None while the task hasn't been found
+    references: HashMap<PathBuf, BTreeMap<String, Option<String>>>,
+    /// Polls the referenced plans; replaced whenever the set of plans changes
+    reference_poller: Option<Poller>,
+    /// Polls the included files; replaced whenever the set of files changes
+    include_poller: Option<Poller>,
+    poll_interval: Duration,
+    /// The shared handle this handler lives in, for the reference poller to call back into
+    this: Weak<Mutex<PlanEventHandler>>,
+}
 
-    // Stop existing watcher if any
-    if watcher_state.debouncer.is_some() {
-        watcher_state.debouncer = None;
-        watcher_state.watched_plan = None;
+impl PlanEventHandler {
+    /// Create a handler and start polling the plans it references and the files it includes
+    fn spawn(
+        plan_path: &str,
+        poll_interval: Duration,
+        on_event: impl Fn(WatchEvent) + Send + 'static,
+    ) -> Result<Arc<Mutex<Self>>, String> {
+        let mut handler = Self::new(plan_path, poll_interval, on_event)?;
+        let handler = Arc::new_cyclic(|this| {
+            handler.this = this.clone();
+            Mutex::new(handler)
+        });
+        {
+            let mut handler = handler.lock().map_err(|e| e.to_string())?;
+            handler.refresh_references();
+            handler.refresh_includes();
+        }
+        Ok(handler)
     }
 
-    let plan_path_clone = plan_path.clone();
-    let layout_path = get_layout_path(&plan_path);
+    fn new(
+        plan_path: &str,
+        poll_interval: Duration,
+        on_event: impl Fn(WatchEvent) + Send + 'static,
+    ) -> Result<Self, String> {
+        let targets = WatchTargets::new(plan_path)?;
+        let known: HashMap<PathBuf, KnownContent> = [&targets.plan, &targets.layout]
+            .into_iter()
+            .filter_map(|path| Some((path.clone(), KnownContent::read(path)?)))
+            .collect();
+
+        let initial_status = if known.contains_key(&targets.plan) {
+            WatchStatus::Watching
+        } else {
+            WatchStatus::Missing
+        };
+
+        Ok(Self {
+            watched_plan: plan_path.to_string(),
+            targets,
+            known,
+            status: Arc::new(Mutex::new(initial_status)),
+            on_event: Box::new(on_event),
+            references: HashMap::new(),
+            reference_poller: None,
+            include_poller: None,
+            poll_interval,
+            this: Weak::new(),
+        })
+    }
+
+    /// Re-read the plan's cross-plan dependencies, polling any newly referenced plans
+    fn refresh_references(&mut self) {
+        let wanted = self
+            .known
+            .get(&self.targets.plan)
+            .map(|plan| referenced_tasks(&self.targets.plan, &plan.content))
+            .unwrap_or_default();
+
+        let same_plans = wanted.len() == self.references.len()
+            && wanted.keys().all(|path| self.references.contains_key(path));
+
+        let mut references = HashMap::new();
+        for (path, task_ids) in wanted {
+            let mut statuses = self.references.remove(&path).unwrap_or_default();
+            statuses.retain(|id, _| task_ids.contains(id));
+            if task_ids.iter().any(|id| !statuses.contains_key(id)) {
+                let outline = fs::read_to_string(&path)
+                    .map(|markdown| outline::scan(&markdown))
+                    .unwrap_or_default();
+                for id in task_ids {
+                    statuses
+                        .entry(id.clone())
+                        .or_insert_with(|| outline.task(&id).map(|t| t.status.clone()));
+                }
+            }
+            references.insert(path, statuses);
+        }
+        self.references = references;
+
+        if same_plans {
+            return;
+        }
+        let this = self.this.clone();
+        let paths: Vec<PathBuf> = self.references.keys().cloned().collect();
+        self.reference_poller = (!paths.is_empty()).then(|| {
+            Poller::spawn(paths, self.poll_interval, move |path| {
+                if let Some(handler) = this.upgrade() {
+                    if let Ok(mut handler) = handler.lock() {
+                        handler.handle_reference(path);
+                    }
+                }
+            })
+        });
+    }
+
+    /// Re-read the files the plan includes, polling any newly included files
+    /// Included files may live outside the plan's directory, so they are polled
+    /// whatever the backend
+    fn refresh_includes(&mut self) {
+        let wanted: BTreeSet<PathBuf> = plan::read_plan_file(&self.targets.plan.to_string_lossy())
+            .map(|doc| {
+                doc.included_files()
+                    .filter_map(|path| resolve_file_path(path).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if wanted == self.targets.includes {
+            return;
+        }
+
+        let dropped = &self.targets.includes - &wanted;
+        self.known.retain(|path, _| !dropped.contains(path));
+        for path in &wanted {
+            if !self.known.contains_key(path) {
+                if let Some(content) = KnownContent::read(path) {
+                    self.known.insert(path.clone(), content);
+                }
+            }
+        }
+        self.targets.includes = wanted;
+
+        let this = self.this.clone();
+        let paths: Vec<PathBuf> = self.targets.includes.iter().cloned().collect();
+        self.include_poller = (!paths.is_empty()).then(|| {
+            Poller::spawn(paths, self.poll_interval, move |path| {
+                if let Some(handler) = this.upgrade() {
+                    if let Ok(mut handler) = handler.lock() {
+                        handler.handle(path);
+                    }
+                }
+            })
+        });
+    }
+
+    /// Handle a possible change to a referenced plan
+    /// Reports status changes of the tasks this plan depends on, compared with the
+    /// last status seen for each, so a half-written file can't hide a change
+    fn handle_reference(&mut self, path: &Path) {
+        let Some(statuses) = self.references.get_mut(path) else {
+            return;
+        };
+        let Ok(markdown) = fs::read_to_string(path) else {
+            return;
+        };
+        let outline = outline::scan(&markdown);
+
+        let mut status_changes = Vec::new();
+        for (id, last) in statuses.iter_mut() {
+            let Some(task) = outline.task(id) else {
+                continue;
+            };
+            if let Some(from) = last.as_ref().filter(|from| **from != task.status) {
+                status_changes.push(StatusChange {
+                    task_id: task.id.clone(),
+                    content: task.content.clone(),
+                    from: from.clone(),
+                    to: task.status.clone(),
+                });
+            }
+            *last = Some(task.status.clone());
+        }
+        if status_changes.is_empty() {
+            return;
+        }
+
+        (self.on_event)(WatchEvent::Changed(FileChangeEvent {
+            path: path.to_string_lossy().to_string(),
+            file_type: "reference".to_string(),
+            plan_path: self.watched_plan.clone(),
+            old_hash: None,
+            new_hash: content_hash(markdown.as_bytes()),
+            changes: Some(FileChanges::Plan(PlanChanges {
+                status_changes,
+                ..Default::default()
+            })),
+        }));
+    }
+
+    /// Handle a possible change to a file in the plan's directory
+    fn handle(&mut self, path: &Path) {
+        let Some(file_type) = self.targets.classify(path) else {
+            return;
+        };
+
+        let status = Arc::clone(&self.status);
+        let Ok(mut status) = status.lock() else {
+            return;
+        };
+
+        let Some(current) = KnownContent::read(path) else {
+            let previous = self.known.remove(path);
+            if file_type != "plan" || *status == WatchStatus::Missing {
+                return;
+            }
+
+            *status = WatchStatus::Missing;
+            let new_path = previous.and_then(|previous| {
+                find_moved_plan(&self.targets.dir, &self.targets.plan, &previous)
+            });
+            (self.on_event)(WatchEvent::Lifecycle(PlanLifecycleEvent {
+                plan_path: self.watched_plan.clone(),
+                kind: if new_path.is_some() {
+                    PlanLifecycleKind::Moved
+                } else {
+                    PlanLifecycleKind::Deleted
+                },
+                new_path: new_path.map(|p| p.to_string_lossy().to_string()),
+            }));
+            return;
+        };
+
+        if file_type == "plan" && *status == WatchStatus::Missing {
+            *status = WatchStatus::Watching;
+            (self.on_event)(WatchEvent::Lifecycle(PlanLifecycleEvent {
+                plan_path: self.watched_plan.clone(),
+                kind: PlanLifecycleKind::Recreated,
+                new_path: None,
+            }));
+        }
+
+        // Included files next to the plan are seen by both the native watcher and the poller
+        let unchanged = self.known.get(path).is_some_and(|p| p.hash == current.hash);
+        if file_type == "include" && unchanged {
+            return;
+        }
+
+        let previous = self.known.remove(path);
+        let self_write = is_self_write(path, &current.hash);
+
+        let payload = FileChangeEvent {
+            path: path.to_string_lossy().to_string(),
+            file_type: file_type.to_string(),
+            plan_path: self.watched_plan.clone(),
+            old_hash: previous.as_ref().map(|p| p.hash.clone()),
+            new_hash: current.hash.clone(),
+            changes: previous
+                .as_ref()
+                .and_then(|p| changes::summarize(file_type, &p.content, &current.content)),
+        };
+        self.known.insert(path.to_path_buf(), current);
+        if file_type == "plan" {
+            self.refresh_references();
+        }
+        if file_type == "plan" || file_type == "include" {
+            self.refresh_includes();
+        }
+
+        // Our own writes (e.g. write_layout) aren't external changes
+        if self_write {
+            return;
+        }
+
+        (self.on_event)(WatchEvent::Changed(payload));
+    }
+}
+
+/// Watch the directory of a plan with the OS file watcher
+/// Watching the directory keeps events flowing when editors save by renaming a temp
+/// file over the plan, and picks up layout files created after watching starts
+fn watch_native(handler: Arc<Mutex<PlanEventHandler>>) -> Result<FileWatch, String> {
+    let (dir, status) = {
+        let handler = handler.lock().map_err(|e| e.to_string())?;
+        (handler.targets.dir.clone(), Arc::clone(&handler.status))
+    };
 
     // Create debouncer with event handler
-    let app_handle = app.clone();
     let mut debouncer = new_debouncer(
         Duration::from_millis(DEBOUNCE_MS),
-        move |res: Result<Vec<notify_debouncer_mini::DebouncedEvent>, notify::Error>| {
-            match res {
-                Ok(events) => {
-                    for event in events {
-                        if event.kind != DebouncedEventKind::Any {
-                            continue;
-                        }
-
-                        let path_str = event.path.to_string_lossy().to_string();
-
-                        // Determine file type
-                        let file_type = if path_str.ends_with(".layout.json") {
-                            "layout"
-                        } else if path_str.ends_with(".md") {
-                            "plan"
-                        } else {
-                            continue;
-                        };
-
-                        let payload = FileChangeEvent {
-                            path: path_str,
-                            file_type: file_type.to_string(),
-                        };
-
-                        // Emit event to frontend
-                        if let Err(e) = app_handle.emit("file-changed", payload) {
-                            eprintln!("Failed to emit file-changed event: {}", e);
-                        }
+        move |res: Result<Vec<notify_debouncer_mini::DebouncedEvent>, notify::Error>| match res {
+            Ok(events) => {
+                let Ok(mut handler) = handler.lock() else {
+                    return;
+                };
+                for event in events {
+                    if event.kind == DebouncedEventKind::Any {
+                        handler.handle(&event.path);
                     }
                 }
-                Err(e) => {
-                    eprintln!("File watch error: {:?}", e);
-                }
+            }
+            Err(e) => {
+                eprintln!("File watch error: {:?}", e);
             }
         },
     )
     .map_err(|e| format!("Failed to create debouncer: {}", e))?;
 
-    // Watch the plan file
-    let plan_path_buf = PathBuf::from(&plan_path);
-    if plan_path_buf.exists() {
-        debouncer
-            .watcher()
-            .watch(&plan_path_buf, notify::RecursiveMode::NonRecursive)
-            .map_err(|e| format!("Failed to watch plan file: {}", e))?;
+    debouncer
+        .watcher()
+        .watch(&dir, notify::RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch plan directory: {}", e))?;
+
+    Ok(FileWatch {
+        backend: ActiveBackend::Native(debouncer),
+        status,
+    })
+}
+
+/// Poll a plan and its layout for mtime/size changes
+fn watch_polling(
+    handler: Arc<Mutex<PlanEventHandler>>,
+    interval: Duration,
+) -> Result<FileWatch, String> {
+    let (paths, status) = {
+        let handler = handler.lock().map_err(|e| e.to_string())?;
+        let targets = &handler.targets;
+        (
+            vec![targets.plan.clone(), targets.layout.clone()],
+            Arc::clone(&handler.status),
+        )
+    };
+
+    let poller = Poller::spawn(paths, interval, move |path| {
+        if let Ok(mut handler) = handler.lock() {
+            handler.handle(path);
+        }
+    });
+
+    Ok(FileWatch {
+        backend: ActiveBackend::Polling(poller),
+        status,
+    })
+}
+
+/// Whether the plan's directory is on a filesystem that should be polled
+fn needs_polling(handler: &Mutex<PlanEventHandler>) -> bool {
+    handler
+        .lock()
+        .is_ok_and(|handler| polling::needs_polling(&handler.targets.dir))
+}
+
+/// Watch a plan and its layout with the given backend
+/// `Auto` polls filesystems native events don't arrive from, and otherwise uses the
+/// native watcher, falling back to polling when it can't be set up
+fn watch_plan_with(
+    plan_path: &str,
+    backend: WatcherBackend,
+    poll_interval: Duration,
+    on_event: impl Fn(WatchEvent) + Send + 'static,
+) -> Result<FileWatch, String> {
+    let handler = PlanEventHandler::spawn(plan_path, poll_interval, on_event)?;
+
+    match backend {
+        WatcherBackend::Native => watch_native(handler),
+        WatcherBackend::Polling => watch_polling(handler, poll_interval),
+        WatcherBackend::Auto if needs_polling(&handler) => watch_polling(handler, poll_interval),
+        WatcherBackend::Auto => watch_native(Arc::clone(&handler)).or_else(|e| {
+            eprintln!("Native file watching unavailable, polling instead: {}", e);
+            watch_polling(handler, poll_interval)
+        }),
     }
+}
 
-    // Watch the layout file if it exists
-    let layout_path_buf = PathBuf::from(&layout_path);
-    if layout_path_buf.exists() {
-        debouncer
-            .watcher()
-            .watch(&layout_path_buf, notify::RecursiveMode::NonRecursive)
-            .map_err(|e| format!("Failed to watch layout file: {}", e))?;
+/// Watch a plan, emitting `file-changed` and `plan-lifecycle` events to the frontend
+/// Uses the watcher backend and poll interval from preferences
+fn watch_plan(app: AppHandle, plan_path: &str) -> Result<FileWatch, String> {
+    let (backend, poll_interval) = preferences::get_watcher_settings();
+    watch_plan_with(
+        plan_path,
+        backend,
+        poll_interval,
+        move |event| match event {
+            WatchEvent::Changed(payload) => {
+                // Keep plan content history for progress replay
+                if payload.file_type == "plan" {
+                    if let Err(e) = history::record_plan_snapshot(&payload.path) {
+                        eprintln!("Failed to record plan snapshot: {}", e);
+                    }
+                }
+
+                // Emit event to frontend
+                if let Err(e) = app.emit("file-changed", payload) {
+                    eprintln!("Failed to emit file-changed event: {}", e);
+                }
+            }
+            WatchEvent::Lifecycle(payload) => {
+                if let Err(e) = app.emit("plan-lifecycle", payload) {
+                    eprintln!("Failed to emit plan-lifecycle event: {}", e);
+                }
+            }
+        },
+    )
+}
+
+/// Start watching a plan file and its associated layout file for this window
+/// Replaces any plan this window was previously watching; other windows are unaffected
+#[tauri::command]
+pub fn start_watching(
+    app: AppHandle,
+    window: Window,
+    plan_path: String,
+    state: tauri::State<'_, Mutex<WatcherState>>,
+) -> Result<(), String> {
+    let mut watcher_state = state.lock().map_err(|e| e.to_string())?;
+    let label = window.label().to_string();
+
+    // Stop this window's previous watches
+    for previous in watcher_state.plans_for_window(&label) {
+        if previous != plan_path {
+            watcher_state.detach(&previous, &label);
+        }
     }
 
-    watcher_state.debouncer = Some(debouncer);
-    watcher_state.watched_plan = Some(plan_path_clone);
+    watcher_state.attach(&plan_path, &label, || watch_plan(app, &plan_path))
+}
+
+/// Stop watching files for this window
+#[tauri::command]
+pub fn stop_watching(
+    window: Window,
+    state: tauri::State<'_, Mutex<WatcherState>>,
+) -> Result<(), String> {
+    let mut watcher_state = state.lock().map_err(|e| e.to_string())?;
+    let label = window.label().to_string();
+
+    for plan_path in watcher_state.plans_for_window(&label) {
+        watcher_state.detach(&plan_path, &label);
+    }
 
     Ok(())
 }
 
-/// Stop watching files
+/// Get the plan path this window is watching
 #[tauri::command]
-pub fn stop_watching(state: tauri::State<'_, Mutex<WatcherState>>) -> Result<(), String> {
-    let mut watcher_state = state.lock().map_err(|e| e.to_string())?;
+pub fn get_watched_plan(
+    window: Window,
+    state: tauri::State<'_, Mutex<WatcherState>>,
+) -> Result<Option<String>, String> {
+    let watcher_state = state.lock().map_err(|e| e.to_string())?;
+    let mut plans = watcher_state.plans_for_window(window.label());
+    plans.sort();
+    Ok(plans.into_iter().next())
+}
 
-    watcher_state.debouncer = None;
-    watcher_state.watched_plan = None;
+/// Add a plan to the watched set without affecting other watches
+/// `window` defaults to "main"
+#[tauri::command]
+pub fn add_watched_plan(
+    app: AppHandle,
+    plan_path: String,
+    window: Option<String>,
+    state: tauri::State<'_, Mutex<WatcherState>>,
+) -> Result<(), String> {
+    let mut watcher_state = state.lock().map_err(|e| e.to_string())?;
+    let owner = window.unwrap_or_else(|| DEFAULT_OWNER.to_string());
+    watcher_state.attach(&plan_path, &owner, || watch_plan(app, &plan_path))
+}
 
+/// Remove a plan from the watched set
+/// With a window, only that window stops watching; without one the watch is dropped
+#[tauri::command]
+pub fn remove_watched_plan(
+    plan_path: String,
+    window: Option<String>,
+    state: tauri::State<'_, Mutex<WatcherState>>,
+) -> Result<(), String> {
+    let mut watcher_state = state.lock().map_err(|e| e.to_string())?;
+    match window {
+        Some(window) => watcher_state.detach(&plan_path, &window),
+        None => {
+            watcher_state.watches.remove(&plan_path);
+        }
+    }
     Ok(())
 }
 
-/// Get the currently watched plan path
+/// List every watched plan and the windows watching it
 #[tauri::command]
-pub fn get_watched_plan(state: tauri::State<'_, Mutex<WatcherState>>) -> Result<Option<String>, String> {
+pub fn list_watched_plans(
+    state: tauri::State<'_, Mutex<WatcherState>>,
+) -> Result<Vec<WatchInfo>, String> {
     let watcher_state = state.lock().map_err(|e| e.to_string())?;
-    Ok(watcher_state.watched_plan.clone())
+    Ok(watcher_state.list())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Instant;
+    use tempfile::tempdir;
 
     #[test]
     fn test_get_layout_path() {
@@ -154,17 +872,319 @@ mod tests {
         let event = FileChangeEvent {
             path: "/path/to/plan.md".to_string(),
             file_type: "plan".to_string(),
+            plan_path: "/path/to/plan.md".to_string(),
+            old_hash: None,
+            new_hash: content_hash(b""),
+            changes: None,
         };
 
         let json = serde_json::to_string(&event).unwrap();
         assert!(json.contains("fileType")); // camelCase
+        assert!(json.contains("planPath"));
         assert!(json.contains("plan"));
     }
 
     #[test]
     fn test_watcher_state_default() {
         let state = WatcherState::default();
-        assert!(state.debouncer.is_none());
-        assert!(state.watched_plan.is_none());
+        assert!(state.watches.is_empty());
+        assert!(state.list().is_empty());
+    }
+
+    fn noop_watch() -> Result<FileWatch, String> {
+        let debouncer = new_debouncer(
+            Duration::from_millis(DEBOUNCE_MS),
+            |_: notify_debouncer_mini::DebounceEventResult| {},
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(FileWatch {
+            backend: ActiveBackend::Native(debouncer),
+            status: Arc::default(),
+        })
+    }
+
+    /// Watch a plan, forwarding its events to a channel
+    fn watch_with(
+        plan_path: &Path,
+        backend: WatcherBackend,
+    ) -> (FileWatch, mpsc::Receiver<WatchEvent>) {
+        let (tx, rx) = mpsc::channel();
+        let watch = watch_plan_with(
+            &plan_path.to_string_lossy(),
+            backend,
+            Duration::from_millis(100),
+            move |event| {
+                let _ = tx.send(event);
+            },
+        )
+        .unwrap();
+        (watch, rx)
+    }
+
+    fn watch(plan_path: &Path) -> (FileWatch, mpsc::Receiver<WatchEvent>) {
+        watch_with(plan_path, WatcherBackend::Native)
+    }
+
+    /// Wait for the first event matching `pick`
+    fn wait<T>(rx: &mpsc::Receiver<WatchEvent>, pick: impl Fn(WatchEvent) -> Option<T>) -> T {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let event = rx
+                .recv_timeout(remaining)
+                .expect("no matching event received");
+            if let Some(found) = pick(event) {
+                return found;
+            }
+        }
+    }
+
+    /// Wait for a file-changed event of the given type
+    fn wait_for(rx: &mpsc::Receiver<WatchEvent>, file_type: &str) -> FileChangeEvent {
+        wait(rx, |event| match event {
+            WatchEvent::Changed(event) if event.file_type == file_type => Some(event),
+            _ => None,
+        })
+    }
+
+    /// Wait for a plan-lifecycle event of the given kind
+    fn wait_lifecycle(
+        rx: &mpsc::Receiver<WatchEvent>,
+        kind: PlanLifecycleKind,
+    ) -> PlanLifecycleEvent {
+        wait(rx, |event| match event {
+            WatchEvent::Lifecycle(event) if event.kind == kind => Some(event),
+            _ => None,
+        })
+    }
+
+    /// Save a file the way vim and JetBrains do: write a temp file and rename it over
+    fn rename_save(path: &Path, content: &str) {
+        let temp = path.with_extension("md.tmp~");
+        fs::write(&temp, content).unwrap();
+        fs::rename(&temp, path).unwrap();
+    }
+
+    #[test]
+    fn test_watch_survives_rename_saves() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        fs::write(&plan_path, "# Plan: Test\n").unwrap();
+
+        let (_watch, rx) = watch(&plan_path);
+
+        // Repeated rename-based saves keep producing plan events
+        for i in 0..2 {
+            rename_save(&plan_path, &format!("# Plan: Test {}\n", i));
+            let event = wait_for(&rx, "plan");
+            assert!(event.path.ends_with("plan.md"));
+            assert_eq!(event.plan_path, plan_path.to_string_lossy());
+            assert_ne!(event.old_hash.as_deref(), Some(event.new_hash.as_str()));
+        }
+
+        // A layout file created after watching started is picked up
+        fs::write(temp.path().join("plan.md.layout.json"), "{}").unwrap();
+        let event = wait_for(&rx, "layout");
+        assert!(event.path.ends_with("plan.md.layout.json"));
+
+        // Temp files never surface as events
+        while let Ok(event) = rx.recv_timeout(Duration::from_millis(DEBOUNCE_MS * 2)) {
+            if let WatchEvent::Changed(event) = event {
+                assert!(!event.path.ends_with('~'));
+            }
+        }
+    }
+
+    #[test]
+    fn test_self_writes_are_suppressed() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let plan_path_str = plan_path.to_string_lossy().to_string();
+        fs::write(&plan_path, "# Plan: Test\n").unwrap();
+
+        let (_watch, rx) = watch(&plan_path);
+
+        crate::layout::write_layout(plan_path_str.clone(), Default::default()).unwrap();
+        assert!(rx
+            .recv_timeout(Duration::from_millis(DEBOUNCE_MS * 3))
+            .is_err());
+
+        // A genuinely external change to the same file still comes through
+        fs::write(temp.path().join("plan.md.layout.json"), "{}").unwrap();
+        wait_for(&rx, "layout");
+    }
+
+    #[test]
+    fn test_plan_change_payload() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        let before = "# Plan: Test\n\n## Phase 1 — Work\n- [ ] Build (id: t1)\n";
+        fs::write(&plan_path, before).unwrap();
+
+        let (_watch, rx) = watch(&plan_path);
+
+        fs::write(&plan_path, before.replace("[ ]", "[x]")).unwrap();
+        let event = wait_for(&rx, "plan");
+
+        assert_eq!(event.old_hash, Some(content_hash(before.as_bytes())));
+        let Some(FileChanges::Plan(changes)) = event.changes else {
+            panic!("expected plan changes");
+        };
+        assert_eq!(changes.status_changes.len(), 1);
+        assert_eq!(changes.status_changes[0].to, "completed");
+    }
+
+    #[test]
+    fn test_referenced_plan_status_changes() {
+        let temp = tempdir().unwrap();
+        let backend_dir = temp.path().join("backend");
+        fs::create_dir(&backend_dir).unwrap();
+        let backend_path = backend_dir.join("plan.md");
+        let backend = "## Phase 1 — API\n- [ ] Auth (id: t11)\n- [ ] Endpoints (id: t12)\n";
+        fs::write(&backend_path, backend).unwrap();
+
+        let plan_path = temp.path().join("frontend.md");
+        let plan = "## Phase 1 — UI\n- [ ] Screens (depends: backend/plan.md#t12) (id: t1)\n";
+        fs::write(&plan_path, plan).unwrap();
+
+        let (_watch, rx) = watch(&plan_path);
+
+        // Tasks nobody depends on don't matter; the referenced one does
+        let backend = backend.replace("[ ] Auth", "[x] Auth");
+        fs::write(&backend_path, &backend).unwrap();
+        thread::sleep(Duration::from_millis(300));
+        fs::write(
+            &backend_path,
+            backend.replace("[ ] Endpoints", "[-] Endpoints"),
+        )
+        .unwrap();
+
+        let event = wait_for(&rx, "reference");
+        assert!(event.path.ends_with("plan.md"));
+        assert_eq!(event.plan_path, plan_path.to_string_lossy());
+        let Some(FileChanges::Plan(changes)) = event.changes else {
+            panic!("expected plan changes");
+        };
+        assert_eq!(changes.status_changes.len(), 1);
+        assert_eq!(changes.status_changes[0].task_id, "t12");
+        assert_eq!(changes.status_changes[0].to, "in_progress");
+    }
+
+    #[test]
+    fn test_included_file_changes() {
+        let temp = tempdir().unwrap();
+        let phases_dir = temp.path().join("phases");
+        fs::create_dir(&phases_dir).unwrap();
+        let auth_path = phases_dir.join("02-auth.md");
+        let auth = "## Phase 2 — Auth\n- [ ] Login (id: t2)\n";
+        fs::write(&auth_path, auth).unwrap();
+
+        let plan_path = temp.path().join("plan.md");
+        let plan = "## Phase 1 — Setup\n- [ ] Init (id: t1)\n<!-- include: phases/02-auth.md -->\n";
+        fs::write(&plan_path, plan).unwrap();
+
+        let (_watch, rx) = watch(&plan_path);
+
+        fs::write(&auth_path, auth.replace("[ ]", "[x]")).unwrap();
+        let event = wait_for(&rx, "include");
+        assert!(event.path.ends_with("02-auth.md"));
+        assert_eq!(event.plan_path, plan_path.to_string_lossy());
+        assert_eq!(event.old_hash, Some(content_hash(auth.as_bytes())));
+        let Some(FileChanges::Plan(changes)) = event.changes else {
+            panic!("expected plan changes");
+        };
+        assert_eq!(changes.status_changes.len(), 1);
+        assert_eq!(changes.status_changes[0].task_id, "t2");
+    }
+
+    #[test]
+    fn test_plan_lifecycle_events() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        fs::write(&plan_path, "# Plan: Test\n").unwrap();
+
+        let (watch, rx) = watch(&plan_path);
+        assert_eq!(watch.status(), WatchStatus::Watching);
+
+        // A rename within the directory is reported with the new path
+        let moved_path = temp.path().join("renamed.md");
+        fs::rename(&plan_path, &moved_path).unwrap();
+        let event = wait_lifecycle(&rx, PlanLifecycleKind::Moved);
+        assert!(event.new_path.unwrap().ends_with("renamed.md"));
+        assert_eq!(watch.status(), WatchStatus::Missing);
+
+        fs::write(&plan_path, "# Plan: Back\n").unwrap();
+        wait_lifecycle(&rx, PlanLifecycleKind::Recreated);
+        wait_for(&rx, "plan");
+        assert_eq!(watch.status(), WatchStatus::Watching);
+
+        fs::remove_file(&plan_path).unwrap();
+        let event = wait_lifecycle(&rx, PlanLifecycleKind::Deleted);
+        assert!(event.new_path.is_none());
+        assert_eq!(watch.status(), WatchStatus::Missing);
+    }
+
+    #[test]
+    fn test_polling_backend_events() {
+        let temp = tempdir().unwrap();
+        let plan_path = temp.path().join("plan.md");
+        fs::write(&plan_path, "# Plan: Test\n").unwrap();
+
+        let (watch, rx) = watch_with(&plan_path, WatcherBackend::Polling);
+        assert_eq!(watch.backend(), WatcherBackend::Polling);
+
+        fs::write(&plan_path, "# Plan: Polled\n").unwrap();
+        let event = wait_for(&rx, "plan");
+        assert!(event.old_hash.is_some());
+
+        fs::write(temp.path().join("plan.md.layout.json"), "{}").unwrap();
+        wait_for(&rx, "layout");
+
+        fs::remove_file(&plan_path).unwrap();
+        wait_lifecycle(&rx, PlanLifecycleKind::Deleted);
+        assert_eq!(watch.status(), WatchStatus::Missing);
+    }
+
+    #[test]
+    fn test_is_self_write_compares_content() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("plan.md.layout.json");
+
+        record_self_write(&path, b"ours");
+        let resolved = resolve_file_path(&path).unwrap();
+        assert!(is_self_write(&resolved, &content_hash(b"ours")));
+        // An editor writing the same content again is reported
+        assert!(!is_self_write(&resolved, &content_hash(b"ours")));
+
+        // A different write consumes the entry too, so a revert to ours is reported
+        record_self_write(&path, b"ours");
+        assert!(!is_self_write(&resolved, &content_hash(b"theirs")));
+        assert!(!is_self_write(&resolved, &content_hash(b"ours")));
+    }
+
+    #[test]
+    fn test_watcher_state_multiple_plans() {
+        let mut state = WatcherState::default();
+        state.attach("/a/plan.md", "main", noop_watch).unwrap();
+        state.attach("/b/plan.md", "main", noop_watch).unwrap();
+        state
+            .attach("/a/plan.md", "second", || panic!("watch should be shared"))
+            .unwrap();
+
+        let watches = state.list();
+        assert_eq!(watches.len(), 2);
+        assert_eq!(watches[0].plan_path, "/a/plan.md");
+        assert_eq!(watches[0].windows, vec!["main", "second"]);
+
+        let mut plans = state.plans_for_window("main");
+        plans.sort();
+        assert_eq!(plans, vec!["/a/plan.md", "/b/plan.md"]);
+
+        // Detaching one window keeps the watch alive for the other
+        state.detach("/a/plan.md", "main");
+        assert_eq!(state.list().len(), 2);
+        state.detach("/a/plan.md", "second");
+        assert_eq!(state.list().len(), 1);
+        assert!(state.plans_for_window("second").is_empty());
     }
 }
@@ -0,0 +1,215 @@
+//! Semantic change summaries for file-changed events
+//!
+//! Compares the previous and new content of a watched file so the frontend
+//! learns what changed without rereading and diffing everything itself.
+
+use serde::{Deserialize, Serialize};
+
+use crate::layout::{LayoutFile, NodeLayout};
+use crate::outline::{self, OutlineTask};
+
+/// A task whose status changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange {
+    pub task_id: String,
+    pub content: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// A phase whose name changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseRename {
+    pub phase_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// What changed in a plan file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanChanges {
+    pub tasks_added: Vec<OutlineTask>,
+    pub tasks_removed: Vec<OutlineTask>,
+    pub status_changes: Vec<StatusChange>,
+    pub renamed_phases: Vec<PhaseRename>,
+}
+
+/// A node whose position changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeMove {
+    pub node_id: String,
+    pub from: NodeLayout,
+    pub to: NodeLayout,
+}
+
+/// What changed in a layout file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutChanges {
+    pub moved_nodes: Vec<NodeMove>,
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+}
+
+/// Change summary attached to a file-changed event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileChanges {
+    Plan(PlanChanges),
+    Layout(LayoutChanges),
+}
+
+/// Diff two versions of plan markdown
+pub fn diff_plan(before: &str, after: &str) -> PlanChanges {
+    let before = outline::scan(before);
+    let after = outline::scan(after);
+    let mut changes = PlanChanges::default();
+
+    for task in &after.tasks {
+        match before.task(&task.id) {
+            None => changes.tasks_added.push(task.clone()),
+            Some(previous) if previous.status != task.status => {
+                changes.status_changes.push(StatusChange {
+                    task_id: task.id.clone(),
+                    content: task.content.clone(),
+                    from: previous.status.clone(),
+                    to: task.status.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    changes.tasks_removed = before
+        .tasks
+        .iter()
+        .filter(|task| after.task(&task.id).is_none())
+        .cloned()
+        .collect();
+
+    for phase in &after.phases {
+        if let Some(previous) = before.phases.iter().find(|p| p.id == phase.id) {
+            if previous.name != phase.name {
+                changes.renamed_phases.push(PhaseRename {
+                    phase_id: phase.id.clone(),
+                    from: previous.name.clone(),
+                    to: phase.name.clone(),
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+/// Diff two versions of a layout file
+/// Returns None when either version isn't a valid layout
+pub fn diff_layout(before: &str, after: &str) -> Option<LayoutChanges> {
+    let before: LayoutFile = serde_json::from_str(before).ok()?;
+    let after: LayoutFile = serde_json::from_str(after).ok()?;
+    let mut changes = LayoutChanges::default();
+
+    for (node_id, to) in &after.layouts {
+        match before.layouts.get(node_id) {
+            None => changes.added_nodes.push(node_id.clone()),
+            Some(from) if from.x != to.x || from.y != to.y => changes.moved_nodes.push(NodeMove {
+                node_id: node_id.clone(),
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    changes.removed_nodes = before
+        .layouts
+        .keys()
+        .filter(|id| !after.layouts.contains_key(*id))
+        .cloned()
+        .collect();
+
+    // HashMap order is arbitrary; keep payloads stable
+    changes
+        .moved_nodes
+        .sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    changes.added_nodes.sort();
+    changes.removed_nodes.sort();
+
+    Some(changes)
+}
+
+/// Summarize a change to a watched file of the given type ("plan", "include" or "layout")
+pub fn summarize(file_type: &str, before: &str, after: &str) -> Option<FileChanges> {
+    match file_type {
+        "plan" | "include" => Some(FileChanges::Plan(diff_plan(before, after))),
+        "layout" => diff_layout(before, after).map(FileChanges::Layout),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_plan() {
+        let before = "# Plan: X\n\n## Phase 1 — Setup\n- [ ] Build (id: t1)\n- [ ] Drop (id: t2)\n";
+        let after =
+            "# Plan: X\n\n## Phase 1 — Bootstrap\n- [x] Build (id: t1)\n- [ ] Test (id: t3)\n";
+
+        let changes = diff_plan(before, after);
+        assert_eq!(changes.tasks_added.len(), 1);
+        assert_eq!(changes.tasks_added[0].id, "t3");
+        assert_eq!(changes.tasks_removed[0].id, "t2");
+        assert_eq!(changes.status_changes[0].from, "pending");
+        assert_eq!(changes.status_changes[0].to, "completed");
+        assert_eq!(
+            changes.renamed_phases,
+            vec![PhaseRename {
+                phase_id: "phase_1".to_string(),
+                from: "Setup".to_string(),
+                to: "Bootstrap".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_layout() {
+        let node = |x: f64| NodeLayout {
+            x,
+            y: 0.0,
+            width: 280.0,
+            height: 80.0,
+        };
+        let mut before = LayoutFile::default();
+        before.layouts.insert("t1".to_string(), node(0.0));
+        before.layouts.insert("t2".to_string(), node(0.0));
+        let mut after = LayoutFile::default();
+        after.layouts.insert("t1".to_string(), node(100.0));
+        after.layouts.insert("t3".to_string(), node(0.0));
+
+        let changes = diff_layout(
+            &serde_json::to_string(&before).unwrap(),
+            &serde_json::to_string(&after).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(changes.moved_nodes.len(), 1);
+        assert_eq!(changes.moved_nodes[0].to.x, 100.0);
+        assert_eq!(changes.added_nodes, vec!["t3"]);
+        assert_eq!(changes.removed_nodes, vec!["t2"]);
+
+        assert!(diff_layout("{ broken", "{}").is_none());
+    }
+
+    #[test]
+    fn test_file_changes_serialization() {
+        let json = serde_json::to_string(&FileChanges::Plan(PlanChanges::default())).unwrap();
+        assert!(json.contains("\"kind\":\"plan\""));
+        assert!(json.contains("statusChanges"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Polling watcher backend
+//!
+//! For filesystems where native change events never arrive (NFS, SSHFS,
+//! Docker bind mounts): checks each file's mtime and size on an interval and
+//! reports the ones that changed.
+//!
+//! On Linux, `needs_polling` spots such filesystems from the mount table so
+//! the `Auto` backend can poll them; elsewhere they need `Polling` set
+//! explicitly.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Default time between polls (ms)
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 1000;
+
+/// Smallest allowed time between polls (ms)
+pub const MIN_POLL_INTERVAL_MS: u64 = 100;
+
+/// Filesystem types whose changes native watching misses: network filesystems
+/// only report changes made on this machine, and FUSE and VM shares
+/// (Docker Desktop, WSL, Vagrant) often report nothing at all
+const POLLED_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "ncpfs",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "9p",
+    "virtiofs",
+    "vboxsf",
+    "vmhgfs",
+    "fuse",
+];
+
+/// Whether files under `dir` live on a filesystem that should be polled
+#[cfg(target_os = "linux")]
+pub fn needs_polling(dir: &Path) -> bool {
+    fs::read_to_string("/proc/self/mounts")
+        .ok()
+        .and_then(|mounts| mount_fs_type(&mounts, dir))
+        .is_some_and(|fs_type| is_polled_fs_type(&fs_type))
+}
+
+/// Whether files under `dir` live on a filesystem that should be polled
+/// Only detected on Linux
+#[cfg(not(target_os = "linux"))]
+pub fn needs_polling(_dir: &Path) -> bool {
+    false
+}
+
+fn is_polled_fs_type(fs_type: &str) -> bool {
+    POLLED_FS_TYPES.contains(&fs_type) || fs_type.starts_with("fuse.")
+}
+
+/// Undo the octal escapes (`\040` for a space) of a mount table path
+fn unescape_mount_path(path: &str) -> String {
+    let mut unescaped = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(index) = rest.find('\\') {
+        unescaped.push_str(&rest[..index]);
+        let code = rest.get(index + 1..index + 4);
+        match code.and_then(|code| u8::from_str_radix(code, 8).ok()) {
+            Some(byte) => {
+                unescaped.push(char::from(byte));
+                rest = &rest[index + 4..];
+            }
+            None => {
+                unescaped.push('\\');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Type of the filesystem holding `path`, from mount table lines
+/// (`device mount_point type options ...`); the deepest mount point wins
+fn mount_fs_type(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = PathBuf::from(unescape_mount_path(fields.nth(1)?));
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point)
+                .then(|| (mount_point.components().count(), fs_type))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, fs_type)| fs_type.to_string())
+}
+
+/// Modification time and size of a file, or None when it doesn't exist
+type Fingerprint = Option<(SystemTime, u64)>;
+
+fn fingerprint(path: &Path) -> Fingerprint {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Background thread polling a fixed set of files; stops when dropped
+pub struct Poller {
+    stop: Arc<AtomicBool>,
+}
+
+impl Poller {
+    /// Start polling `paths` every `interval`, calling `on_change` for each file whose
+    /// fingerprint changed (including files appearing or disappearing)
+    pub fn spawn(
+        paths: Vec<PathBuf>,
+        interval: Duration,
+        mut on_change: impl FnMut(&Path) + Send + 'static,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        // Taken up front so changes made right after spawning aren't missed
+        let mut last: HashMap<PathBuf, Fingerprint> = paths
+            .iter()
+            .map(|path| (path.clone(), fingerprint(path)))
+            .collect();
+
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                thread::sleep(interval);
+                if thread_stop.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                for path in &paths {
+                    let current = fingerprint(path);
+                    if last.get(path) != Some(&current) {
+                        last.insert(path.clone(), current);
+                        on_change(path);
+                    }
+                }
+            }
+        });
+
+        Self { stop }
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_poller_reports_changed_files() {
+        let temp = tempdir().unwrap();
+        let watched = temp.path().join("plan.md");
+        let other = temp.path().join("other.md");
+        fs::write(&watched, "one").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let poller = Poller::spawn(
+            vec![watched.clone()],
+            Duration::from_millis(50),
+            move |path| {
+                let _ = tx.send(path.to_path_buf());
+            },
+        );
+
+        fs::write(&other, "ignored").unwrap();
+        fs::write(&watched, "longer content").unwrap();
+        let changed = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(changed, watched);
+
+        fs::remove_file(&watched).unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), watched);
+
+        drop(poller);
+    }
+
+    #[test]
+    fn test_mount_fs_type() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+            server:/export /mnt/shared nfs4 rw 0 0\n\
+            alice@host: /home/alice/remote\\040dir fuse.sshfs rw 0 0\n\
+            overlay /var/lib/docker/overlay2/x/merged overlay rw 0 0\n";
+        let fs_type = |path: &str| mount_fs_type(mounts, Path::new(path));
+
+        assert_eq!(fs_type("/home/alice/plan.md").as_deref(), Some("ext4"));
+        assert_eq!(fs_type("/mnt/shared/plans").as_deref(), Some("nfs4"));
+        assert_eq!(
+            fs_type("/home/alice/remote dir/plan.md").as_deref(),
+            Some("fuse.sshfs")
+        );
+        // Path components, not string prefixes
+        assert_eq!(fs_type("/mnt/sharedother").as_deref(), Some("ext4"));
+
+        assert!(is_polled_fs_type("nfs4"));
+        assert!(is_polled_fs_type("fuse.sshfs"));
+        assert!(!is_polled_fs_type("ext4"));
+        // Container roots still get inotify events
+        assert!(!is_polled_fs_type("overlay"));
+    }
+}
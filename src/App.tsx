@@ -12,32 +12,20 @@ import {
   ChevronDown,
 } from 'lucide-react';
 import { open } from '@tauri-apps/plugin-dialog';
-import { readTextFile } from '@tauri-apps/plugin-fs';
 
 import { Button } from './components/ui/button';
 import { WelcomeSkeleton } from './components/ui/skeleton';
 import { QuickActions } from './components/QuickActions';
 import { ConflictBanner } from './components/ConflictBanner';
+import { StaleLayoutBanner } from './components/StaleLayoutBanner';
 import { ChatPanel } from './components/chat';
 import { Terminal } from './components/terminal';
 import { ToastContainer, useToasts, toast } from './components/ui/toast';
 import { PlanCanvas } from './canvas';
-import { usePlanStore, usePreferencesStore, useTerminalStore } from './store';
-import { parsePlan } from './parser';
-import { useFileWatcher, startWatching, stopWatching, useKeyboardShortcuts, type KeyboardShortcut } from './hooks';
+import { usePlanStore, usePreferencesStore, useTerminalStore, readPlan } from './store';
+import { useFileWatcher, startWatching, stopWatching, useKeyboardShortcuts, type KeyboardShortcut, type FileChangeEvent } from './hooks';
 import type { PlanDoc, LayoutMap, Status } from './types';
 
-/** Simple hash function for plan content */
-function hashString(str: string): string {
-  let hash = 0;
-  for (let i = 0; i < str.length; i++) {
-    const char = str.charCodeAt(i);
-    hash = ((hash << 5) - hash) + char;
-    hash = hash & hash;
-  }
-  return Math.abs(hash).toString(16);
-}
-
 /** Sample plan for demo mode */
 const SAMPLE_PLAN: PlanDoc = {
   phases: [
@@ -258,6 +246,9 @@ export default function App() {
     externalChangeType,
     notifyExternalChange,
     dismissExternalChanges,
+    isLayoutStale,
+    dismissStaleLayout,
+    regenerateLayout,
     reloadPlan,
     reloadLayout,
   } = usePlanStore();
@@ -269,6 +260,7 @@ export default function App() {
     isLoadingPreferences,
     loadLaunchConfig,
     loadPreferences,
+    loadPlanDefaults,
     setLastPlan,
     removeRecentPlan,
   } = usePreferencesStore();
@@ -314,18 +306,19 @@ export default function App() {
   // Helper function to open a plan file
   const openPlanFile = useCallback(async (filePath: string) => {
     try {
-      const content = await readTextFile(filePath);
-      const result = parsePlan(content);
+      const result = await readPlan(filePath);
 
       if (result.success) {
-        const hash = hashString(content);
-        await mergeLayout(result.doc, filePath, hash, result.title);
+        await mergeLayout(result.doc, filePath, result.title);
         await startWatching(filePath);
         await setLastPlan(filePath);
 
-        // Set cwd to the plan's directory
+        // Working directory: --cwd given with this plan, then its front matter, then its directory
+        const defaults = await loadPlanDefaults(filePath);
+        const cliCwd = launchConfig?.planPath === filePath ? launchConfig.cwd : undefined;
         const dir = filePath.substring(0, filePath.lastIndexOf('/'));
-        if (dir) setCwd(dir);
+        const planCwd = cliCwd ?? defaults.cwd ?? dir;
+        if (planCwd) setCwd(planCwd);
 
         setIsCanvasView(true);
         toast.success(`Opened ${filePath.split('/').pop()}`);
@@ -337,7 +330,7 @@ export default function App() {
       console.error('Failed to open plan:', err);
       toast.error(`Failed to open plan: ${err}`);
     }
-  }, [mergeLayout, setLastPlan]);
+  }, [mergeLayout, setLastPlan, loadPlanDefaults, launchConfig]);
 
   // Handle external file changes
   const handleExternalPlanChange = useCallback(
@@ -354,10 +347,20 @@ export default function App() {
     [notifyExternalChange]
   );
 
+  const handleReferenceChange = useCallback((event: FileChangeEvent) => {
+    if (event.changes?.kind !== 'plan') return;
+    const plan = event.path.split('/').pop();
+    for (const change of event.changes.statusChanges) {
+      toast.info(`${plan}#${change.taskId} is now ${change.to.replace('_', ' ')}`);
+    }
+  }, []);
+
   // Set up file watcher
   useFileWatcher({
     onPlanChange: handleExternalPlanChange,
     onLayoutChange: handleExternalLayoutChange,
+    onReferenceChange: handleReferenceChange,
+    planPath,
     enabled: isCanvasView && !!planPath,
   });
 
@@ -385,7 +388,7 @@ export default function App() {
   }, [removeRecentPlan]);
 
   const handleDemoMode = useCallback(() => {
-    setPlan(SAMPLE_PLAN, '', 'demo');
+    setPlan(SAMPLE_PLAN, '');
     setLayouts(SAMPLE_LAYOUTS);
     setIsCanvasView(true);
   }, [setPlan, setLayouts]);
@@ -521,6 +524,9 @@ export default function App() {
                 onDismiss={dismissExternalChanges}
               />
             )}
+            {isLayoutStale && !hasExternalChanges && (
+              <StaleLayoutBanner onRegenerate={regenerateLayout} onDismiss={dismissStaleLayout} />
+            )}
             <PlanCanvas
               plan={plan}
               layouts={layouts}
@@ -18,12 +18,16 @@ const STATUS_COLORS = {
   pending: '#e2e8f0', // slate-200
   in_progress: '#fef3c7', // amber-100
   completed: '#d1fae5', // emerald-100
+  blocked: '#fee2e2', // red-100
+  cancelled: '#f1f5f9', // slate-100
 } as const;
 
 const STATUS_LABEL_COLORS = {
   pending: '#64748b', // slate-500
   in_progress: '#d97706', // amber-600
   completed: '#059669', // emerald-600
+  blocked: '#dc2626', // red-600
+  cancelled: '#94a3b8', // slate-400
 } as const;
 
 /** Generate a TLDraw shape ID from a plan node ID */
@@ -87,6 +91,8 @@ export function createTaskShape(
     pending: 'grey',
     in_progress: 'yellow',
     completed: 'green',
+    blocked: 'red',
+    cancelled: 'light-violet',
   } as const;
 
   return {
@@ -121,6 +127,8 @@ const ARROW_COLORS = {
   pending: 'grey',
   in_progress: 'yellow',
   completed: 'green',
+  blocked: 'red',
+  cancelled: 'light-violet',
 } as const;
 
 /** Create a TLDraw arrow shape for a dependency edge */
@@ -4,7 +4,7 @@
  * Shows quick action buttons when a node is selected.
  */
 
-import { Check, Circle, Clock, Plus, Trash2 } from 'lucide-react';
+import { AlertTriangle, Ban, Check, Circle, Clock, Plus, Trash2 } from 'lucide-react';
 import { Button } from './ui/button';
 import type { Status } from '../types';
 
@@ -33,6 +33,16 @@ const STATUS_CONFIG = {
     label: 'Completed',
     color: 'text-emerald-600',
   },
+  blocked: {
+    icon: AlertTriangle,
+    label: 'Blocked',
+    color: 'text-red-600',
+  },
+  cancelled: {
+    icon: Ban,
+    label: 'Cancelled',
+    color: 'text-slate-400',
+  },
 } as const;
 
 export function QuickActions({
@@ -0,0 +1,52 @@
+/**
+ * StaleLayoutBanner - Shows when the saved layout predates the plan's structure
+ *
+ * Offers to arrange the canvas from scratch or keep the current positions.
+ */
+
+import { LayoutGrid, X } from 'lucide-react';
+import { Button } from './ui/button';
+
+export interface StaleLayoutBannerProps {
+  /** Called when user asks for a fresh layout */
+  onRegenerate: () => void;
+  /** Called when user keeps the current positions */
+  onDismiss: () => void;
+}
+
+export function StaleLayoutBanner({ onRegenerate, onDismiss }: StaleLayoutBannerProps) {
+  return (
+    <div className="absolute top-0 left-0 right-0 z-50 bg-sky-50 border-b border-sky-200 px-4 py-2">
+      <div className="flex items-center justify-between max-w-4xl mx-auto">
+        <div className="flex items-center gap-3">
+          <LayoutGrid className="h-5 w-5 text-sky-600 flex-shrink-0" />
+          <span className="text-sm text-sky-800">
+            The <strong>layout</strong> was saved for an earlier version of this plan. Arrange it again?
+          </span>
+        </div>
+        <div className="flex items-center gap-2">
+          <Button
+            variant="outline"
+            size="sm"
+            onClick={onRegenerate}
+            className="h-7 gap-1.5 border-sky-300 bg-white hover:bg-sky-100"
+          >
+            <LayoutGrid className="h-3.5 w-3.5" />
+            Re-layout
+          </Button>
+          <Button
+            variant="ghost"
+            size="sm"
+            onClick={onDismiss}
+            className="h-7 w-7 p-0 hover:bg-sky-100"
+            title="Keep current positions"
+          >
+            <X className="h-4 w-4" />
+          </Button>
+        </div>
+      </div>
+    </div>
+  );
+}
+
+export default StaleLayoutBanner;
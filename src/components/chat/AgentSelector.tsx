@@ -44,7 +44,7 @@ export function AgentSelector({ cwd, planPath, className }: AgentSelectorProps)
     clearError,
   } = useAgentStore();
 
-  const { launchConfig, preferences, setPlanAgent } = usePreferencesStore();
+  const { launchConfig, preferences, planDefaults, setPlanAgent } = usePreferencesStore();
 
   // Track if we've attempted auto-connect
   const autoConnectAttempted = useRef(false);
@@ -76,16 +76,18 @@ export function AgentSelector({ cwd, planPath, className }: AgentSelectorProps)
       }
     }
 
-    // Priority 2: Last-used agent for this plan
-    if (!targetAgent && planPath && preferences?.planPreferences[planPath]?.lastAgent) {
-      const lastAgent = preferences.planPreferences[planPath].lastAgent as AgentType;
-      if (lastAgent in AGENT_INFO) {
-        targetAgent = lastAgent;
+    // Priority 2: The plan's defaults (last-used agent, then front matter, then default agent)
+    if (!targetAgent && planPath) {
+      // Wait until the defaults for this plan have loaded
+      if (!planDefaults || planDefaults.planPath !== planPath) return;
+      const planAgent = planDefaults.agent?.replace('-', '_') as AgentType | undefined;
+      if (planAgent && planAgent in AGENT_INFO) {
+        targetAgent = planAgent;
       }
     }
 
-    // Priority 3: Default agent
-    if (!targetAgent && preferences?.defaultAgent) {
+    // Priority 3: Default agent when no plan is open
+    if (!targetAgent && !planPath && preferences?.defaultAgent) {
       const defaultAgent = preferences.defaultAgent.replace('-', '_') as AgentType;
       if (defaultAgent in AGENT_INFO) {
         targetAgent = defaultAgent;
@@ -100,6 +102,7 @@ export function AgentSelector({ cwd, planPath, className }: AgentSelectorProps)
   }, [
     launchConfig,
     preferences,
+    planDefaults,
     planPath,
     availableAgents,
     isCheckingAgents,
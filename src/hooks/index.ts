@@ -3,7 +3,13 @@ export {
   startWatching,
   stopWatching,
   getWatchedPlan,
+  addWatchedPlan,
+  removeWatchedPlan,
+  listWatchedPlans,
   type FileChangeEvent,
+  type FileChanges,
+  type PlanLifecycleEvent,
+  type WatchInfo,
   type UseFileWatcherOptions,
 } from './useFileWatcher';
 
@@ -5,10 +5,69 @@
 import { useEffect, useCallback } from 'react';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
+import type { Status } from '../types';
 
 export interface FileChangeEvent {
   path: string;
-  fileType: 'plan' | 'layout';
+  /**
+   * 'include' is a file spliced into the plan with an include directive;
+   * 'reference' is another plan named by one of this plan's dependencies
+   */
+  fileType: 'plan' | 'layout' | 'include' | 'reference';
+  /** The watched plan this change belongs to */
+  planPath: string;
+  /** Content hash before the change, if known */
+  oldHash?: string;
+  /** Content hash after the change; equal to oldHash for no-op events */
+  newHash: string;
+  /** What changed, when the previous content was known */
+  changes?: FileChanges;
+}
+
+export interface OutlineTask {
+  id: string;
+  content: string;
+  status: Status;
+  phaseId: string;
+}
+
+export interface NodeLayout {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export type FileChanges =
+  | {
+      kind: 'plan';
+      tasksAdded: OutlineTask[];
+      tasksRemoved: OutlineTask[];
+      statusChanges: { taskId: string; content: string; from: string; to: string }[];
+      renamedPhases: { phaseId: string; from: string; to: string }[];
+    }
+  | {
+      kind: 'layout';
+      movedNodes: { nodeId: string; from: NodeLayout; to: NodeLayout }[];
+      addedNodes: string[];
+      removedNodes: string[];
+    };
+
+export interface PlanLifecycleEvent {
+  planPath: string;
+  kind: 'deleted' | 'moved' | 'recreated';
+  /** New location of a moved plan, when it could be detected */
+  newPath?: string;
+}
+
+export interface WatchInfo {
+  planPath: string;
+  /** Labels of the windows watching this plan */
+  windows: string[];
+  /** 'missing' while the plan file is deleted or moved away */
+  status: 'watching' | 'missing';
+  /** Backend in use for this watch */
+  backend: 'native' | 'polling';
 }
 
 export interface UseFileWatcherOptions {
@@ -16,6 +75,12 @@ export interface UseFileWatcherOptions {
   onPlanChange?: (path: string) => void;
   /** Called when the layout.json file changes */
   onLayoutChange?: (path: string) => void;
+  /** Called when a task this plan depends on changes status in another plan */
+  onReferenceChange?: (event: FileChangeEvent) => void;
+  /** Called when the watched plan is deleted, moved or recreated */
+  onPlanLifecycle?: (event: PlanLifecycleEvent) => void;
+  /** Only report changes for this plan (all watched plans when omitted) */
+  planPath?: string | null;
   /** Whether file watching is enabled */
   enabled?: boolean;
 }
@@ -26,25 +91,42 @@ export interface UseFileWatcherOptions {
 export function useFileWatcher({
   onPlanChange,
   onLayoutChange,
+  onReferenceChange,
+  onPlanLifecycle,
+  planPath,
   enabled = true,
 }: UseFileWatcherOptions) {
   useEffect(() => {
     if (!enabled) return;
 
     let unlisten: UnlistenFn | null = null;
+    let unlistenLifecycle: UnlistenFn | null = null;
 
     const setupListener = async () => {
       unlisten = await listen<FileChangeEvent>('file-changed', (event) => {
         const { path, fileType } = event.payload;
 
+        if (planPath && event.payload.planPath !== planPath) return;
+
         console.log(`File changed: ${fileType} - ${path}`);
 
         if (fileType === 'plan' && onPlanChange) {
           onPlanChange(path);
+        } else if (fileType === 'include' && onPlanChange) {
+          onPlanChange(event.payload.planPath);
         } else if (fileType === 'layout' && onLayoutChange) {
           onLayoutChange(path);
+        } else if (fileType === 'reference' && onReferenceChange) {
+          onReferenceChange(event.payload);
         }
       });
+
+      unlistenLifecycle = await listen<PlanLifecycleEvent>('plan-lifecycle', (event) => {
+        if (planPath && event.payload.planPath !== planPath) return;
+
+        console.log(`Plan ${event.payload.kind}: ${event.payload.planPath}`);
+        onPlanLifecycle?.(event.payload);
+      });
     };
 
     setupListener();
@@ -53,29 +135,54 @@ export function useFileWatcher({
       if (unlisten) {
         unlisten();
       }
+      if (unlistenLifecycle) {
+        unlistenLifecycle();
+      }
     };
-  }, [onPlanChange, onLayoutChange, enabled]);
+  }, [onPlanChange, onLayoutChange, onReferenceChange, onPlanLifecycle, planPath, enabled]);
 }
 
 /**
- * Start watching a plan file and its layout
+ * Start watching a plan file and its layout for this window
+ * Replaces this window's previous watch; other windows keep theirs
  */
 export async function startWatching(planPath: string): Promise<void> {
   await invoke('start_watching', { planPath });
 }
 
 /**
- * Stop watching files
+ * Stop watching files for this window
  */
 export async function stopWatching(): Promise<void> {
   await invoke('stop_watching');
 }
 
 /**
- * Get the currently watched plan path
+ * Get the plan path this window is watching
  */
 export async function getWatchedPlan(): Promise<string | null> {
   return invoke<string | null>('get_watched_plan');
 }
 
+/**
+ * Add a plan to the watched set without affecting other watches
+ */
+export async function addWatchedPlan(planPath: string, window?: string): Promise<void> {
+  await invoke('add_watched_plan', { planPath, window });
+}
+
+/**
+ * Remove a plan from the watched set (only for `window` when given)
+ */
+export async function removeWatchedPlan(planPath: string, window?: string): Promise<void> {
+  await invoke('remove_watched_plan', { planPath, window });
+}
+
+/**
+ * List every watched plan and the windows watching it
+ */
+export async function listWatchedPlans(): Promise<WatchInfo[]> {
+  return invoke<WatchInfo[]>('list_watched_plans');
+}
+
 export default useFileWatcher;
@@ -2,14 +2,18 @@ import { describe, it, expect } from 'vitest';
 import { readFileSync } from 'fs';
 import { join } from 'path';
 import { parsePlan, validatePlan } from '../parser';
-import { serializePlan, computePlanHash } from '../serializer';
+import { serializePlan, computePlanHash, removeTask } from '../serializer';
+import { fromNativePlan } from '../native';
 import {
   PATTERNS,
   parseCheckboxStatus,
   statusToCheckbox,
+  combinedStatus,
   extractTaskId,
   extractDependencies,
   cleanTaskContent,
+  isCrossPlanDependency,
+  splitFrontMatter,
 } from '../schema';
 
 const fixturesDir = join(__dirname, 'fixtures');
@@ -44,6 +48,16 @@ describe('Schema utilities', () => {
       expect(parseCheckboxStatus('x')).toBe('completed');
       expect(parseCheckboxStatus('X')).toBe('completed');
       expect(parseCheckboxStatus('-')).toBe('in_progress');
+      expect(parseCheckboxStatus('!')).toBe('blocked');
+      expect(parseCheckboxStatus('~')).toBe('cancelled');
+    });
+  });
+
+  describe('PATTERNS.CHECKBOX', () => {
+    it('matches every checkbox mark, with or without the bullet', () => {
+      expect('- [!] Deploy'.match(PATTERNS.CHECKBOX)?.[1]).toBe('!');
+      expect('[~] Deploy'.match(PATTERNS.CHECKBOX)?.[1]).toBe('~');
+      expect('[?] Deploy'.match(PATTERNS.CHECKBOX)).toBeNull();
     });
   });
 
@@ -52,6 +66,18 @@ describe('Schema utilities', () => {
       expect(statusToCheckbox('pending')).toBe(' ');
       expect(statusToCheckbox('completed')).toBe('x');
       expect(statusToCheckbox('in_progress')).toBe('-');
+      expect(statusToCheckbox('blocked')).toBe('!');
+      expect(statusToCheckbox('cancelled')).toBe('~');
+    });
+  });
+
+  describe('combinedStatus', () => {
+    it('rolls task statuses up like the backend', () => {
+      expect(combinedStatus([])).toBe('pending');
+      expect(combinedStatus(['completed', 'cancelled'])).toBe('completed');
+      expect(combinedStatus(['cancelled', 'cancelled'])).toBe('cancelled');
+      expect(combinedStatus(['blocked', 'pending'])).toBe('blocked');
+      expect(combinedStatus(['blocked', 'completed'])).toBe('in_progress');
     });
   });
 
@@ -67,6 +93,14 @@ describe('Schema utilities', () => {
       expect(extractDependencies('Task (depends: t1, t2) (id: t3)')).toEqual(['t1', 't2']);
       expect(extractDependencies('Task (id: t1)')).toEqual([]);
     });
+
+    it('extracts cross-plan dependencies', () => {
+      expect(
+        extractDependencies('Task (depends: t1, ../backend/plan.md#t12) (id: t3)')
+      ).toEqual(['t1', '../backend/plan.md#t12']);
+      expect(isCrossPlanDependency('../backend/plan.md#t12')).toBe(true);
+      expect(isCrossPlanDependency('t1')).toBe(false);
+    });
   });
 
   describe('cleanTaskContent', () => {
@@ -75,6 +109,17 @@ describe('Schema utilities', () => {
       expect(cleanTaskContent('Simple task (id: t1)')).toBe('Simple task');
     });
   });
+
+  describe('splitFrontMatter', () => {
+    it('splits a closed front matter block from the document', () => {
+      expect(splitFrontMatter('---\nagent: codex\n...\n# Plan: A\n')).toEqual({
+        frontMatter: '---\nagent: codex\n...',
+        body: '# Plan: A\n',
+      });
+      expect(splitFrontMatter('---\nagent: codex\n')).toEqual({ body: '---\nagent: codex\n' });
+      expect(splitFrontMatter('# Plan: A\n---\n')).toEqual({ body: '# Plan: A\n---\n' });
+    });
+  });
 });
 
 describe('Parser', () => {
@@ -138,6 +183,55 @@ describe('Parser', () => {
       expect(result.errors).toContain('Duplicate task ID: t1');
     });
 
+    it('parses in-progress, blocked and cancelled tasks', () => {
+      const markdown = `# Plan: Test
+
+## Phase 0 — Setup
+- [-] Build (id: t1)
+- [!] Deploy (id: t2)
+- [~] Announce (id: t3)
+`;
+      const result = parsePlan(markdown);
+
+      expect(result.success).toBe(true);
+      if (!result.success) return;
+
+      const tasks = result.doc.phases[0].tasks;
+      expect(tasks.map((t) => t.status)).toEqual(['in_progress', 'blocked', 'cancelled']);
+      expect(tasks[1].content).toBe('Deploy');
+      expect(result.doc.nodes[0].status).toBe('in_progress');
+    });
+
+    it('parses nested list items as subtasks', () => {
+      const markdown = `# Plan: Test
+
+## Phase 0 — Setup
+- [ ] Build (id: t1)
+  - [x] Compile (id: t2)
+    - [ ] Link (depends: t2) (id: t3)
+- [ ] Ship (id: t4)
+`;
+      const result = parsePlan(markdown);
+
+      expect(result.success).toBe(true);
+      if (!result.success) return;
+
+      const tasks = result.doc.phases[0].tasks;
+      expect(tasks.map((t) => t.id)).toEqual(['t1', 't4']);
+      expect(tasks[0].subtasks?.[0].id).toBe('t2');
+      expect(tasks[0].subtasks?.[0].subtasks?.[0].id).toBe('t3');
+
+      const parents = result.doc.nodes.map((n) => [n.id, n.parentId]);
+      expect(parents).toEqual([
+        ['phase_0', undefined],
+        ['t1', undefined],
+        ['t2', 't1'],
+        ['t3', 't2'],
+        ['t4', undefined],
+      ]);
+      expect(result.doc.edges).toHaveLength(1);
+    });
+
     it('detects invalid dependency references', () => {
       const markdown = `# Plan: Test
 
@@ -150,6 +244,27 @@ describe('Parser', () => {
       if (result.success) return;
       expect(result.errors[0]).toContain('unknown dependency');
     });
+
+    it('keeps cross-plan dependencies without creating edges for them', () => {
+      const markdown = `# Plan: Test
+
+## Phase 0 — Setup
+- [ ] Task one (id: t1)
+- [ ] Task two (depends: t1, ../backend/plan.md#t12) (id: t2)
+`;
+      const result = parsePlan(markdown);
+
+      expect(result.success).toBe(true);
+      if (!result.success) return;
+      expect(result.doc.phases[0].tasks[1].dependencies).toEqual([
+        't1',
+        '../backend/plan.md#t12',
+      ]);
+      expect(result.doc.edges).toEqual([{ id: 'edge_t1_t2', from: 't1', to: 't2' }]);
+      expect(serializePlan(result.doc, { title: 'Test' })).toContain(
+        '(depends: t1, ../backend/plan.md#t12) (id: t2)'
+      );
+    });
   });
 
   describe('validatePlan', () => {
@@ -220,6 +335,73 @@ describe('Serializer', () => {
     });
   });
 
+  describe('status round-trip', () => {
+    it('keeps blocked and cancelled tasks', () => {
+      const markdown = `# Plan: Test
+
+## Phase 0 — Setup
+- [x] Build (id: t1)
+- [!] Deploy (depends: t1) (id: t2)
+- [~] Announce (id: t3)
+`;
+      const result = parsePlan(markdown);
+      if (!result.success) throw new Error(result.errors.join(', '));
+
+      expect(serializePlan(result.doc, { title: result.title })).toBe(markdown);
+    });
+  });
+
+  describe('front matter', () => {
+    it('keeps front matter through parse-serialize', () => {
+      const markdown = `---
+agent: codex
+cwd: app
+---
+# Plan: Test
+
+## Phase 0 — Setup
+- [ ] Task one (id: t1)
+`;
+      const result = parsePlan(markdown);
+      expect(result.success).toBe(true);
+      if (!result.success) return;
+
+      expect(result.doc.frontMatter).toBe('---\nagent: codex\ncwd: app\n---');
+      expect(result.doc.phases).toHaveLength(1);
+      expect(serializePlan(result.doc, { title: result.title })).toBe(markdown);
+    });
+  });
+
+  describe('subtasks', () => {
+    const markdown = `# Plan: Test
+
+## Phase 0 — Setup
+- [ ] Build (id: t1)
+  - [x] Compile (id: t2)
+    - [ ] Link (depends: t2) (id: t3)
+- [ ] Ship (depends: t3) (id: t4)
+`;
+
+    it('keeps nesting through parse-serialize cycle', () => {
+      const result = parsePlan(markdown);
+      if (!result.success) throw new Error(result.errors.join(', '));
+
+      expect(serializePlan(result.doc, { title: result.title })).toBe(markdown);
+    });
+
+    it('removes a task together with its subtasks', () => {
+      const result = parsePlan(markdown);
+      if (!result.success) throw new Error(result.errors.join(', '));
+
+      const doc = removeTask(result.doc, 't1');
+      expect(doc.nodes.map((n) => n.id)).toEqual(['phase_0', 't4']);
+      expect(doc.edges).toHaveLength(0);
+      expect(serializePlan(doc, { title: result.title })).toBe(
+        '# Plan: Test\n\n## Phase 0 — Setup\n- [ ] Ship (depends: t3) (id: t4)\n'
+      );
+    });
+  });
+
   describe('computePlanHash', () => {
     it('produces consistent hashes', () => {
       const markdown = '# Plan: Test\n';
@@ -237,3 +419,56 @@ describe('Serializer', () => {
     });
   });
 });
+
+describe('Backend plans', () => {
+  describe('fromNativePlan', () => {
+    it('converts phases from the plan and its included files', () => {
+      const { doc, title } = fromNativePlan({
+        title: 'Big',
+        phases: [
+          {
+            id: 'phase_1',
+            number: '1',
+            name: 'Setup',
+            tasks: [
+              {
+                id: 't1',
+                content: 'Init',
+                status: 'completed',
+                subtasks: [{ id: 't5', content: 'Config', status: 'completed' }],
+              },
+            ],
+          },
+          {
+            id: 'phase_2',
+            number: '2',
+            name: 'Auth',
+            file: '/plans/phases/02-auth.md',
+            tasks: [
+              {
+                id: 't2',
+                content: 'Login',
+                status: 'blocked',
+                dependencies: ['t1', '../api/plan.md#t9'],
+              },
+            ],
+          },
+        ],
+      });
+
+      expect(title).toBe('Big');
+      expect(doc.phases.map((p) => p.title)).toEqual(['Phase 1 — Setup', 'Phase 2 — Auth']);
+      expect(doc.phases[1].file).toBe('/plans/phases/02-auth.md');
+      expect(doc.phases[1].tasks[0].dependencies).toEqual(['t1', '../api/plan.md#t9']);
+      expect(doc.nodes.map((n) => [n.id, n.status, n.phaseId, n.parentId])).toEqual([
+        ['phase_1', 'completed', undefined, undefined],
+        ['t1', 'completed', 'phase_1', undefined],
+        ['t5', 'completed', 'phase_1', 't1'],
+        ['phase_2', 'blocked', undefined, undefined],
+        ['t2', 'blocked', 'phase_2', undefined],
+      ]);
+      expect(doc.edges).toEqual([{ id: 'edge_t1_t2', from: 't1', to: 't2' }]);
+      expect(validatePlan(doc)).toEqual([]);
+    });
+  });
+});
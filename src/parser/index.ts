@@ -7,3 +7,4 @@
 export * from './schema';
 export * from './parser';
 export * from './serializer';
+export * from './native';
@@ -0,0 +1,96 @@
+/**
+ * Backend Plan Documents
+ *
+ * Converts plans as read by the Rust backend (`read_plan` and the plan edit
+ * commands) to the frontend's PlanDoc. The backend splices in phases from
+ * `<!-- include: ... -->` files and writes each edit back to the file it
+ * touches, so plans are loaded and changed through it instead of being
+ * re-serialized here.
+ */
+
+import type { PlanDoc, PlanPhase, PlanTask, PlanNode, PlanEdge, Status } from '../types';
+import { combinedStatus, isCrossPlanDependency } from './schema';
+
+/** A task as serialized by the backend */
+export interface NativeTask {
+  id: string;
+  content: string;
+  status: Status;
+  dependencies?: string[];
+  subtasks?: NativeTask[];
+}
+
+/** A phase as serialized by the backend */
+export interface NativePhase {
+  id: string;
+  number: string;
+  name: string;
+  tasks: NativeTask[];
+  /** Included file the phase comes from; absent for the plan's own phases */
+  file?: string;
+}
+
+/** A plan as serialized by the backend */
+export interface NativePlanDoc {
+  title: string;
+  phases: NativePhase[];
+}
+
+/** Convert a backend task and its subtasks */
+function toTask(task: NativeTask): PlanTask {
+  return {
+    id: task.id,
+    content: task.content,
+    status: task.status,
+    dependencies: task.dependencies?.length ? task.dependencies : undefined,
+    subtasks: task.subtasks?.length ? task.subtasks.map(toTask) : undefined,
+  };
+}
+
+/** Convert a backend plan to a PlanDoc with its canvas nodes and edges */
+export function fromNativePlan(native: NativePlanDoc): { doc: PlanDoc; title: string } {
+  const phases: PlanPhase[] = [];
+  const nodes: PlanNode[] = [];
+  const edges: PlanEdge[] = [];
+
+  for (const nativePhase of native.phases) {
+    const tasks = nativePhase.tasks.map(toTask);
+    phases.push({
+      id: nativePhase.id,
+      title: `Phase ${nativePhase.number} — ${nativePhase.name}`,
+      tasks,
+      ...(nativePhase.file ? { file: nativePhase.file } : {}),
+    });
+
+    nodes.push({
+      id: nativePhase.id,
+      type: 'phase',
+      label: nativePhase.name,
+      status: tasks.length > 0 ? combinedStatus(tasks.map((t) => t.status)) : 'pending',
+    });
+
+    const addTaskNodes = (task: PlanTask, parentId?: string) => {
+      nodes.push({
+        id: task.id,
+        type: 'task',
+        label: task.content,
+        status: task.status,
+        phaseId: nativePhase.id,
+        ...(parentId ? { parentId } : {}),
+      });
+
+      // Tasks in other plans have no node here
+      for (const dep of task.dependencies ?? []) {
+        if (isCrossPlanDependency(dep)) continue;
+        edges.push({ id: `edge_${dep}_${task.id}`, from: dep, to: task.id });
+      }
+
+      for (const subtask of task.subtasks ?? []) {
+        addTaskNodes(subtask, task.id);
+      }
+    };
+    tasks.forEach((task) => addTaskNodes(task));
+  }
+
+  return { doc: { phases, nodes, edges }, title: native.title };
+}
@@ -7,10 +7,20 @@
 import { unified } from 'unified';
 import remarkParse from 'remark-parse';
 import remarkGfm from 'remark-gfm';
-import { visit } from 'unist-util-visit';
-import type { Root, ListItem, Paragraph, Text } from 'mdast';
+import { visit, SKIP } from 'unist-util-visit';
+import type { Root, List, ListItem, Paragraph, Text } from 'mdast';
 import type { PlanDoc, PlanPhase, PlanTask, PlanNode, PlanEdge, Status } from '../types';
-import { PATTERNS, extractTaskId, extractDependencies, cleanTaskContent } from './schema';
+import {
+  PATTERNS,
+  extractTaskId,
+  extractDependencies,
+  cleanTaskContent,
+  parseCheckboxStatus,
+  combinedStatus,
+  isCrossPlanDependency,
+  splitFrontMatter,
+} from './schema';
+import { flattenTasks } from './serializer';
 
 export interface ParseResult {
   success: true;
@@ -43,14 +53,14 @@ function getTextContent(node: Paragraph | Text | ListItem): string {
   return '';
 }
 
-/** Parse a list item into a PlanTask */
+/** Parse a list item into a PlanTask, with its nested list items as subtasks */
 function parseListItem(item: ListItem): PlanTask | null {
   if (!item.children.length) return null;
 
   const firstChild = item.children[0];
   if (firstChild.type !== 'paragraph') return null;
 
-  const text = getTextContent(firstChild);
+  let text = getTextContent(firstChild);
   const id = extractTaskId(text);
 
   if (!id) return null; // Tasks must have IDs
@@ -60,21 +70,29 @@ function parseListItem(item: ListItem): PlanTask | null {
 
   if (checked === true) {
     status = 'completed';
-  } else if (checked === false) {
-    // Check for in-progress marker in text
-    if (text.includes('[-]') || text.match(/^\s*-\s*\[-\]/)) {
-      status = 'in_progress';
+  } else if (checked !== false) {
+    // GFM only knows [ ] and [x]; [-], [!] and [~] stay at the start of the text
+    const marker = text.match(PATTERNS.CHECKBOX);
+    if (marker) {
+      status = parseCheckboxStatus(marker[1]);
+      text = text.slice(marker[0].length);
     }
   }
 
   const dependencies = extractDependencies(text);
   const content = cleanTaskContent(text);
+  const subtasks = item.children
+    .filter((child): child is List => child.type === 'list')
+    .flatMap((list) => list.children)
+    .map(parseListItem)
+    .filter((task): task is PlanTask => task !== null);
 
   return {
     id,
     content,
     status,
     dependencies: dependencies.length > 0 ? dependencies : undefined,
+    subtasks: subtasks.length > 0 ? subtasks : undefined,
   };
 }
 
@@ -88,9 +106,12 @@ export function parsePlan(markdown: string): ParseOutput {
   let title = 'Untitled Plan';
   let currentPhase: PlanPhase | null = null;
 
+  // Front matter is carried through untouched
+  const { frontMatter, body } = splitFrontMatter(markdown);
+
   // Parse markdown to AST with GFM support for checkboxes
   const processor = unified().use(remarkParse).use(remarkGfm);
-  const tree = processor.parse(markdown) as Root;
+  const tree = processor.parse(body) as Root;
 
   visit(tree, (node) => {
     // Handle H1 - Plan title
@@ -138,39 +159,52 @@ export function parsePlan(markdown: string): ParseOutput {
 
     // Handle lists - contains tasks
     if (node.type === 'list' && currentPhase) {
+      const phaseId = currentPhase.id;
+      const addTaskNodes = (task: PlanTask, parentId?: string) => {
+        if (seenIds.has(task.id)) {
+          errors.push(`Duplicate task ID: ${task.id}`);
+        }
+        seenIds.add(task.id);
+
+        // Add task node
+        nodes.push({
+          id: task.id,
+          type: 'task',
+          label: task.content,
+          status: task.status,
+          phaseId,
+          ...(parentId ? { parentId } : {}),
+        });
+
+        // Add dependency edges; tasks in other plans have no node here
+        if (task.dependencies) {
+          for (const dep of task.dependencies) {
+            if (isCrossPlanDependency(dep)) continue;
+            edges.push({
+              id: `edge_${dep}_${task.id}`,
+              from: dep,
+              to: task.id,
+            });
+          }
+        }
+
+        for (const subtask of task.subtasks ?? []) {
+          addTaskNodes(subtask, task.id);
+        }
+      };
+
       for (const item of node.children) {
         if (item.type === 'listItem') {
           const task = parseListItem(item);
           if (task) {
-            if (seenIds.has(task.id)) {
-              errors.push(`Duplicate task ID: ${task.id}`);
-            }
-            seenIds.add(task.id);
-
             currentPhase.tasks.push(task);
-
-            // Add task node
-            nodes.push({
-              id: task.id,
-              type: 'task',
-              label: task.content,
-              status: task.status,
-              phaseId: currentPhase.id,
-            });
-
-            // Add dependency edges
-            if (task.dependencies) {
-              for (const dep of task.dependencies) {
-                edges.push({
-                  id: `edge_${dep}_${task.id}`,
-                  from: dep,
-                  to: task.id,
-                });
-              }
-            }
+            addTaskNodes(task);
           }
         }
       }
+
+      // Nested lists were read as subtasks of their items
+      return SKIP;
     }
   });
 
@@ -185,16 +219,7 @@ export function parsePlan(markdown: string): ParseOutput {
   for (const phase of phases) {
     const phaseNode = nodes.find((n) => n.id === phase.id);
     if (phaseNode && phase.tasks.length > 0) {
-      const allCompleted = phase.tasks.every((t) => t.status === 'completed');
-      const anyInProgress = phase.tasks.some(
-        (t) => t.status === 'in_progress' || t.status === 'completed'
-      );
-
-      if (allCompleted) {
-        phaseNode.status = 'completed';
-      } else if (anyInProgress) {
-        phaseNode.status = 'in_progress';
-      }
+      phaseNode.status = combinedStatus(phase.tasks.map((t) => t.status));
     }
   }
 
@@ -205,7 +230,7 @@ export function parsePlan(markdown: string): ParseOutput {
   return {
     success: true,
     title,
-    doc: { phases, nodes, edges },
+    doc: { ...(frontMatter ? { frontMatter } : {}), phases, nodes, edges },
   };
 }
 
@@ -222,7 +247,7 @@ export function validatePlan(doc: PlanDoc): string[] {
     seenIds.add(phase.id);
 
     // Check task IDs
-    for (const task of phase.tasks) {
+    for (const task of flattenTasks(phase.tasks)) {
       if (seenIds.has(task.id)) {
         errors.push(`Duplicate task ID: ${task.id}`);
       }
@@ -237,10 +262,10 @@ export function validatePlan(doc: PlanDoc): string[] {
 
   // Validate dependency references
   for (const phase of doc.phases) {
-    for (const task of phase.tasks) {
+    for (const task of flattenTasks(phase.tasks)) {
       if (task.dependencies) {
         for (const dep of task.dependencies) {
-          if (!seenIds.has(dep)) {
+          if (!isCrossPlanDependency(dep) && !seenIds.has(dep)) {
             errors.push(`Task "${task.id}" references unknown dependency "${dep}"`);
           }
         }
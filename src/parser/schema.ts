@@ -10,12 +10,22 @@
  * - [ ] Task description (id: <unique_id>)
  * - [x] Completed task (id: <unique_id>)
  * - [-] In-progress task (id: <unique_id>)
+ * - [!] Blocked task (id: <unique_id>)
+ * - [~] Cancelled task (id: <unique_id>)
+ *
+ * Front matter (optional, before the title) is kept as written:
+ * ---
+ * agent: codex
+ * ---
  *
  * Dependencies (optional, in task description):
  * - [ ] Task that depends on t1 (depends: t1) (id: t2)
  * - [ ] Task with multiple deps (depends: t1, t3) (id: t4)
+ * - [ ] Task in another plan (depends: ../backend/plan.md#t12) (id: t5)
  */
 
+import type { Status } from '../types';
+
 /** Regex patterns for parsing */
 export const PATTERNS = {
   /** Matches plan title: # Plan: <title> */
@@ -27,37 +37,60 @@ export const PATTERNS = {
   /** Matches task ID at end of line: (id: <id>) */
   TASK_ID: /\(id:\s*([a-zA-Z0-9_-]+)\)\s*$/,
 
-  /** Matches checkbox state: [ ], [x], [-] */
-  CHECKBOX: /^\s*-\s*\[([ xX-])\]\s*/,
+  /** Matches checkbox state: [ ], [x], [-], [!], [~] (the list bullet is optional) */
+  CHECKBOX: /^\s*(?:[-*+]\s+)?\[([ xX!~-])\]\s*/,
 
-  /** Matches dependencies: (depends: t1, t2, ...) */
-  DEPENDENCIES: /\(depends:\s*([a-zA-Z0-9_,\s-]+)\)/,
+  /** Matches dependencies: (depends: t1, ../other/plan.md#t2, ...) */
+  DEPENDENCIES: /\(depends:\s*([a-zA-Z0-9_,\s.\/#-]+)\)/,
 } as const;
 
 /** Parse checkbox state to Status */
-export function parseCheckboxStatus(char: string): 'pending' | 'in_progress' | 'completed' {
+export function parseCheckboxStatus(char: string): Status {
   switch (char.toLowerCase()) {
     case 'x':
       return 'completed';
     case '-':
       return 'in_progress';
+    case '!':
+      return 'blocked';
+    case '~':
+      return 'cancelled';
     default:
       return 'pending';
   }
 }
 
 /** Convert Status to checkbox character */
-export function statusToCheckbox(status: 'pending' | 'in_progress' | 'completed'): string {
+export function statusToCheckbox(status: Status): string {
   switch (status) {
     case 'completed':
       return 'x';
     case 'in_progress':
       return '-';
+    case 'blocked':
+      return '!';
+    case 'cancelled':
+      return '~';
     default:
       return ' ';
   }
 }
 
+/**
+ * Combined status of a group of tasks (a phase), as the backend computes it.
+ * Cancelled tasks don't hold a group open; a group is blocked when blocked
+ * tasks are all that keep it from starting.
+ */
+export function combinedStatus(statuses: Status[]): Status {
+  const open = statuses.filter((s) => s !== 'cancelled');
+  if (statuses.length === 0) return 'pending';
+  if (open.length === 0) return 'cancelled';
+  if (open.every((s) => s === 'completed')) return 'completed';
+  if (open.some((s) => s === 'in_progress' || s === 'completed')) return 'in_progress';
+  if (open.includes('blocked')) return 'blocked';
+  return 'pending';
+}
+
 /** Extract task ID from text */
 export function extractTaskId(text: string): string | null {
   const match = text.match(PATTERNS.TASK_ID);
@@ -71,6 +104,11 @@ export function extractDependencies(text: string): string[] {
   return match[1].split(',').map((d) => d.trim()).filter(Boolean);
 }
 
+/** Whether a dependency names a task in another plan (`path/to/plan.md#t12`) */
+export function isCrossPlanDependency(dep: string): boolean {
+  return dep.includes('#');
+}
+
 /** Remove ID and dependencies from task text to get clean content */
 export function cleanTaskContent(text: string): string {
   return text
@@ -78,3 +116,22 @@ export function cleanTaskContent(text: string): string {
     .replace(PATTERNS.DEPENDENCIES, '')
     .trim();
 }
+
+/**
+ * Split a leading front matter block (`---` ... `---` or `...`) from the document
+ * The block is returned as written, delimiters included; unclosed blocks aren't front matter
+ */
+export function splitFrontMatter(markdown: string): { frontMatter?: string; body: string } {
+  const lines = markdown.split('\n');
+  if (lines[0]?.trimEnd() !== '---') return { body: markdown };
+
+  const end = lines.findIndex(
+    (line, i) => i > 0 && (line.trimEnd() === '---' || line.trimEnd() === '...')
+  );
+  if (end === -1) return { body: markdown };
+
+  return {
+    frontMatter: lines.slice(0, end + 1).join('\n'),
+    body: lines.slice(end + 1).join('\n'),
+  };
+}
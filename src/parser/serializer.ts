@@ -15,10 +15,10 @@ export interface SerializeOptions {
   trailingNewline?: boolean;
 }
 
-/** Serialize a single task to markdown */
-function serializeTask(task: PlanTask): string {
+/** Serialize a task and its subtasks to markdown, two spaces of indentation per level */
+function serializeTask(task: PlanTask, depth = 0): string {
   const checkbox = statusToCheckbox(task.status);
-  let line = `- [${checkbox}] ${task.content}`;
+  let line = `${'  '.repeat(depth)}- [${checkbox}] ${task.content}`;
 
   // Add dependencies if present
   if (task.dependencies && task.dependencies.length > 0) {
@@ -28,7 +28,8 @@ function serializeTask(task: PlanTask): string {
   // Add ID
   line += ` (id: ${task.id})`;
 
-  return line;
+  const subtasks = (task.subtasks ?? []).map((subtask) => serializeTask(subtask, depth + 1));
+  return [line, ...subtasks].join('\n');
 }
 
 /** Serialize a phase to markdown */
@@ -52,6 +53,11 @@ export function serializePlan(doc: PlanDoc, options: SerializeOptions = {}): str
 
   const lines: string[] = [];
 
+  // Front matter, as it was parsed
+  if (doc.frontMatter) {
+    lines.push(doc.frontMatter);
+  }
+
   // Title
   lines.push(`# Plan: ${title}`);
   lines.push('');
@@ -75,13 +81,43 @@ export function serializePlan(doc: PlanDoc, options: SerializeOptions = {}): str
   return result;
 }
 
+/** Every task at any depth, parents before their subtasks */
+export function flattenTasks(tasks: PlanTask[]): PlanTask[] {
+  return tasks.flatMap((task) => [task, ...flattenTasks(task.subtasks ?? [])]);
+}
+
+/** Apply `update` to every task at any depth (immutable) */
+export function mapTasks(tasks: PlanTask[], update: (task: PlanTask) => PlanTask): PlanTask[] {
+  return tasks.map((task) => {
+    const updated = update(task);
+    return updated.subtasks
+      ? { ...updated, subtasks: mapTasks(updated.subtasks, update) }
+      : updated;
+  });
+}
+
+/** Remove tasks, with their subtasks, at any depth (immutable) */
+export function removeTasks(tasks: PlanTask[], taskIds: string[]): PlanTask[] {
+  return tasks
+    .filter((task) => !taskIds.includes(task.id))
+    .map((task) =>
+      task.subtasks ? { ...task, subtasks: removeTasks(task.subtasks, taskIds) } : task
+    );
+}
+
+/** IDs of a task's subtasks at any depth, from the plan's nodes */
+export function subtaskIds(doc: PlanDoc, taskId: string): string[] {
+  const children = doc.nodes.filter((node) => node.parentId === taskId).map((node) => node.id);
+  return children.flatMap((id) => [id, ...subtaskIds(doc, id)]);
+}
+
 /** Update a single task's status in a PlanDoc (immutable) */
 export function updateTaskStatus(doc: PlanDoc, taskId: string, status: Status): PlanDoc {
   return {
     ...doc,
     phases: doc.phases.map((phase) => ({
       ...phase,
-      tasks: phase.tasks.map((task) =>
+      tasks: mapTasks(phase.tasks, (task) =>
         task.id === taskId ? { ...task, status } : task
       ),
     })),
@@ -97,7 +133,7 @@ export function updateTaskContent(doc: PlanDoc, taskId: string, content: string)
     ...doc,
     phases: doc.phases.map((phase) => ({
       ...phase,
-      tasks: phase.tasks.map((task) =>
+      tasks: mapTasks(phase.tasks, (task) =>
         task.id === taskId ? { ...task, content } : task
       ),
     })),
@@ -148,16 +184,17 @@ export function addTask(
   };
 }
 
-/** Remove a task from a PlanDoc (immutable) */
+/** Remove a task and its subtasks from a PlanDoc (immutable) */
 export function removeTask(doc: PlanDoc, taskId: string): PlanDoc {
+  const removed = [taskId, ...subtaskIds(doc, taskId)];
   return {
     ...doc,
     phases: doc.phases.map((phase) => ({
       ...phase,
-      tasks: phase.tasks.filter((task) => task.id !== taskId),
+      tasks: removeTasks(phase.tasks, removed),
     })),
-    nodes: doc.nodes.filter((node) => node.id !== taskId),
-    edges: doc.edges.filter((edge) => edge.from !== taskId && edge.to !== taskId),
+    nodes: doc.nodes.filter((node) => !removed.includes(node.id)),
+    edges: doc.edges.filter((edge) => !removed.includes(edge.from) && !removed.includes(edge.to)),
   };
 }
 
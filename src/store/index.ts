@@ -1,4 +1,4 @@
-export { usePlanStore, default as planStore } from './planStore';
+export { usePlanStore, default as planStore, readPlan } from './planStore';
 export { useChatStore, default as chatStore } from './chatStore';
 export { useAgentStore, default as agentStore } from './agentStore';
 export { usePreferencesStore, default as preferencesStore } from './preferencesStore';
@@ -4,11 +4,12 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import { readTextFile, writeTextFile } from '@tauri-apps/plugin-fs';
 import type { PlanDoc, LayoutMap, LayoutFile, PlanNode, Status } from '../types';
 import { debounce } from '../lib/utils';
-import { serializePlan, computePlanHash } from '../parser/serializer';
-import { parsePlan } from '../parser/parser';
+import { mapTasks, removeTasks, subtaskIds } from '../parser/serializer';
+import { validatePlan, type ParseOutput } from '../parser/parser';
+import { fromNativePlan, type NativePlanDoc } from '../parser/native';
+import { combinedStatus } from '../parser/schema';
 
 /** Debounce delay for auto-save (ms) */
 const AUTOSAVE_DELAY = 1000;
@@ -19,10 +20,19 @@ interface MergeResult {
   removedNodes: string[];
 }
 
+/** Result of a backend plan edit command */
+interface PlanEditResult {
+  plan: NativePlanDoc;
+  /** Content hash of the new plan.md */
+  contentHash: string;
+  layout: MergeResult;
+}
+
 interface NodeInfo {
   id: string;
   nodeType: string;
   phaseId: string | null;
+  parentId?: string;
 }
 
 /** Settings for reload behavior */
@@ -37,7 +47,8 @@ interface PlanState {
   planTitle: string;
   plan: PlanDoc | null;
   layouts: LayoutMap;
-  planHash: string;
+  /** The saved layout was arranged for an earlier structure of the plan */
+  isLayoutStale: boolean;
 
   // UI state
   selectedNodeId: string | null;
@@ -52,7 +63,7 @@ interface PlanState {
   settings: ReloadSettings;
 
   // Actions
-  setPlan: (plan: PlanDoc, planPath: string, planHash: string, title?: string) => void;
+  setPlan: (plan: PlanDoc, planPath: string, title?: string) => void;
   setLayouts: (layouts: LayoutMap) => void;
   updateLayoutsAndSave: (layouts: LayoutMap) => void;
   setSelectedNode: (nodeId: string | null) => void;
@@ -63,11 +74,13 @@ interface PlanState {
   setSettings: (settings: Partial<ReloadSettings>) => void;
   notifyExternalChange: (type: 'plan' | 'layout') => void;
   dismissExternalChanges: () => void;
+  dismissStaleLayout: () => void;
 
   // Async actions
   loadLayout: (planPath: string) => Promise<void>;
   saveLayout: () => Promise<void>;
-  mergeLayout: (plan: PlanDoc, planPath: string, planHash: string, title?: string) => Promise<void>;
+  mergeLayout: (plan: PlanDoc, planPath: string, title?: string) => Promise<void>;
+  regenerateLayout: () => Promise<void>;
   reloadPlan: () => Promise<void>;
   reloadLayout: () => Promise<void>;
 }
@@ -78,37 +91,42 @@ function toNodeInfo(node: PlanNode): NodeInfo {
     id: node.id,
     nodeType: node.type,
     phaseId: node.phaseId ?? null,
+    parentId: node.parentId,
   };
 }
 
+/**
+ * Read a plan through the backend, with the phases of the files it includes
+ */
+export async function readPlan(planPath: string): Promise<ParseOutput> {
+  const native = await invoke<NativePlanDoc>('read_plan', { planPath });
+  const { doc, title } = fromNativePlan(native);
+  const errors = validatePlan(doc);
+  return errors.length > 0 ? { success: false, errors } : { success: true, doc, title };
+}
+
 /** Debounced save function (created once per store instance) */
 let debouncedSave: (() => void) | null = null;
 
 export const usePlanStore = create<PlanState>((set, get) => {
-  // Create debounced save function that saves both layout and markdown
+  // Create debounced save function for the layout; plan.md is changed by
+  // the backend edit commands, which also write the files it includes
   const performSave = async () => {
-    const { planPath, plan, planTitle, layouts } = get();
+    const { planPath, plan, layouts } = get();
     if (!planPath || !plan) return;
 
     set({ isSaving: true });
     try {
-      // Serialize and save markdown
-      const markdown = serializePlan(plan, { title: planTitle });
-      const newHash = computePlanHash(markdown);
-
-      await writeTextFile(planPath, markdown);
-
-      // Save layout
+      // Save layout; the backend hashes the plan it was saved for
       const layoutFile: LayoutFile = {
         version: 1,
-        planHash: newHash,
         layouts,
         lastModified: new Date().toISOString(),
       };
       await invoke('write_layout', { planPath, layout: layoutFile });
 
-      set({ isSaving: false, isDirty: false, planHash: newHash });
-      console.log('Plan and layout auto-saved');
+      set({ isSaving: false, isDirty: false });
+      console.log('Layout auto-saved');
     } catch (err) {
       console.error('Auto-save failed:', err);
       set({
@@ -120,13 +138,40 @@ export const usePlanStore = create<PlanState>((set, get) => {
 
   debouncedSave = debounce(performSave, AUTOSAVE_DELAY);
 
+  // Apply an edit through the backend and take the plan it wrote; positions
+  // on the canvas win over the saved layout, except for nodes the edit added
+  const applyEdit = async (command: string, args: Record<string, unknown>) => {
+    const { planPath } = get();
+    if (!planPath) return;
+
+    set({ isSaving: true });
+    try {
+      const result = await invoke<PlanEditResult>(command, { planPath, ...args });
+      const { doc, title } = fromNativePlan(result.plan);
+      const { layouts } = get();
+      const merged = { ...result.layout.layout.layouts };
+      for (const id of Object.keys(merged)) {
+        if (layouts[id] && !result.layout.addedNodes.includes(id)) {
+          merged[id] = layouts[id];
+        }
+      }
+      set({ plan: doc, planTitle: title, layouts: merged, isSaving: false });
+    } catch (err) {
+      console.error(`Failed to ${command.replace(/_/g, ' ')}:`, err);
+      set({ isSaving: false });
+      // Drop the change shown on the canvas
+      await get().reloadPlan();
+      set({ error: err instanceof Error ? err.message : String(err) });
+    }
+  };
+
   return {
     // Initial state
     planPath: null,
     planTitle: 'Untitled Plan',
     plan: null,
     layouts: {},
-    planHash: '',
+    isLayoutStale: false,
     hasExternalChanges: false,
     externalChangeType: null,
     settings: {
@@ -139,8 +184,8 @@ export const usePlanStore = create<PlanState>((set, get) => {
     error: null,
 
     // Synchronous actions
-    setPlan: (plan, planPath, planHash, title = 'Untitled Plan') => {
-      set({ plan, planPath, planHash, planTitle: title, error: null });
+    setPlan: (plan, planPath, title = 'Untitled Plan') => {
+      set({ plan, planPath, planTitle: title, isLayoutStale: false, error: null });
     },
 
     setLayouts: (layouts) => {
@@ -173,7 +218,7 @@ export const usePlanStore = create<PlanState>((set, get) => {
       // Update the task in phases
       const updatedPhases = plan.phases.map((phase) => ({
         ...phase,
-        tasks: phase.tasks.map((task) =>
+        tasks: mapTasks(phase.tasks, (task) =>
           task.id === nodeId ? { ...task, status } : task
         ),
       }));
@@ -192,13 +237,9 @@ export const usePlanStore = create<PlanState>((set, get) => {
         if (node.type === 'phase') {
           const phase = phasesWithStatus.find((p) => p.id === node.id);
           if (phase && phase.tasks.length > 0) {
-            const allCompleted = phase.tasks.every((t) => t.status === 'completed');
-            const anyInProgress = phase.tasks.some(
-              (t) => t.status === 'in_progress' || t.status === 'completed'
-            );
             return {
               ...node,
-              status: allCompleted ? 'completed' : anyInProgress ? 'in_progress' : 'pending',
+              status: combinedStatus(phase.tasks.map((t) => t.status)),
             } as PlanNode;
           }
         }
@@ -212,12 +253,12 @@ export const usePlanStore = create<PlanState>((set, get) => {
           phases: phasesWithStatus,
           nodes: nodesWithPhaseStatus,
         },
-        isDirty: true,
       });
 
-      // Trigger auto-save if we have a file path
-      if (planPath && debouncedSave) {
-        debouncedSave();
+      // Phases take their status from their tasks; only tasks are written
+      const node = plan.nodes.find((n) => n.id === nodeId);
+      if (planPath && node?.type === 'task') {
+        applyEdit('set_task_status', { taskId: nodeId, status });
       }
     },
 
@@ -229,12 +270,16 @@ export const usePlanStore = create<PlanState>((set, get) => {
       const nodeToDelete = plan.nodes.find((n) => n.id === nodeId);
       if (!nodeToDelete) return;
 
-      // Remove node from nodes array
-      const updatedNodes = plan.nodes.filter((n) => n.id !== nodeId);
+      // A task goes together with its subtasks
+      const removedIds =
+        nodeToDelete.type === 'task' ? [nodeId, ...subtaskIds(plan, nodeId)] : [nodeId];
 
-      // Remove edges involving this node
+      // Remove nodes from nodes array
+      const updatedNodes = plan.nodes.filter((n) => !removedIds.includes(n.id));
+
+      // Remove edges involving these nodes
       const updatedEdges = plan.edges.filter(
-        (e) => e.from !== nodeId && e.to !== nodeId
+        (e) => !removedIds.includes(e.from) && !removedIds.includes(e.to)
       );
 
       // If it's a task, remove from phase
@@ -242,7 +287,7 @@ export const usePlanStore = create<PlanState>((set, get) => {
       if (nodeToDelete.type === 'task') {
         updatedPhases = plan.phases.map((phase) => ({
           ...phase,
-          tasks: phase.tasks.filter((t) => t.id !== nodeId),
+          tasks: removeTasks(phase.tasks, removedIds),
         }));
       }
 
@@ -275,19 +320,17 @@ export const usePlanStore = create<PlanState>((set, get) => {
           },
           layouts: updatedLayouts,
           selectedNodeId: null,
-          isDirty: true,
         });
 
-        // Trigger auto-save
-        if (planPath && debouncedSave) {
-          debouncedSave();
+        if (planPath) {
+          applyEdit('remove_phase', { phaseId: nodeId });
         }
         return;
       }
 
-      // Remove layout for deleted node
+      // Remove layouts for deleted nodes
       const updatedLayouts = { ...layouts };
-      delete updatedLayouts[nodeId];
+      removedIds.forEach((id) => delete updatedLayouts[id]);
 
       const { planPath } = get();
       set({
@@ -299,12 +342,10 @@ export const usePlanStore = create<PlanState>((set, get) => {
         },
         layouts: updatedLayouts,
         selectedNodeId: null,
-        isDirty: true,
       });
 
-      // Trigger auto-save
-      if (planPath && debouncedSave) {
-        debouncedSave();
+      if (planPath) {
+        applyEdit('remove_task', { taskId: nodeId });
       }
     },
 
@@ -344,7 +385,7 @@ export const usePlanStore = create<PlanState>((set, get) => {
       // Calculate position for new task
       const phaseLayout = layouts[phaseId];
       const tasksInPhase = plan.nodes.filter(
-        (n) => n.type === 'task' && n.phaseId === phaseId
+        (n) => n.type === 'task' && n.phaseId === phaseId && !n.parentId
       );
       const taskCount = tasksInPhase.length;
       const col = taskCount % 3;
@@ -376,12 +417,10 @@ export const usePlanStore = create<PlanState>((set, get) => {
           [newId]: newLayout,
         },
         selectedNodeId: newId,
-        isDirty: true,
       });
 
-      // Trigger auto-save
-      if (planPath && debouncedSave) {
-        debouncedSave();
+      if (planPath) {
+        applyEdit('add_task', { phaseId, content, id: newId });
       }
     },
 
@@ -391,7 +430,7 @@ export const usePlanStore = create<PlanState>((set, get) => {
         planTitle: 'Untitled Plan',
         plan: null,
         layouts: {},
-        planHash: '',
+        isLayoutStale: false,
         selectedNodeId: null,
         isDirty: false,
         hasExternalChanges: false,
@@ -431,12 +470,20 @@ export const usePlanStore = create<PlanState>((set, get) => {
       set({ hasExternalChanges: false, externalChangeType: null });
     },
 
+    dismissStaleLayout: () => {
+      set({ isLayoutStale: false });
+    },
+
   // Async actions
   loadLayout: async (planPath) => {
     set({ isLoading: true, error: null });
     try {
       const layoutFile = await invoke<LayoutFile>('read_layout', { planPath });
-      set({ layouts: layoutFile.layouts, isLoading: false });
+      set({
+        layouts: layoutFile.layouts,
+        isLayoutStale: !!layoutFile.stale,
+        isLoading: false,
+      });
     } catch (err) {
       set({
         error: err instanceof Error ? err.message : String(err),
@@ -446,14 +493,13 @@ export const usePlanStore = create<PlanState>((set, get) => {
   },
 
   saveLayout: async () => {
-    const { planPath, layouts, planHash } = get();
+    const { planPath, layouts } = get();
     if (!planPath) return;
 
     set({ isSaving: true, error: null });
     try {
       const layoutFile: LayoutFile = {
         version: 1,
-        planHash,
         layouts,
         lastModified: new Date().toISOString(),
       };
@@ -467,22 +513,21 @@ export const usePlanStore = create<PlanState>((set, get) => {
     }
   },
 
-  mergeLayout: async (plan, planPath, planHash, title = 'Untitled Plan') => {
+  mergeLayout: async (plan, planPath, title = 'Untitled Plan') => {
     set({ isLoading: true, error: null });
     try {
       const nodes = plan.nodes.map(toNodeInfo);
       const result = await invoke<MergeResult>('merge_layout', {
         planPath,
         nodes,
-        planHash,
       });
 
       set({
         plan,
         planPath,
-        planHash,
         planTitle: title,
         layouts: result.layout.layouts,
+        isLayoutStale: !!result.layout.stale,
         isLoading: false,
       });
 
@@ -498,16 +543,16 @@ export const usePlanStore = create<PlanState>((set, get) => {
       try {
         const nodes = plan.nodes.map(toNodeInfo);
         const layoutFile = await invoke<LayoutFile>('generate_layout', {
+          planPath,
           nodes,
-          planHash,
         });
 
         set({
           plan,
           planPath,
-          planHash,
           planTitle: title,
           layouts: layoutFile.layouts,
+          isLayoutStale: false,
           isLoading: false,
         });
       } catch (genErr) {
@@ -519,14 +564,34 @@ export const usePlanStore = create<PlanState>((set, get) => {
     }
   },
 
+  regenerateLayout: async () => {
+    const { plan, planPath } = get();
+    if (!plan || !planPath) return;
+
+    set({ isLoading: true, error: null });
+    try {
+      const nodes = plan.nodes.map(toNodeInfo);
+      const layoutFile = await invoke<LayoutFile>('generate_layout', {
+        planPath,
+        nodes,
+      });
+      await invoke('write_layout', { planPath, layout: layoutFile });
+      set({ layouts: layoutFile.layouts, isLayoutStale: false, isLoading: false });
+    } catch (err) {
+      set({
+        error: err instanceof Error ? err.message : String(err),
+        isLoading: false,
+      });
+    }
+  },
+
   reloadPlan: async () => {
     const { planPath } = get();
     if (!planPath) return;
 
     set({ isLoading: true, error: null });
     try {
-      const content = await readTextFile(planPath);
-      const result = parsePlan(content);
+      const result = await readPlan(planPath);
 
       if (!result.success) {
         set({
@@ -536,7 +601,6 @@ export const usePlanStore = create<PlanState>((set, get) => {
         return;
       }
 
-      const newHash = computePlanHash(content);
       const nodes = result.doc.nodes.map(toNodeInfo);
 
       // Merge with existing layout to preserve positions
@@ -544,14 +608,13 @@ export const usePlanStore = create<PlanState>((set, get) => {
         const mergeResult = await invoke<MergeResult>('merge_layout', {
           planPath,
           nodes,
-          planHash: newHash,
         });
 
         set({
           plan: result.doc,
           planTitle: result.title,
-          planHash: newHash,
           layouts: mergeResult.layout.layouts,
+          isLayoutStale: !!mergeResult.layout.stale,
           isLoading: false,
           hasExternalChanges: false,
           externalChangeType: null,
@@ -564,7 +627,6 @@ export const usePlanStore = create<PlanState>((set, get) => {
         set({
           plan: result.doc,
           planTitle: result.title,
-          planHash: newHash,
           isLoading: false,
           hasExternalChanges: false,
           externalChangeType: null,
@@ -588,7 +650,7 @@ export const usePlanStore = create<PlanState>((set, get) => {
 
       set({
         layouts: layoutFile.layouts,
-        planHash: layoutFile.planHash,
+        isLayoutStale: !!layoutFile.stale,
         isLoading: false,
         hasExternalChanges: false,
         externalChangeType: null,
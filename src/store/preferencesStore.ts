@@ -4,7 +4,7 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { LaunchConfig, UserPreferences } from '../types';
+import type { LaunchConfig, PlanDefaults, UserPreferences } from '../types';
 
 interface PreferencesState {
   // Launch config (from CLI args)
@@ -15,12 +15,16 @@ interface PreferencesState {
   preferences: UserPreferences | null;
   isLoadingPreferences: boolean;
 
+  // Defaults for the open plan
+  planDefaults: (PlanDefaults & { planPath: string }) | null;
+
   // Actions
   loadLaunchConfig: () => Promise<void>;
   loadPreferences: () => Promise<void>;
   setLastPlan: (planPath: string) => Promise<void>;
   setPlanAgent: (planPath: string, agent: string) => Promise<void>;
   getPlanAgent: (planPath: string) => Promise<string | null>;
+  loadPlanDefaults: (planPath: string) => Promise<PlanDefaults>;
   setDefaultAgent: (agent: string) => Promise<void>;
   removeRecentPlan: (planPath: string) => Promise<void>;
 }
@@ -31,6 +35,7 @@ export const usePreferencesStore = create<PreferencesState>((set, get) => ({
   isLoadingConfig: false,
   preferences: null,
   isLoadingPreferences: false,
+  planDefaults: null,
 
   loadLaunchConfig: async () => {
     set({ isLoadingConfig: true });
@@ -104,6 +109,17 @@ export const usePreferencesStore = create<PreferencesState>((set, get) => ({
     }
   },
 
+  loadPlanDefaults: async (planPath: string) => {
+    let defaults: PlanDefaults = {};
+    try {
+      defaults = await invoke<PlanDefaults>('get_plan_defaults', { planPath });
+    } catch (err) {
+      console.error('Failed to get plan defaults:', err);
+    }
+    set({ planDefaults: { ...defaults, planPath } });
+    return defaults;
+  },
+
   setDefaultAgent: async (agent: string) => {
     try {
       await invoke('set_default_agent', { agent });
@@ -2,8 +2,8 @@
  * Shared types for Plan Visualizer
  */
 
-/** Task/Phase status */
-export type Status = 'pending' | 'in_progress' | 'completed';
+/** Task/Phase status; blocked is `[!]`, cancelled is `[~]` */
+export type Status = 'pending' | 'in_progress' | 'completed' | 'blocked' | 'cancelled';
 
 /** A task within a phase */
 export interface PlanTask {
@@ -11,6 +11,7 @@ export interface PlanTask {
   content: string;
   status: Status;
   dependencies?: string[]; // IDs of tasks this depends on
+  subtasks?: PlanTask[]; // Nested list items under this task
 }
 
 /** A phase containing tasks */
@@ -18,6 +19,7 @@ export interface PlanPhase {
   id: string;
   title: string;
   tasks: PlanTask[];
+  file?: string; // Included file the phase comes from, for plans read by the backend
 }
 
 /** A node in the visual plan (can be a phase or task) */
@@ -27,6 +29,7 @@ export interface PlanNode {
   label: string;
   status: Status;
   phaseId?: string; // For tasks, the parent phase ID
+  parentId?: string; // For subtasks, the parent task ID
 }
 
 /** An edge representing a dependency between nodes */
@@ -38,6 +41,7 @@ export interface PlanEdge {
 
 /** The complete plan document structure */
 export interface PlanDoc {
+  frontMatter?: string; // Raw front matter block, delimiters included
   phases: PlanPhase[];
   nodes: PlanNode[];
   edges: PlanEdge[];
@@ -57,9 +61,10 @@ export type LayoutMap = Record<string, NodeLayout>;
 /** Complete layout file structure */
 export interface LayoutFile {
   version: number;
-  planHash: string; // Hash of plan.md to detect changes
+  planHash?: string; // Structural hash of plan.md, always set by the backend on write
   layouts: LayoutMap;
   lastModified: string; // ISO timestamp
+  stale?: boolean; // Set by the backend when the plan's structure changed since it was saved
 }
 
 /** Chat message role */
@@ -122,7 +127,7 @@ export interface CredentialStatus {
   error?: string;
 }
 
-/** Launch configuration from CLI */
+/** Launch configuration; only what was given on the command line */
 export interface LaunchConfig {
   planPath?: string;
   agent?: string;
@@ -130,6 +135,12 @@ export interface LaunchConfig {
   fromCli: boolean;
 }
 
+/** Agent and working directory to use for a plan, from preferences and its front matter */
+export interface PlanDefaults {
+  agent?: string;
+  cwd?: string;
+}
+
 /** Per-plan preferences */
 export interface PlanPreferences {
   lastAgent?: string;